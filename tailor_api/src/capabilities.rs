@@ -0,0 +1,129 @@
+use std::{fmt, io, str::FromStr};
+
+use crate::Switch;
+
+/// Hardware features of the device as detected by tailord.
+///
+/// Clients use this to hide features that aren't supported.
+/// Unknown values are empty instead of missing because
+/// D-Bus structs have a fixed number of fields.
+#[derive(
+    Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize, zvariant::Type,
+)]
+pub struct Capabilities {
+    /// `Clevo` or `Uniwill`.
+    pub platform: String,
+    /// Version of the `tuxedo_io` kernel module.
+    pub driver_version: String,
+    /// Number of fans controlled by tailord.
+    pub fans: u8,
+    /// Fans that report a temperature, indexed from 0.
+    pub temperature_sensors: Vec<u8>,
+    /// Keyboard color zones, see [`KeyboardZone`].
+    pub keyboard_zones: Vec<String>,
+    /// Effects that are built into the keyboard firmware, for example `breathe`.
+    pub keyboard_effects: Vec<String>,
+    /// Whether the brightness of the keyboard backlight can be changed.
+    pub keyboard_brightness: bool,
+    /// Names of the supported performance profiles.
+    pub performance_profiles: Vec<String>,
+    /// Names of the available switches, see [`Switch`].
+    pub switches: Vec<String>,
+}
+
+impl Capabilities {
+    /// Returns `true` if tailord can change the keyboard colors.
+    pub fn has_keyboard_colors(&self) -> bool {
+        !self.keyboard_zones.is_empty()
+    }
+
+    pub fn has_switch(&self, switch: Switch) -> bool {
+        self.switches.iter().any(|name| name == switch.as_str())
+    }
+}
+
+/// Whether a part of the hardware that tailord controls on its own,
+/// for example the fans or the keyboard, is currently running.
+///
+/// Missing devices are looked for again periodically,
+/// so an unavailable subsystem can become available later on.
+#[derive(
+    Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize, zvariant::Type,
+)]
+pub struct SubsystemStatus {
+    /// `tuxedo_io`, `keyboard` or `cpu`.
+    pub name: String,
+    pub available: bool,
+    /// Why the subsystem isn't available, empty if it is.
+    pub error: String,
+}
+
+/// Color zones of a keyboard backlight.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize, serde::Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyboardZone {
+    Left,
+    Center,
+    Right,
+    /// Additional lights, for example a light bar.
+    Extra,
+}
+
+impl KeyboardZone {
+    pub const ALL: [Self; 4] = [Self::Left, Self::Center, Self::Right, Self::Extra];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Left => "left",
+            Self::Center => "center",
+            Self::Right => "right",
+            Self::Extra => "extra",
+        }
+    }
+}
+
+impl fmt::Display for KeyboardZone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for KeyboardZone {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|zone| zone.as_str() == s)
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Unknown keyboard zone `{s}`"),
+                )
+            })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{Capabilities, SubsystemStatus, Switch};
+    use zvariant::Type;
+
+    #[test]
+    fn capabilities() {
+        let capabilities = Capabilities {
+            keyboard_zones: vec!["left".to_string()],
+            switches: vec!["webcam".to_string()],
+            ..Capabilities::default()
+        };
+        assert!(capabilities.has_keyboard_colors());
+        assert!(capabilities.has_switch(Switch::Webcam));
+        assert!(!capabilities.has_switch(Switch::Touchpad));
+        assert!(!Capabilities::default().has_keyboard_colors());
+
+        assert_eq!(Capabilities::signature(), "(ssyayasasbasas)");
+        assert_eq!(SubsystemStatus::signature(), "(sbs)");
+    }
+}
@@ -0,0 +1,56 @@
+use zvariant::{Signature, Type};
+
+/// CPU frequency scaling settings that are applied to all online logical cores.
+///
+/// Empty strings keep the current setting because the
+/// available values depend on the CPU and its driver.
+/// The default profile doesn't change anything.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct CpuProfile {
+    /// Scaling governor, for example `powersave`.
+    #[serde(default)]
+    pub governor: String,
+    /// Minimum frequency in kHz, `0` for the lowest frequency of the CPU.
+    #[serde(default)]
+    pub min_freq: u32,
+    /// Maximum frequency in kHz, `0` for the highest frequency of the CPU.
+    #[serde(default)]
+    pub max_freq: u32,
+    /// Allow frequencies above the base clock, `None` keeps the current setting.
+    #[serde(default, with = "crate::profile::optional")]
+    pub boost: Option<bool>,
+    /// Energy performance preference, for example `balance_power`.
+    #[serde(default)]
+    pub energy_performance_preference: String,
+    /// Number of logical cores that are online, `0` keeps the current cores online.
+    #[serde(default)]
+    pub online_cores: u32,
+}
+
+// D-Bus has no optional values, so the boost setting is an
+// array with at most one element, like in `ProfileInfo`.
+impl Type for CpuProfile {
+    fn signature() -> Signature<'static> {
+        Signature::from_static_str_unchecked("(suuabsu)")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::CpuProfile;
+    use zvariant::Type;
+
+    #[test]
+    fn cpu_profile() {
+        let profile: CpuProfile = serde_json::from_str("{}").unwrap();
+        assert_eq!(profile, CpuProfile::default());
+        assert_eq!(profile.boost, None);
+
+        let profile: CpuProfile =
+            serde_json::from_str(r#"{ "governor": "powersave", "boost": false }"#).unwrap();
+        assert_eq!(profile.governor, "powersave");
+        assert_eq!(profile.boost, Some(false));
+
+        assert_eq!(CpuProfile::signature(), "(suuabsu)");
+    }
+}
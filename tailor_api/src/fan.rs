@@ -1,5 +1,17 @@
-#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize, zvariant::Type)]
 pub struct FanProfilePoint {
     pub temp: u8,
     pub fan: u8,
 }
+
+/// Emitted by tailord when the thermal watchdog of a fan
+/// raises or clears an alarm.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct ThermalAlarm {
+    /// Index of the fan, starting at 0.
+    pub fan: u8,
+    /// `false` if the alarm was cleared.
+    pub active: bool,
+    /// Human readable reason of the alarm.
+    pub reason: String,
+}
@@ -1,40 +1,322 @@
 use atoi::FromRadix16;
-use std::{fmt::Display, io, str::FromStr};
+use std::{
+    fmt::{self, Display},
+    io,
+    str::FromStr,
+};
+use zvariant::{OwnedValue, Value};
 
-#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+use crate::{ColorSpace, ColorTransition, KeyboardZone};
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, zvariant::Type)]
 pub struct ColorPoint {
     pub color: Color,
+    /// How the previous color changes into this one.
     pub transition: ColorTransition,
     /// Transition time in ms.
     pub transition_time: u32,
+    /// Where the transition blends the previous color and this one.
+    #[serde(default)]
+    pub color_space: ColorSpace,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
-pub enum ColorTransition {
-    None,
-    Linear,
-}
-
-#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub enum ColorProfile {
+    #[default]
     None,
     Single(Color),
     Multiple(Vec<ColorPoint>),
+    /// An effect that is animated by the keyboard firmware,
+    /// so tailord doesn't need to update the colors.
+    ///
+    /// There are no speed or brightness settings for effects:
+    /// the sysfs interface of the keyboard driver only selects
+    /// the effect and has no speed attribute. Effects are shown at the
+    /// brightness of [`ProfileInfo::keyboard_brightness`](crate::ProfileInfo::keyboard_brightness).
+    Effect(KeyboardEffect),
+    /// Separate colors for each zone of the keyboard,
+    /// which are animated in sync.
+    /// Zones that aren't listed keep their color.
+    Zones(Vec<ZoneColors>),
+    /// Show the CPU temperature as a color on all zones.
+    Temperature(TemperatureGradient),
 }
 
-impl Default for ColorProfile {
-    fn default() -> Self {
-        Self::None
-    }
+/// Colors of a single keyboard zone, see [`ColorProfile::Zones`].
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ZoneColors {
+    pub zone: KeyboardZone,
+    /// Animated like [`ColorProfile::Multiple`], so
+    /// a single point keeps the zone at a static color.
+    pub points: Vec<ColorPoint>,
+    /// Time in ms by which the animation of this zone lags behind.
+    /// Increasing offsets from left to right sweep the colors across the keyboard.
+    #[serde(default)]
+    pub offset: u32,
+}
+
+/// Colors for CPU temperatures, see [`ColorProfile::Temperature`].
+///
+/// Temperatures between two points blend their colors,
+/// temperatures outside of all points keep the closest color.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct TemperatureGradient {
+    /// Sorted by temperature.
+    pub points: Vec<TemperatureColor>,
+    /// Where two colors are blended.
+    #[serde(default)]
+    pub color_space: ColorSpace,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct TemperatureColor {
+    /// Temperature in °C.
+    pub temp: u8,
+    pub color: Color,
+}
+
+/// Effects that are built into the keyboard firmware.
+///
+/// Only some keyboards support them, see
+/// [`Capabilities::keyboard_effects`](crate::Capabilities::keyboard_effects).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyboardEffect {
+    Breathe,
+    Cycle,
+    Dance,
+    Flash,
+    RandomColor,
+    Tempo,
+    Wave,
+}
+
+impl KeyboardEffect {
+    pub const ALL: [Self; 7] = [
+        Self::Breathe,
+        Self::Cycle,
+        Self::Dance,
+        Self::Flash,
+        Self::RandomColor,
+        Self::Tempo,
+        Self::Wave,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Breathe => "breathe",
+            Self::Cycle => "cycle",
+            Self::Dance => "dance",
+            Self::Flash => "flash",
+            Self::RandomColor => "random_color",
+            Self::Tempo => "tempo",
+            Self::Wave => "wave",
+        }
+    }
+}
+
+impl Display for KeyboardEffect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for KeyboardEffect {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|effect| effect.as_str() == s)
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Unknown keyboard effect `{s}`"),
+                )
+            })
+    }
+}
+
+/// Typed D-Bus representation of a [`ColorProfile`] with the signature `(sv)`.
+///
+/// The string names the variant and the value holds its data:
+/// an empty `a((yyy)sus)` for `None`, a `(yyy)` color for `Single`,
+/// an `a((yyy)sus)` array of color points for `Multiple`,
+/// the name of the effect as `s` for `Effect` and
+/// `a(sa((yyy)sus)u)` with the name, color points and offset
+/// of each zone for `Zones` and `(a(y(yyy))s)` with the points
+/// and color space for `Temperature`.
+///
+/// Color points hold the color, transition, transition time
+/// and color space.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, zvariant::Type)]
+pub struct ColorProfileVariant {
+    pub kind: String,
+    pub value: OwnedValue,
+}
+
+type ColorTuple = (u8, u8, u8);
+type ColorPointTuple = (ColorTuple, String, u32, String);
+type ZoneColorsTuple = (String, Vec<ColorPointTuple>, u32);
+type TemperatureGradientTuple = (Vec<(u8, ColorTuple)>, String);
+
+impl From<&ColorProfile> for ColorProfileVariant {
+    fn from(profile: &ColorProfile) -> Self {
+        let (kind, value) = match profile {
+            ColorProfile::None => ("None", Value::new(Vec::<ColorPointTuple>::new())),
+            ColorProfile::Single(color) => ("Single", Value::new(color.to_tuple())),
+            ColorProfile::Multiple(points) => (
+                "Multiple",
+                Value::new(points.iter().map(ColorPoint::to_tuple).collect::<Vec<_>>()),
+            ),
+            ColorProfile::Effect(effect) => ("Effect", Value::new(effect.as_str())),
+            ColorProfile::Zones(zones) => (
+                "Zones",
+                Value::new(zones.iter().map(ZoneColors::to_tuple).collect::<Vec<_>>()),
+            ),
+            ColorProfile::Temperature(gradient) => ("Temperature", Value::new(gradient.to_tuple())),
+        };
+        Self {
+            kind: kind.to_string(),
+            value: value.into(),
+        }
+    }
+}
+
+impl TryFrom<ColorProfileVariant> for ColorProfile {
+    type Error = zvariant::Error;
+
+    fn try_from(variant: ColorProfileVariant) -> Result<Self, Self::Error> {
+        match variant.kind.as_str() {
+            "None" => Ok(Self::None),
+            "Single" => {
+                let color: ColorTuple = variant.value.try_into()?;
+                Ok(Self::Single(Color::from_tuple(color)))
+            }
+            "Multiple" => {
+                let points: Vec<ColorPointTuple> = variant.value.try_into()?;
+                points
+                    .into_iter()
+                    .map(ColorPoint::from_tuple)
+                    .collect::<Result<_, _>>()
+                    .map(Self::Multiple)
+            }
+            "Effect" => {
+                let effect: String = variant.value.try_into()?;
+                effect
+                    .parse()
+                    .map(Self::Effect)
+                    .map_err(|err: io::Error| zvariant::Error::Message(err.to_string()))
+            }
+            "Zones" => {
+                let zones: Vec<ZoneColorsTuple> = variant.value.try_into()?;
+                zones
+                    .into_iter()
+                    .map(ZoneColors::from_tuple)
+                    .collect::<Result<_, _>>()
+                    .map(Self::Zones)
+            }
+            "Temperature" => {
+                let gradient: TemperatureGradientTuple = variant.value.try_into()?;
+                TemperatureGradient::from_tuple(gradient).map(Self::Temperature)
+            }
+            other => Err(zvariant::Error::Message(format!(
+                "Unknown color profile kind `{other}`"
+            ))),
+        }
+    }
+}
+
+impl ColorPoint {
+    fn to_tuple(&self) -> ColorPointTuple {
+        (
+            self.color.to_tuple(),
+            self.transition.to_string(),
+            self.transition_time,
+            self.color_space.to_string(),
+        )
+    }
+
+    fn from_tuple(
+        (color, transition, transition_time, color_space): ColorPointTuple,
+    ) -> zvariant::Result<Self> {
+        let parse_error = |err: io::Error| zvariant::Error::Message(err.to_string());
+        Ok(Self {
+            color: Color::from_tuple(color),
+            transition: transition.parse().map_err(parse_error)?,
+            transition_time,
+            color_space: color_space.parse().map_err(parse_error)?,
+        })
+    }
+}
+
+impl ZoneColors {
+    fn to_tuple(&self) -> ZoneColorsTuple {
+        (
+            self.zone.as_str().to_string(),
+            self.points.iter().map(ColorPoint::to_tuple).collect(),
+            self.offset,
+        )
+    }
+
+    fn from_tuple((zone, points, offset): ZoneColorsTuple) -> zvariant::Result<Self> {
+        Ok(Self {
+            zone: zone
+                .parse()
+                .map_err(|err: io::Error| zvariant::Error::Message(err.to_string()))?,
+            points: points
+                .into_iter()
+                .map(ColorPoint::from_tuple)
+                .collect::<Result<_, _>>()?,
+            offset,
+        })
+    }
+}
+
+impl TemperatureGradient {
+    fn to_tuple(&self) -> TemperatureGradientTuple {
+        (
+            self.points
+                .iter()
+                .map(|point| (point.temp, point.color.to_tuple()))
+                .collect(),
+            self.color_space.to_string(),
+        )
+    }
+
+    fn from_tuple((points, color_space): TemperatureGradientTuple) -> zvariant::Result<Self> {
+        Ok(Self {
+            points: points
+                .into_iter()
+                .map(|(temp, color)| TemperatureColor {
+                    temp,
+                    color: Color::from_tuple(color),
+                })
+                .collect(),
+            color_space: color_space
+                .parse()
+                .map_err(|err: io::Error| zvariant::Error::Message(err.to_string()))?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, zvariant::Type)]
 pub struct Color {
     pub r: u8,
     pub g: u8,
     pub b: u8,
 }
 
+impl Color {
+    fn to_tuple(&self) -> ColorTuple {
+        (self.r, self.g, self.b)
+    }
+
+    fn from_tuple((r, g, b): ColorTuple) -> Self {
+        Self { r, g, b }
+    }
+}
+
 impl Display for Color {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "0x{:02X}{:02X}{:02X}", self.r, self.g, self.b)
@@ -51,9 +333,9 @@ impl FromStr for Color {
                 "Incorrect length for 3x8-bit hexadecimal value",
             ))
         } else {
-            let r = u8::from_radix_16(s[0..2].as_bytes());
-            let g = u8::from_radix_16(s[2..4].as_bytes());
-            let b = u8::from_radix_16(s[4..6].as_bytes());
+            let r = u8::from_radix_16(&s.as_bytes()[0..2]);
+            let g = u8::from_radix_16(&s.as_bytes()[2..4]);
+            let b = u8::from_radix_16(&s.as_bytes()[4..6]);
 
             if r.1 == 2 && g.1 == 2 && b.1 == 2 {
                 Ok(Self {
@@ -73,8 +355,15 @@ impl FromStr for Color {
 
 #[cfg(test)]
 mod test {
-    use crate::keyboard::Color;
+    use crate::{
+        keyboard::{
+            Color, ColorPoint, ColorProfile, ColorProfileVariant, KeyboardEffect, TemperatureColor,
+            TemperatureGradient, ZoneColors,
+        },
+        ColorSpace, ColorTransition, KeyboardZone,
+    };
     use std::str::FromStr;
+    use zvariant::Type;
 
     #[test]
     fn color_from_string() {
@@ -94,4 +383,110 @@ mod test {
         Color::from_str("F").unwrap_err();
         Color::from_str("INVLD!").unwrap_err();
     }
+
+    #[test]
+    fn color_profile_variant() {
+        let color = Color { r: 1, g: 2, b: 3 };
+        let profiles = [
+            ColorProfile::None,
+            ColorProfile::Single(color.clone()),
+            ColorProfile::Multiple(vec![
+                ColorPoint {
+                    color: color.clone(),
+                    transition: ColorTransition::None,
+                    transition_time: 100,
+                    color_space: ColorSpace::Rgb,
+                },
+                ColorPoint {
+                    color,
+                    transition: ColorTransition::CubicBezier {
+                        x1: 500,
+                        y1: -500,
+                        x2: 500,
+                        y2: 1500,
+                    },
+                    transition_time: 2000,
+                    color_space: ColorSpace::Oklab,
+                },
+            ]),
+            ColorProfile::Effect(KeyboardEffect::RandomColor),
+            ColorProfile::Zones(vec![ZoneColors {
+                zone: KeyboardZone::Extra,
+                points: vec![ColorPoint {
+                    color: Color { r: 3, g: 2, b: 1 },
+                    transition: ColorTransition::Steps(3),
+                    transition_time: 0,
+                    color_space: ColorSpace::Hsv,
+                }],
+                offset: 500,
+            }]),
+            ColorProfile::Temperature(TemperatureGradient {
+                points: vec![
+                    TemperatureColor {
+                        temp: 40,
+                        color: Color { r: 0, g: 0, b: 255 },
+                    },
+                    TemperatureColor {
+                        temp: 90,
+                        color: Color { r: 255, g: 0, b: 0 },
+                    },
+                ],
+                color_space: ColorSpace::Hsv,
+            }),
+        ];
+
+        for profile in profiles {
+            let variant = ColorProfileVariant::from(&profile);
+            assert_eq!(ColorProfile::try_from(variant).unwrap(), profile);
+        }
+
+        let variant = ColorProfileVariant {
+            kind: "Rainbow".to_string(),
+            value: zvariant::Value::new(0_u8).into(),
+        };
+        ColorProfile::try_from(variant).unwrap_err();
+
+        let variant = ColorProfileVariant {
+            kind: "Effect".to_string(),
+            value: zvariant::Value::new("rainbow").into(),
+        };
+        ColorProfile::try_from(variant).unwrap_err();
+
+        assert_eq!(ColorProfileVariant::signature(), "(sv)");
+        assert_eq!(ColorPoint::signature(), "((yyy)sus)");
+
+        // Profiles without a color space blend in RGB
+        let point: ColorPoint = serde_json::from_str(
+            r#"{ "color": { "r": 0, "g": 0, "b": 0 }, "transition": "Linear", "transition_time": 10 }"#,
+        )
+        .unwrap();
+        assert_eq!(point.color_space, ColorSpace::Rgb);
+    }
+
+    #[test]
+    fn effect_names() {
+        for effect in KeyboardEffect::ALL {
+            assert_eq!(
+                effect.to_string().parse::<KeyboardEffect>().unwrap(),
+                effect
+            );
+        }
+        let profile: ColorProfile = serde_json::from_str(r#"{ "Effect": "wave" }"#).unwrap();
+        assert_eq!(profile, ColorProfile::Effect(KeyboardEffect::Wave));
+    }
+
+    #[test]
+    fn zone_colors() {
+        let profile: ColorProfile =
+            serde_json::from_str(r#"{ "Zones": [{ "zone": "center", "points": [] }] }"#).unwrap();
+        assert_eq!(
+            profile,
+            ColorProfile::Zones(vec![ZoneColors {
+                zone: KeyboardZone::Center,
+                points: Vec::new(),
+                offset: 0,
+            }])
+        );
+        assert!("middle".parse::<KeyboardZone>().is_err());
+    }
 }
@@ -1,7 +1,20 @@
+mod capabilities;
+mod cpu;
 mod fan;
 mod keyboard;
+mod power;
 mod profile;
+mod switches;
+mod transition;
 
-pub use fan::FanProfilePoint;
-pub use keyboard::{Color, ColorPoint, ColorProfile, ColorTransition};
-pub use profile::ProfileInfo;
+pub use capabilities::{Capabilities, KeyboardZone, SubsystemStatus};
+pub use cpu::CpuProfile;
+pub use fan::{FanProfilePoint, ThermalAlarm};
+pub use keyboard::{
+    Color, ColorPoint, ColorProfile, ColorProfileVariant, KeyboardEffect, TemperatureColor,
+    TemperatureGradient, ZoneColors,
+};
+pub use power::PowerRule;
+pub use profile::{ProfileChange, ProfileInfo};
+pub use switches::Switch;
+pub use transition::{ColorSpace, ColorTransition};
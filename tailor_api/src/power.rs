@@ -0,0 +1,47 @@
+/// Global profiles that tailord activates automatically
+/// when the laptop is plugged in or unplugged.
+///
+/// An empty name keeps the active profile for that power source.
+#[derive(
+    Debug, Default, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, zvariant::Type,
+)]
+pub struct PowerRule {
+    /// Global profile used on AC power.
+    #[serde(default)]
+    pub ac: String,
+    /// Global profile used on battery power.
+    #[serde(default)]
+    pub battery: String,
+}
+
+impl PowerRule {
+    /// Returns the name of the global profile for the given power source, if any.
+    pub fn profile(&self, on_battery: bool) -> Option<&str> {
+        let name = if on_battery { &self.battery } else { &self.ac };
+        (!name.is_empty()).then_some(name.as_str())
+    }
+
+    /// Returns the names of all global profiles used by this rule.
+    pub fn profiles(&self) -> impl Iterator<Item = &str> {
+        [false, true]
+            .into_iter()
+            .filter_map(|on_battery| self.profile(on_battery))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::PowerRule;
+    use zvariant::Type;
+
+    #[test]
+    fn power_rule() {
+        let rule: PowerRule = serde_json::from_str(r#"{ "battery": "silent" }"#).unwrap();
+        assert_eq!(rule.profile(false), None);
+        assert_eq!(rule.profile(true), Some("silent"));
+        assert_eq!(rule.profiles().collect::<Vec<_>>(), ["silent"]);
+
+        assert_eq!(PowerRule::default().profiles().count(), 0);
+        assert_eq!(PowerRule::signature(), "(ss)");
+    }
+}
@@ -1,14 +1,157 @@
+use std::collections::BTreeMap;
+
+use zvariant::{Signature, Type};
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
 pub struct ProfileInfo {
+    /// Fan profile used for all fans without a separate assignment.
     pub fan: String,
+    /// Separate fan profiles for individual fans, indexed from 0.
+    /// For example, this allows different curves for the CPU and GPU fan.
+    // Can't be skipped when empty because D-Bus structs have a fixed number of fields.
+    #[serde(default)]
+    pub fan_assignments: BTreeMap<u8, String>,
     pub keyboard: String,
+    /// Profiles without a CPU profile use the default one.
+    #[serde(default = "default_name")]
+    pub cpu: String,
+    /// Name of the platform performance profile, for example
+    /// `quiet` on Clevo or `enthusiast` on Uniwill devices.
+    /// An empty name keeps the current performance profile.
+    #[serde(default)]
+    pub performance_profile: String,
+    /// States of hardware switches, indexed by [`Switch`](crate::Switch) names.
+    /// For example, `{ "webcam": false }` turns off the webcam.
+    /// Switches that aren't listed keep their state.
+    #[serde(default)]
+    pub switches: BTreeMap<String, bool>,
+    /// Brightness of the keyboard backlight, applied together
+    /// with the [`keyboard`](Self::keyboard) colors.
+    /// Profiles without a brightness keep the current one.
+    #[serde(default, with = "optional")]
+    pub keyboard_brightness: Option<u8>,
+}
+
+// D-Bus has no optional values, so the brightness is an array
+// with at most one element, see [`optional`].
+impl Type for ProfileInfo {
+    fn signature() -> Signature<'static> {
+        Signature::from_static_str_unchecked("(sa{ys}sssa{sb}ay)")
+    }
+}
+
+fn default_name() -> String {
+    "default".to_string()
+}
+
+/// Stores an `Option` as value or `null` in JSON
+/// and as an array with at most one element on D-Bus.
+pub(crate) mod optional {
+    use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<T, S>(value: &Option<T>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Serialize,
+        S: Serializer,
+    {
+        if serializer.is_human_readable() {
+            value.serialize(serializer)
+        } else {
+            match value {
+                Some(value) => std::slice::from_ref(value).serialize(serializer),
+                None => <&[T]>::default().serialize(serializer),
+            }
+        }
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+    where
+        T: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        if deserializer.is_human_readable() {
+            Option::deserialize(deserializer)
+        } else {
+            let mut values = Vec::<T>::deserialize(deserializer)?;
+            if values.len() > 1 {
+                return Err(de::Error::invalid_length(
+                    values.len(),
+                    &"at most one value",
+                ));
+            }
+            Ok(values.pop())
+        }
+    }
+}
+
+impl ProfileInfo {
+    /// Returns the name of the fan profile used for the given fan.
+    pub fn fan_profile(&self, fan: u8) -> &str {
+        self.fan_assignments.get(&fan).unwrap_or(&self.fan)
+    }
+
+    /// Returns the names of all fan profiles used by this profile.
+    pub fn fan_profiles(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.fan.as_str()).chain(self.fan_assignments.values().map(String::as_str))
+    }
+}
+
+/// A change of the stored profiles of one kind, as announced by tailord.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ProfileChange {
+    Added(String),
+    Removed(String),
+    Renamed { from: String, to: String },
 }
 
 impl Default for ProfileInfo {
     fn default() -> Self {
         Self {
             fan: "default".to_string(),
+            fan_assignments: BTreeMap::new(),
             keyboard: "default".to_string(),
+            cpu: default_name(),
+            performance_profile: String::new(),
+            switches: BTreeMap::new(),
+            keyboard_brightness: None,
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::ProfileInfo;
+    use zvariant::Type;
+
+    #[test]
+    fn fan_assignments() {
+        let info: ProfileInfo =
+            serde_json::from_str(r#"{ "fan": "default", "keyboard": "default" }"#).unwrap();
+        assert_eq!(info, ProfileInfo::default());
+        assert_eq!(info.fan_profile(1), "default");
+
+        let info: ProfileInfo = serde_json::from_str(
+            r#"{ "fan": "cpu", "fan_assignments": { "1": "gpu" }, "keyboard": "default" }"#,
+        )
+        .unwrap();
+        assert_eq!(info.fan_profile(0), "cpu");
+        assert_eq!(info.fan_profile(1), "gpu");
+        assert_eq!(info.fan_profiles().collect::<Vec<_>>(), ["cpu", "gpu"]);
+
+        assert_eq!(ProfileInfo::signature(), "(sa{ys}sssa{sb}ay)");
+    }
+
+    #[test]
+    fn keyboard_brightness() {
+        let info: ProfileInfo = serde_json::from_str(
+            r#"{ "fan": "default", "keyboard": "default", "keyboard_brightness": 128 }"#,
+        )
+        .unwrap();
+        assert_eq!(info.keyboard_brightness, Some(128));
+
+        let json = serde_json::to_string(&ProfileInfo::default()).unwrap();
+        assert!(json.contains(r#""keyboard_brightness":null"#));
+        let info: ProfileInfo = serde_json::from_str(&json).unwrap();
+        assert_eq!(info, ProfileInfo::default());
+    }
+}
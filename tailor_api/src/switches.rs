@@ -0,0 +1,62 @@
+use std::{fmt, io, str::FromStr};
+
+/// Hardware switches that tailord can toggle.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize, serde::Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum Switch {
+    Webcam,
+    Touchpad,
+    /// Turns off all wireless devices while enabled.
+    FlightMode,
+}
+
+impl Switch {
+    pub const ALL: [Self; 3] = [Self::Webcam, Self::Touchpad, Self::FlightMode];
+
+    /// The name that is used in global profiles.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Webcam => "webcam",
+            Self::Touchpad => "touchpad",
+            Self::FlightMode => "flight_mode",
+        }
+    }
+}
+
+impl fmt::Display for Switch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Switch {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|switch| switch.as_str() == s)
+            .ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidData, format!("Unknown switch `{s}`"))
+            })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::Switch;
+
+    #[test]
+    fn switch_names() {
+        for switch in Switch::ALL {
+            assert_eq!(switch.to_string().parse::<Switch>().unwrap(), switch);
+            assert_eq!(
+                serde_json::to_string(&switch).unwrap(),
+                format!("\"{switch}\"")
+            );
+        }
+        assert!("camera".parse::<Switch>().is_err());
+    }
+}
@@ -0,0 +1,270 @@
+use std::{fmt, io, str::FromStr};
+
+/// How the color changes from the previous color point to the next one.
+///
+/// Stored as a string like `EaseIn`, `CubicBezier(0.25, 0.1, 0.25, 1)` or `Steps(4)`,
+/// both in profiles and on D-Bus.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, zvariant::Type,
+)]
+#[serde(into = "String", try_from = "String")]
+#[zvariant(signature = "s")]
+pub enum ColorTransition {
+    /// Switch to the color right away.
+    None,
+    Linear,
+    /// Start slowly and speed up.
+    EaseIn,
+    /// Start quickly and slow down.
+    EaseOut,
+    EaseInOut,
+    /// A timing curve like in CSS, which passes through `(0, 0)`,
+    /// `(x1, y1)`, `(x2, y2)` and `(1, 1)`.
+    ///
+    /// The values are stored in thousandths, so `CubicBezier(0.25, 0.1, 0.25, 1)`
+    /// has `x1: 250, y1: 100, x2: 250, y2: 1000`.
+    /// Both `x` values must be between 0 and 1000.
+    CubicBezier {
+        x1: u16,
+        y1: i16,
+        x2: u16,
+        y2: i16,
+    },
+    /// Change the color in the given number of equal jumps,
+    /// the first one after `transition_time / steps`.
+    Steps(u32),
+}
+
+impl fmt::Display for ColorTransition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::None => f.write_str("None"),
+            Self::Linear => f.write_str("Linear"),
+            Self::EaseIn => f.write_str("EaseIn"),
+            Self::EaseOut => f.write_str("EaseOut"),
+            Self::EaseInOut => f.write_str("EaseInOut"),
+            Self::CubicBezier { x1, y1, x2, y2 } => write!(
+                f,
+                "CubicBezier({}, {}, {}, {})",
+                from_thousandths(*x1),
+                from_thousandths(*y1),
+                from_thousandths(*x2),
+                from_thousandths(*y2)
+            ),
+            Self::Steps(steps) => write!(f, "Steps({steps})"),
+        }
+    }
+}
+
+impl FromStr for ColorTransition {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+
+        let (name, args) = match s.split_once('(') {
+            Some((name, args)) => {
+                let args = args
+                    .strip_suffix(')')
+                    .ok_or_else(|| invalid(format!("Missing `)` in color transition `{s}`")))?;
+                (name.trim(), Some(args))
+            }
+            None => (s.trim(), None),
+        };
+
+        match (name, args) {
+            ("None", None) => Ok(Self::None),
+            ("Linear", None) => Ok(Self::Linear),
+            ("EaseIn", None) => Ok(Self::EaseIn),
+            ("EaseOut", None) => Ok(Self::EaseOut),
+            ("EaseInOut", None) => Ok(Self::EaseInOut),
+            ("CubicBezier", Some(args)) => {
+                let values = args
+                    .split(',')
+                    .map(|value| value.trim().parse::<f64>())
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|err| invalid(format!("Invalid cubic bezier `{s}`: {err}")))?;
+                let [x1, y1, x2, y2] = values[..] else {
+                    return Err(invalid(format!(
+                        "A cubic bezier needs 4 values, found `{s}`"
+                    )));
+                };
+                let x = |value| to_thousandths::<u16>(value).filter(|&x| x <= 1000);
+                let (Some(x1), Some(x2)) = (x(x1), x(x2)) else {
+                    return Err(invalid(format!(
+                        "The x values of a cubic bezier must be between 0 and 1, found `{s}`"
+                    )));
+                };
+                let (Some(y1), Some(y2)) = (to_thousandths(y1), to_thousandths(y2)) else {
+                    return Err(invalid(format!(
+                        "The y values of a cubic bezier must be between -32 and 32, found `{s}`"
+                    )));
+                };
+                Ok(Self::CubicBezier { x1, y1, x2, y2 })
+            }
+            ("Steps", Some(args)) => match args.trim().parse::<u32>() {
+                Ok(steps) if steps > 0 => Ok(Self::Steps(steps)),
+                _ => Err(invalid(format!(
+                    "Steps need a positive number of steps, found `{s}`"
+                ))),
+            },
+            _ => Err(invalid(format!("Unknown color transition `{s}`"))),
+        }
+    }
+}
+
+/// Rounds a value to thousandths or returns `None` if it doesn't fit into `T`.
+fn to_thousandths<T: TryFrom<i64>>(value: f64) -> Option<T> {
+    let thousandths = (value * 1000.0).round();
+    // `as` saturates, so only NaN would slip through as 0.
+    if thousandths.is_nan() {
+        return None;
+    }
+    T::try_from(thousandths as i64).ok()
+}
+
+fn from_thousandths(value: impl Into<f64>) -> f64 {
+    value.into() / 1000.0
+}
+
+impl From<ColorTransition> for String {
+    fn from(transition: ColorTransition) -> Self {
+        transition.to_string()
+    }
+}
+
+impl TryFrom<String> for ColorTransition {
+    type Error = io::Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+/// The color space in which a transition blends two colors.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize, zvariant::Type,
+)]
+#[zvariant(signature = "s")]
+pub enum ColorSpace {
+    /// Blend the red, green and blue values, which
+    /// looks muddy between complementary colors.
+    #[default]
+    Rgb,
+    /// Take the shorter way around the color wheel.
+    Hsv,
+    /// Perceptually uniform blending.
+    Oklab,
+}
+
+impl ColorSpace {
+    pub const ALL: [Self; 3] = [Self::Rgb, Self::Hsv, Self::Oklab];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Rgb => "Rgb",
+            Self::Hsv => "Hsv",
+            Self::Oklab => "Oklab",
+        }
+    }
+}
+
+impl fmt::Display for ColorSpace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ColorSpace {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|space| space.as_str() == s)
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Unknown color space `{s}`"),
+                )
+            })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{ColorSpace, ColorTransition};
+
+    #[test]
+    fn transition_names() {
+        let transitions = [
+            ColorTransition::None,
+            ColorTransition::Linear,
+            ColorTransition::EaseIn,
+            ColorTransition::EaseOut,
+            ColorTransition::EaseInOut,
+            ColorTransition::CubicBezier {
+                x1: 250,
+                y1: 100,
+                x2: 250,
+                y2: 1000,
+            },
+            ColorTransition::CubicBezier {
+                x1: 500,
+                y1: -1500,
+                x2: 1,
+                y2: 2500,
+            },
+            ColorTransition::Steps(4),
+        ];
+        for transition in transitions {
+            assert_eq!(
+                transition.to_string().parse::<ColorTransition>().unwrap(),
+                transition
+            );
+            assert_eq!(
+                serde_json::to_string(&transition).unwrap(),
+                format!("\"{transition}\"")
+            );
+        }
+
+        assert_eq!(
+            "CubicBezier(0.42,0,1,1)"
+                .parse::<ColorTransition>()
+                .unwrap(),
+            ColorTransition::CubicBezier {
+                x1: 420,
+                y1: 0,
+                x2: 1000,
+                y2: 1000,
+            }
+        );
+        assert_eq!(
+            ColorTransition::CubicBezier {
+                x1: 250,
+                y1: 100,
+                x2: 1,
+                y2: -500,
+            }
+            .to_string(),
+            "CubicBezier(0.25, 0.1, 0.001, -0.5)"
+        );
+        for invalid in [
+            "Bounce",
+            "Linear(1)",
+            "Steps(0)",
+            "Steps(2",
+            "CubicBezier(0, 0, 1)",
+            "CubicBezier(1.5, 0, 1, 1)",
+            "CubicBezier(-0.1, 0, 1, 1)",
+            "CubicBezier(0, 40, 1, 1)",
+            "CubicBezier(0, NaN, 1, 1)",
+        ] {
+            assert!(invalid.parse::<ColorTransition>().is_err(), "{invalid}");
+        }
+
+        for space in ColorSpace::ALL {
+            assert_eq!(space.to_string().parse::<ColorSpace>().unwrap(), space);
+        }
+    }
+}
@@ -0,0 +1,370 @@
+mod monitor;
+mod output;
+mod profile;
+
+use std::{collections::BTreeMap, str::FromStr, time::Duration};
+
+use clap::{Parser, Subcommand};
+use output::Format;
+use profile::{CpuProfiles, FanProfiles, GlobalProfiles, KeyboardProfiles, ProfileAction};
+use tailor_api::{Color, PowerRule, Switch};
+use tailor_client::TailorConnection;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// Command line interface for tailord.
+#[derive(Debug, Parser)]
+#[command(name = "tailor", version)]
+struct Args {
+    /// Print machine readable JSON instead of human readable text.
+    #[arg(long, global = true)]
+    json: bool,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Manage global profiles that combine fan and keyboard profiles.
+    Profile {
+        #[command(subcommand)]
+        action: GlobalAction,
+    },
+    /// Manage fan profiles.
+    Fan {
+        #[command(subcommand)]
+        action: FanAction,
+    },
+    /// Manage keyboard color profiles.
+    Keyboard {
+        #[command(subcommand)]
+        action: KeyboardAction,
+    },
+    /// Manage CPU frequency profiles.
+    Cpu {
+        #[command(subcommand)]
+        action: ProfileAction,
+    },
+    /// Change the performance profile of the platform.
+    Performance {
+        #[command(subcommand)]
+        action: PerformanceAction,
+    },
+    /// Toggle the webcam, touchpad and flight mode.
+    Switch {
+        #[command(subcommand)]
+        action: SwitchAction,
+    },
+    /// Switch global profiles automatically when plugging in or unplugging.
+    Power {
+        #[command(subcommand)]
+        action: PowerAction,
+    },
+    /// Print live temperatures and fan speeds until interrupted.
+    Monitor,
+    /// Print the hardware features supported by the device.
+    Info,
+    /// Print which parts of the hardware are controlled by the daemon.
+    Status,
+}
+
+#[derive(Debug, Subcommand)]
+enum GlobalAction {
+    #[command(flatten)]
+    Profile(ProfileAction),
+    /// Activate a global profile.
+    Activate { name: String },
+    /// Print the name of the active global profile.
+    Active,
+}
+
+#[derive(Debug, Subcommand)]
+enum FanAction {
+    #[command(flatten)]
+    Profile(ProfileAction),
+    /// Override the fan speed for a while.
+    Override {
+        /// Fan speed in percent.
+        #[arg(value_parser = clap::value_parser!(u8).range(0..=100))]
+        speed: u8,
+        /// Only override a single fan (starting at 0).
+        #[arg(long)]
+        fan: Option<u8>,
+        /// Duration of the override in seconds.
+        #[arg(long, default_value_t = 5)]
+        duration: u64,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum KeyboardAction {
+    #[command(flatten)]
+    Profile(ProfileAction),
+    /// Override the keyboard color for a while.
+    Override {
+        /// Hexadecimal color, for example `FF8000`.
+        #[arg(value_parser = parse_color)]
+        color: Color,
+        /// Duration of the override in seconds.
+        #[arg(long, default_value_t = 5)]
+        duration: u64,
+    },
+    /// Print the keyboard brightness or change it until another profile is activated.
+    Brightness {
+        /// New brightness from 0 to 255.
+        brightness: Option<u8>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum PerformanceAction {
+    /// List the performance profiles supported by the device.
+    List,
+    /// Print the current performance profile.
+    Get,
+    /// Set the performance profile until another global profile is activated.
+    Set { name: String },
+}
+
+#[derive(Debug, Subcommand)]
+enum SwitchAction {
+    /// Print the state of all switches.
+    Show,
+    /// Turn a switch on.
+    Enable {
+        /// One of `webcam`, `touchpad` or `flight_mode`.
+        #[arg(value_parser = parse_switch)]
+        switch: Switch,
+    },
+    /// Turn a switch off.
+    Disable {
+        /// One of `webcam`, `touchpad` or `flight_mode`.
+        #[arg(value_parser = parse_switch)]
+        switch: Switch,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum PowerAction {
+    /// Print the global profiles used on AC and battery power.
+    Show,
+    /// Set the global profiles used on AC and battery power.
+    ///
+    /// Omitted names keep the active profile.
+    Set {
+        #[arg(long, default_value = "")]
+        ac: String,
+        #[arg(long, default_value = "")]
+        battery: String,
+    },
+}
+
+fn parse_color(value: &str) -> std::result::Result<Color, String> {
+    let value = value.trim_start_matches('#').trim_start_matches("0x");
+    Color::from_str(value).map_err(|err| err.to_string())
+}
+
+fn parse_switch(value: &str) -> std::result::Result<Switch, String> {
+    Switch::from_str(value).map_err(|err| err.to_string())
+}
+
+/// Overrides only last for 1s in tailord, so they need to be repeated.
+const OVERRIDE_INTERVAL: Duration = Duration::from_millis(500);
+
+async fn repeat_override<F, Fut>(duration: u64, mut set_override: F) -> Result<()>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = tailor_client::ClientResult<()>>,
+{
+    let end = tokio::time::Instant::now() + Duration::from_secs(duration);
+    loop {
+        set_override().await?;
+        if tokio::time::Instant::now() + OVERRIDE_INTERVAL >= end {
+            return Ok(());
+        }
+        tokio::time::sleep(OVERRIDE_INTERVAL).await;
+    }
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    let args = Args::parse();
+
+    if let Err(err) = run(args).await {
+        eprintln!("Error: {err}");
+        std::process::exit(1);
+    }
+}
+
+async fn run(args: Args) -> Result<()> {
+    let format = if args.json {
+        Format::Json
+    } else {
+        Format::Human
+    };
+    let connection = TailorConnection::new().await.map_err(|err| {
+        format!("Couldn't connect to tailord, make sure that it is running: `{err}`")
+    })?;
+
+    match args.command {
+        Command::Profile { action } => match action {
+            GlobalAction::Profile(action) => {
+                profile::run::<GlobalProfiles>(&connection, action, format).await
+            }
+            GlobalAction::Activate { name } => {
+                connection.set_active_global_profile_name(&name).await?;
+                Ok(connection.reload().await?)
+            }
+            GlobalAction::Active => {
+                let name = connection.get_active_global_profile_name().await?;
+                output::print_name(&name, format)
+            }
+        },
+        Command::Fan { action } => match action {
+            FanAction::Profile(action) => {
+                profile::run::<FanProfiles>(&connection, action, format).await
+            }
+            FanAction::Override {
+                speed,
+                fan,
+                duration,
+            } => {
+                repeat_override(duration, || async {
+                    match fan {
+                        Some(fan) => connection.override_single_fan_speed(fan, speed).await,
+                        None => connection.override_fan_speed(speed).await,
+                    }
+                })
+                .await
+            }
+        },
+        Command::Keyboard { action } => match action {
+            KeyboardAction::Profile(action) => {
+                profile::run::<KeyboardProfiles>(&connection, action, format).await
+            }
+            KeyboardAction::Override { color, duration } => {
+                if !connection.get_capabilities().await?.has_keyboard_colors() {
+                    return Err("Keyboard colors aren't supported on this device".into());
+                }
+                repeat_override(duration, || connection.override_keyboard_color(&color)).await
+            }
+            KeyboardAction::Brightness { brightness } => {
+                if !connection.get_capabilities().await?.keyboard_brightness {
+                    return Err("The keyboard brightness can't be changed on this device".into());
+                }
+                match brightness {
+                    Some(brightness) => Ok(connection.set_keyboard_brightness(brightness).await?),
+                    None => {
+                        // A plain number is valid JSON as well.
+                        println!("{}", connection.get_keyboard_brightness().await?);
+                        Ok(())
+                    }
+                }
+            }
+        },
+        Command::Cpu { action } => profile::run::<CpuProfiles>(&connection, action, format).await,
+        Command::Performance { action } => match action {
+            PerformanceAction::List => {
+                let names = connection.list_performance_profiles().await?;
+                output::print_names(&names, format)
+            }
+            PerformanceAction::Get => {
+                let name = connection.get_performance_profile().await?;
+                output::print_name(&name, format)
+            }
+            PerformanceAction::Set { name } => {
+                Ok(connection.set_performance_profile(&name).await?)
+            }
+        },
+        Command::Switch { action } => match action {
+            SwitchAction::Show => {
+                let capabilities = connection.get_capabilities().await?;
+                let mut states = BTreeMap::new();
+                for switch in Switch::ALL {
+                    if capabilities.has_switch(switch) {
+                        states.insert(switch.to_string(), connection.get_switch(switch).await?);
+                    }
+                }
+                match format {
+                    Format::Human => {
+                        for (switch, enabled) in &states {
+                            let state = if *enabled { "on" } else { "off" };
+                            println!("{switch}: {state}");
+                        }
+                    }
+                    Format::Json => println!("{}", serde_json::to_string_pretty(&states)?),
+                }
+                Ok(())
+            }
+            SwitchAction::Enable { switch } => Ok(connection.set_switch(switch, true).await?),
+            SwitchAction::Disable { switch } => Ok(connection.set_switch(switch, false).await?),
+        },
+        Command::Power { action } => match action {
+            PowerAction::Show => {
+                let rule = connection.get_power_rule().await?;
+                let on_battery = connection.is_on_battery().await?;
+                match format {
+                    Format::Human => {
+                        let describe = |name: &str| match name {
+                            "" => "(unchanged)".to_string(),
+                            name => name.to_string(),
+                        };
+                        println!("AC: {}", describe(&rule.ac));
+                        println!("Battery: {}", describe(&rule.battery));
+                        println!("On battery: {}", if on_battery { "yes" } else { "no" });
+                    }
+                    Format::Json => {
+                        let value = serde_json::json!({ "rule": rule, "on_battery": on_battery });
+                        println!("{}", serde_json::to_string_pretty(&value)?);
+                    }
+                }
+                Ok(())
+            }
+            PowerAction::Set { ac, battery } => Ok(connection
+                .set_power_rule(&PowerRule { ac, battery })
+                .await?),
+        },
+        Command::Monitor => monitor::run(&connection, format).await,
+        Command::Info => {
+            let capabilities = connection.get_capabilities().await?;
+            match format {
+                Format::Human => println!("{}", output::describe_capabilities(&capabilities)),
+                Format::Json => println!("{}", serde_json::to_string_pretty(&capabilities)?),
+            }
+            Ok(())
+        }
+        Command::Status => {
+            let subsystems = connection.get_subsystems().await?;
+            match format {
+                Format::Human => println!("{}", output::describe_subsystems(&subsystems)),
+                Format::Json => println!("{}", serde_json::to_string_pretty(&subsystems)?),
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use clap::CommandFactory;
+    use tailor_api::Color;
+
+    use super::{parse_color, Args};
+
+    #[test]
+    fn verify_args() {
+        Args::command().debug_assert();
+    }
+
+    #[test]
+    fn color_argument() {
+        let color = Color {
+            r: 255,
+            g: 128,
+            b: 0,
+        };
+        assert_eq!(parse_color("FF8000"), Ok(color.clone()));
+        assert_eq!(parse_color("#ff8000"), Ok(color));
+        assert!(parse_color("orange").is_err());
+    }
+}
@@ -0,0 +1,81 @@
+use futures_util::StreamExt;
+use tailor_api::ThermalAlarm;
+use tailor_client::TailorConnection;
+
+use crate::{output::Format, Result};
+
+/// The live values of all fans, indexed by fan.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+struct Snapshot {
+    temperatures: Vec<u8>,
+    fan_speeds: Vec<u8>,
+    target_fan_speeds: Vec<u8>,
+}
+
+impl Snapshot {
+    fn describe(&self) -> String {
+        self.temperatures
+            .iter()
+            .zip(&self.fan_speeds)
+            .zip(&self.target_fan_speeds)
+            .enumerate()
+            .map(|(fan, ((temp, speed), target))| {
+                format!("Fan {fan}: {temp:>3}°C {speed:>3}% (target {target:>3}%)")
+            })
+            .collect::<Vec<_>>()
+            .join("  |  ")
+    }
+
+    fn print(&self, format: Format) -> Result<()> {
+        match format {
+            Format::Human => println!("{}", self.describe()),
+            Format::Json => println!("{}", serde_json::to_string(self)?),
+        }
+        Ok(())
+    }
+}
+
+fn print_alarm(alarm: &ThermalAlarm, format: Format) -> Result<()> {
+    match format {
+        Format::Human if alarm.active => {
+            println!("Thermal alarm for fan {}: {}", alarm.fan, alarm.reason)
+        }
+        Format::Human => println!("Thermal alarm for fan {} cleared", alarm.fan),
+        Format::Json => println!("{}", serde_json::json!({ "alarm": alarm })),
+    }
+    Ok(())
+}
+
+pub async fn run(connection: &TailorConnection<'_>, format: Format) -> Result<()> {
+    let mut temperatures = Box::pin(connection.receive_temperatures().await);
+    let mut fan_speeds = Box::pin(connection.receive_fan_speeds().await);
+    let mut target_fan_speeds = Box::pin(connection.receive_target_fan_speeds().await);
+    let mut alarms = Box::pin(connection.receive_thermal_alarms().await?);
+
+    let mut snapshot = Snapshot {
+        temperatures: connection.get_temperatures().await?,
+        fan_speeds: connection.get_fan_speeds().await?,
+        target_fan_speeds: connection.get_target_fan_speeds().await?,
+    };
+    snapshot.print(format)?;
+    let mut printed = snapshot.clone();
+
+    loop {
+        tokio::select! {
+            Some(values) = temperatures.next() => snapshot.temperatures = values,
+            Some(values) = fan_speeds.next() => snapshot.fan_speeds = values,
+            Some(values) = target_fan_speeds.next() => snapshot.target_fan_speeds = values,
+            Some(alarm) = alarms.next() => {
+                print_alarm(&alarm, format)?;
+                continue;
+            }
+            else => return Err("Lost the connection to tailord".into()),
+        }
+
+        // Property streams start with the current value, so skip duplicates.
+        if snapshot != printed {
+            snapshot.print(format)?;
+            printed = snapshot.clone();
+        }
+    }
+}
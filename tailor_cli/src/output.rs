@@ -0,0 +1,79 @@
+use tailor_api::{Capabilities, SubsystemStatus};
+
+use crate::Result;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Human,
+    Json,
+}
+
+pub fn print_name(name: &str, format: Format) -> Result<()> {
+    match format {
+        Format::Human => println!("{name}"),
+        Format::Json => println!("{}", serde_json::to_string(name)?),
+    }
+    Ok(())
+}
+
+pub fn print_names(names: &[String], format: Format) -> Result<()> {
+    match format {
+        Format::Human => names.iter().for_each(|name| println!("{name}")),
+        Format::Json => println!("{}", serde_json::to_string_pretty(names)?),
+    }
+    Ok(())
+}
+
+pub fn describe_capabilities(capabilities: &Capabilities) -> String {
+    fn list<T: ToString>(values: &[T]) -> String {
+        if values.is_empty() {
+            "none".to_string()
+        } else {
+            values
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        }
+    }
+
+    [
+        format!("Platform: {}", capabilities.platform),
+        format!("Driver version: {}", capabilities.driver_version),
+        format!("Fans: {}", capabilities.fans),
+        format!(
+            "Temperature sensors: {}",
+            list(&capabilities.temperature_sensors)
+        ),
+        format!("Keyboard zones: {}", list(&capabilities.keyboard_zones)),
+        format!("Keyboard effects: {}", list(&capabilities.keyboard_effects)),
+        format!(
+            "Keyboard brightness: {}",
+            if capabilities.keyboard_brightness {
+                "yes"
+            } else {
+                "no"
+            }
+        ),
+        format!(
+            "Performance profiles: {}",
+            list(&capabilities.performance_profiles)
+        ),
+        format!("Switches: {}", list(&capabilities.switches)),
+    ]
+    .join("\n")
+}
+
+pub fn describe_subsystems(subsystems: &[SubsystemStatus]) -> String {
+    subsystems
+        .iter()
+        .map(|status| {
+            if status.available {
+                format!("{}: available", status.name)
+            } else {
+                format!("{}: unavailable ({})", status.name, status.error)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,399 @@
+use std::{io::Read, path::PathBuf, process::Command};
+
+use clap::Subcommand;
+use serde::{de::DeserializeOwned, Serialize};
+use tailor_api::{ColorPoint, ColorProfile, CpuProfile, FanProfilePoint, ProfileInfo};
+use tailor_client::{ClientResult, TailorConnection};
+
+use crate::{
+    output::{self, Format},
+    Result,
+};
+
+/// Actions that are available for all kinds of profiles.
+#[derive(Debug, Subcommand)]
+pub enum ProfileAction {
+    /// List the names of all profiles.
+    List,
+    /// Print a profile.
+    Show { name: String },
+    /// Add or overwrite a profile with JSON read from a file or stdin.
+    Add {
+        name: String,
+        /// JSON file with the profile. Reads from stdin if omitted.
+        file: Option<PathBuf>,
+    },
+    /// Edit a profile as JSON in `$EDITOR`.
+    Edit { name: String },
+    /// Remove a profile.
+    Remove { name: String },
+    /// Rename a profile and update all references to it.
+    Rename { from: String, to: String },
+    /// Copy a profile.
+    Copy { from: String, to: String },
+}
+
+/// A kind of profile that is managed by tailord.
+pub trait ProfileKind {
+    type Profile: Serialize + DeserializeOwned;
+
+    async fn list(connection: &TailorConnection<'_>) -> ClientResult<Vec<String>>;
+
+    async fn get(connection: &TailorConnection<'_>, name: &str) -> ClientResult<Self::Profile>;
+
+    async fn add(
+        connection: &TailorConnection<'_>,
+        name: &str,
+        profile: &Self::Profile,
+    ) -> ClientResult<()>;
+
+    async fn remove(connection: &TailorConnection<'_>, name: &str) -> ClientResult<()>;
+
+    async fn rename(connection: &TailorConnection<'_>, from: &str, to: &str) -> ClientResult<()>;
+
+    async fn copy(connection: &TailorConnection<'_>, from: &str, to: &str) -> ClientResult<()>;
+
+    /// Human readable description of a profile.
+    fn describe(profile: &Self::Profile) -> String;
+}
+
+pub async fn run<K: ProfileKind>(
+    connection: &TailorConnection<'_>,
+    action: ProfileAction,
+    format: Format,
+) -> Result<()> {
+    match action {
+        ProfileAction::List => {
+            let names = K::list(connection).await?;
+            output::print_names(&names, format)
+        }
+        ProfileAction::Show { name } => {
+            let profile = K::get(connection, &name).await?;
+            match format {
+                Format::Human => println!("{}", K::describe(&profile)),
+                Format::Json => println!("{}", serde_json::to_string_pretty(&profile)?),
+            }
+            Ok(())
+        }
+        ProfileAction::Add { name, file } => {
+            let data = match file {
+                Some(path) => std::fs::read_to_string(path)?,
+                None => {
+                    let mut data = String::new();
+                    std::io::stdin().read_to_string(&mut data)?;
+                    data
+                }
+            };
+            let profile: K::Profile = serde_json::from_str(&data)?;
+            Ok(K::add(connection, &name, &profile).await?)
+        }
+        ProfileAction::Edit { name } => {
+            let profile = K::get(connection, &name).await?;
+            let profile: K::Profile = edit_json(&name, &profile)?;
+            Ok(K::add(connection, &name, &profile).await?)
+        }
+        ProfileAction::Remove { name } => Ok(K::remove(connection, &name).await?),
+        ProfileAction::Rename { from, to } => Ok(K::rename(connection, &from, &to).await?),
+        ProfileAction::Copy { from, to } => Ok(K::copy(connection, &from, &to).await?),
+    }
+}
+
+/// Let the user edit a value as JSON in `$EDITOR`.
+fn edit_json<T: Serialize + DeserializeOwned>(name: &str, value: &T) -> Result<T> {
+    let path = std::env::temp_dir().join(format!("tailor-{}-{name}.json", std::process::id()));
+    std::fs::write(&path, serde_json::to_string_pretty(value)?)?;
+
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    // The editor might contain arguments such as `code --wait`.
+    let mut editor_args = editor.split_whitespace();
+    let status = Command::new(editor_args.next().unwrap_or("vi"))
+        .args(editor_args)
+        .arg(&path)
+        .status();
+
+    let result = match status {
+        Ok(status) if status.success() => std::fs::read_to_string(&path)
+            .map_err(Into::into)
+            .and_then(|data| serde_json::from_str(&data).map_err(Into::into)),
+        Ok(status) => Err(format!("`{editor}` exited with {status}").into()),
+        Err(err) => Err(format!("Couldn't start `{editor}`: `{err}`").into()),
+    };
+    std::fs::remove_file(&path).ok();
+    result
+}
+
+pub struct GlobalProfiles;
+
+impl ProfileKind for GlobalProfiles {
+    type Profile = ProfileInfo;
+
+    async fn list(connection: &TailorConnection<'_>) -> ClientResult<Vec<String>> {
+        connection.list_global_profiles().await
+    }
+
+    async fn get(connection: &TailorConnection<'_>, name: &str) -> ClientResult<ProfileInfo> {
+        connection.get_global_profile(name).await
+    }
+
+    async fn add(
+        connection: &TailorConnection<'_>,
+        name: &str,
+        profile: &ProfileInfo,
+    ) -> ClientResult<()> {
+        connection.add_global_profile(name, profile).await
+    }
+
+    async fn remove(connection: &TailorConnection<'_>, name: &str) -> ClientResult<()> {
+        connection.remove_global_profile(name).await
+    }
+
+    async fn rename(connection: &TailorConnection<'_>, from: &str, to: &str) -> ClientResult<()> {
+        connection.rename_global_profile(from, to).await.map(drop)
+    }
+
+    async fn copy(connection: &TailorConnection<'_>, from: &str, to: &str) -> ClientResult<()> {
+        connection.copy_global_profile(from, to).await
+    }
+
+    fn describe(profile: &ProfileInfo) -> String {
+        let mut lines = vec![format!("Fan: {}", profile.fan)];
+        for (fan, name) in &profile.fan_assignments {
+            lines.push(format!("Fan {fan}: {name}"));
+        }
+        lines.push(format!("Keyboard: {}", profile.keyboard));
+        if let Some(brightness) = profile.keyboard_brightness {
+            lines.push(format!("Keyboard brightness: {brightness}"));
+        }
+        lines.push(format!("CPU: {}", profile.cpu));
+        if !profile.performance_profile.is_empty() {
+            lines.push(format!("Performance: {}", profile.performance_profile));
+        }
+        for (switch, enabled) in &profile.switches {
+            let state = if *enabled { "on" } else { "off" };
+            lines.push(format!("Switch {switch}: {state}"));
+        }
+        lines.join("\n")
+    }
+}
+
+pub struct FanProfiles;
+
+impl ProfileKind for FanProfiles {
+    type Profile = Vec<FanProfilePoint>;
+
+    async fn list(connection: &TailorConnection<'_>) -> ClientResult<Vec<String>> {
+        connection.list_fan_profiles().await
+    }
+
+    async fn get(
+        connection: &TailorConnection<'_>,
+        name: &str,
+    ) -> ClientResult<Vec<FanProfilePoint>> {
+        connection.get_fan_profile(name).await
+    }
+
+    async fn add(
+        connection: &TailorConnection<'_>,
+        name: &str,
+        profile: &Vec<FanProfilePoint>,
+    ) -> ClientResult<()> {
+        connection.add_fan_profile(name, profile).await
+    }
+
+    async fn remove(connection: &TailorConnection<'_>, name: &str) -> ClientResult<()> {
+        connection.remove_fan_profile(name).await
+    }
+
+    async fn rename(connection: &TailorConnection<'_>, from: &str, to: &str) -> ClientResult<()> {
+        connection.rename_fan_profile(from, to).await.map(drop)
+    }
+
+    async fn copy(connection: &TailorConnection<'_>, from: &str, to: &str) -> ClientResult<()> {
+        connection.copy_fan_profile(from, to).await
+    }
+
+    fn describe(profile: &Vec<FanProfilePoint>) -> String {
+        profile
+            .iter()
+            .map(|point| format!("{:>3}°C -> {:>3}%", point.temp, point.fan))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+pub struct KeyboardProfiles;
+
+impl ProfileKind for KeyboardProfiles {
+    type Profile = ColorProfile;
+
+    async fn list(connection: &TailorConnection<'_>) -> ClientResult<Vec<String>> {
+        connection.list_keyboard_profiles().await
+    }
+
+    async fn get(connection: &TailorConnection<'_>, name: &str) -> ClientResult<ColorProfile> {
+        connection.get_keyboard_profile(name).await
+    }
+
+    async fn add(
+        connection: &TailorConnection<'_>,
+        name: &str,
+        profile: &ColorProfile,
+    ) -> ClientResult<()> {
+        connection.add_keyboard_profile(name, profile).await
+    }
+
+    async fn remove(connection: &TailorConnection<'_>, name: &str) -> ClientResult<()> {
+        connection.remove_keyboard_profile(name).await
+    }
+
+    async fn rename(connection: &TailorConnection<'_>, from: &str, to: &str) -> ClientResult<()> {
+        connection.rename_keyboard_profile(from, to).await.map(drop)
+    }
+
+    async fn copy(connection: &TailorConnection<'_>, from: &str, to: &str) -> ClientResult<()> {
+        connection.copy_keyboard_profile(from, to).await
+    }
+
+    fn describe(profile: &ColorProfile) -> String {
+        match profile {
+            ColorProfile::None => "No color".to_string(),
+            ColorProfile::Single(color) => format!("Single color {color}"),
+            ColorProfile::Multiple(points) => describe_points(points),
+            ColorProfile::Effect(effect) => format!("Firmware effect {effect}"),
+            ColorProfile::Zones(zones) => zones
+                .iter()
+                .map(|zone| {
+                    format!(
+                        "Zone {} with an offset of {} ms:\n{}",
+                        zone.zone,
+                        zone.offset,
+                        describe_points(&zone.points)
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+            ColorProfile::Temperature(gradient) => {
+                let points = gradient
+                    .points
+                    .iter()
+                    .map(|point| format!("{} at {}°C", point.color, point.temp))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                format!("CPU temperature ({}):\n{points}", gradient.color_space)
+            }
+        }
+    }
+}
+
+fn describe_points(points: &[ColorPoint]) -> String {
+    points
+        .iter()
+        .map(|point| {
+            format!(
+                "{} {} transition in {} ms ({})",
+                point.color, point.transition, point.transition_time, point.color_space
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub struct CpuProfiles;
+
+impl ProfileKind for CpuProfiles {
+    type Profile = CpuProfile;
+
+    async fn list(connection: &TailorConnection<'_>) -> ClientResult<Vec<String>> {
+        connection.list_cpu_profiles().await
+    }
+
+    async fn get(connection: &TailorConnection<'_>, name: &str) -> ClientResult<CpuProfile> {
+        connection.get_cpu_profile(name).await
+    }
+
+    async fn add(
+        connection: &TailorConnection<'_>,
+        name: &str,
+        profile: &CpuProfile,
+    ) -> ClientResult<()> {
+        connection.add_cpu_profile(name, profile).await
+    }
+
+    async fn remove(connection: &TailorConnection<'_>, name: &str) -> ClientResult<()> {
+        connection.remove_cpu_profile(name).await
+    }
+
+    async fn rename(connection: &TailorConnection<'_>, from: &str, to: &str) -> ClientResult<()> {
+        connection.rename_cpu_profile(from, to).await.map(drop)
+    }
+
+    async fn copy(connection: &TailorConnection<'_>, from: &str, to: &str) -> ClientResult<()> {
+        connection.copy_cpu_profile(from, to).await
+    }
+
+    fn describe(profile: &CpuProfile) -> String {
+        let or_unchanged = |value: &str| match value {
+            "" => "unchanged".to_string(),
+            value => value.to_string(),
+        };
+        let freq = |freq: u32, default: &str| match freq {
+            0 => default.to_string(),
+            freq => format!("{} MHz", freq / 1000),
+        };
+        let boost = match profile.boost {
+            Some(true) => "on",
+            Some(false) => "off",
+            None => "unchanged",
+        };
+        let online_cores = match profile.online_cores {
+            0 => "unchanged".to_string(),
+            cores => cores.to_string(),
+        };
+        [
+            format!("Governor: {}", or_unchanged(&profile.governor)),
+            format!("Minimum frequency: {}", freq(profile.min_freq, "lowest")),
+            format!("Maximum frequency: {}", freq(profile.max_freq, "highest")),
+            format!("Boost: {boost}"),
+            format!(
+                "Energy performance preference: {}",
+                or_unchanged(&profile.energy_performance_preference)
+            ),
+            format!("Online cores: {online_cores}"),
+        ]
+        .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tailor_api::{FanProfilePoint, ProfileInfo};
+
+    use super::{FanProfiles, GlobalProfiles, ProfileKind};
+
+    #[test]
+    fn describe_profiles() {
+        let mut info = ProfileInfo::default();
+        info.fan_assignments.insert(1, "gpu".to_string());
+        assert_eq!(
+            GlobalProfiles::describe(&info),
+            "Fan: default\nFan 1: gpu\nKeyboard: default\nCPU: default"
+        );
+        info.keyboard_brightness = Some(128);
+        assert!(GlobalProfiles::describe(&info).contains("\nKeyboard brightness: 128\n"));
+        info.performance_profile = "quiet".to_string();
+        assert!(GlobalProfiles::describe(&info).ends_with("\nPerformance: quiet"));
+        info.switches.insert("webcam".to_string(), false);
+        assert!(GlobalProfiles::describe(&info).ends_with("\nSwitch webcam: off"));
+
+        let profile = vec![
+            FanProfilePoint { temp: 30, fan: 0 },
+            FanProfilePoint { temp: 90, fan: 100 },
+        ];
+        assert_eq!(
+            FanProfiles::describe(&profile),
+            " 30°C ->   0%\n 90°C -> 100%"
+        );
+    }
+}
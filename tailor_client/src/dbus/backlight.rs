@@ -0,0 +1,16 @@
+use zbus::{dbus_proxy, fdo};
+
+#[dbus_proxy(
+    interface = "com.tux.Tailor.KeyboardBacklight",
+    default_service = "com.tux.Tailor",
+    default_path = "/com/tux/Tailor"
+)]
+trait KeyboardBacklight {
+    #[dbus_proxy(property)]
+    fn brightness(&self) -> fdo::Result<u8>;
+
+    #[dbus_proxy(property)]
+    fn max_brightness(&self) -> fdo::Result<u8>;
+
+    fn set_brightness(&self, brightness: u8) -> fdo::Result<()>;
+}
@@ -0,0 +1,19 @@
+use tailor_api::CpuProfile;
+use zbus::{dbus_proxy, fdo};
+
+#[dbus_proxy(
+    interface = "com.tux.Tailor2.Cpu",
+    default_service = "com.tux.Tailor",
+    default_path = "/com/tux/Tailor"
+)]
+trait Cpu {
+    async fn add_profile(&self, name: &str, profile: &CpuProfile) -> fdo::Result<()>;
+
+    async fn get_profile(&self, name: &str) -> fdo::Result<CpuProfile>;
+
+    async fn list_profiles(&self) -> fdo::Result<Vec<String>>;
+
+    async fn remove_profile(&self, name: &str) -> fdo::Result<()>;
+
+    async fn rename_profile(&self, from: &str, to: &str) -> fdo::Result<Vec<String>>;
+}
@@ -0,0 +1,16 @@
+use tailor_api::{Capabilities, SubsystemStatus};
+use zbus::{dbus_proxy, fdo};
+
+#[dbus_proxy(
+    interface = "com.tux.Tailor.Device",
+    default_service = "com.tux.Tailor",
+    default_path = "/com/tux/Tailor"
+)]
+trait Device {
+    async fn get_capabilities(&self) -> fdo::Result<Capabilities>;
+
+    async fn get_subsystems(&self) -> fdo::Result<Vec<SubsystemStatus>>;
+
+    #[dbus_proxy(signal)]
+    async fn subsystems_changed(&self, subsystems: Vec<SubsystemStatus>) -> fdo::Result<()>;
+}
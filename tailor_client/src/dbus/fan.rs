@@ -1,14 +1,15 @@
+use tailor_api::FanProfilePoint;
 use zbus::{dbus_proxy, fdo};
 
 #[dbus_proxy(
-    interface = "com.tux.Tailor.Fan",
+    interface = "com.tux.Tailor2.Fan",
     default_service = "com.tux.Tailor",
     default_path = "/com/tux/Tailor"
 )]
 trait Fan {
-    async fn add_profile(&self, name: &str, value: &str) -> fdo::Result<()>;
+    async fn add_profile(&self, name: &str, profile: &[FanProfilePoint]) -> fdo::Result<()>;
 
-    async fn get_profile(&self, name: &str) -> fdo::Result<String>;
+    async fn get_profile(&self, name: &str) -> fdo::Result<Vec<FanProfilePoint>>;
 
     async fn list_profiles(&self) -> fdo::Result<Vec<String>>;
 
@@ -17,4 +18,11 @@ trait Fan {
     async fn rename_profile(&self, from: &str, to: &str) -> fdo::Result<Vec<String>>;
 
     async fn override_speed(&self, speed: u8) -> fdo::Result<()>;
+
+    async fn override_single_speed(&self, fan: u8, speed: u8) -> fdo::Result<()>;
+
+    async fn get_number_of_fans(&self) -> fdo::Result<u8>;
+
+    #[dbus_proxy(signal)]
+    fn thermal_alarm(&self, fan: u8, active: bool, reason: &str) -> fdo::Result<()>;
 }
@@ -1,14 +1,15 @@
+use tailor_api::{Color, ColorProfileVariant};
 use zbus::{dbus_proxy, fdo};
 
 #[dbus_proxy(
-    interface = "com.tux.Tailor.Keyboard",
+    interface = "com.tux.Tailor2.Keyboard",
     default_service = "com.tux.Tailor",
     default_path = "/com/tux/Tailor"
 )]
 trait Keyboard {
-    async fn add_profile(&self, name: &str, value: &str) -> fdo::Result<()>;
+    async fn add_profile(&self, name: &str, profile: &ColorProfileVariant) -> fdo::Result<()>;
 
-    async fn get_profile(&self, name: &str) -> fdo::Result<String>;
+    async fn get_profile(&self, name: &str) -> fdo::Result<ColorProfileVariant>;
 
     async fn list_profiles(&self) -> fdo::Result<Vec<String>>;
 
@@ -16,5 +17,5 @@ trait Keyboard {
 
     async fn rename_profile(&self, from: &str, to: &str) -> fdo::Result<Vec<String>>;
 
-    async fn override_color(&self, color: &str) -> fdo::Result<()>;
+    async fn override_color(&self, color: &Color) -> fdo::Result<()>;
 }
@@ -1,7 +1,21 @@
+mod backlight;
+mod cpu;
+mod device;
 mod fan;
 mod keyboard;
+mod performance;
+mod power;
 mod profiles;
+mod switches;
+mod telemetry;
 
+pub(crate) use backlight::KeyboardBacklightProxy;
+pub(crate) use cpu::CpuProxy;
+pub(crate) use device::DeviceProxy;
 pub(crate) use fan::FanProxy;
 pub(crate) use keyboard::KeyboardProxy;
+pub(crate) use performance::PerformanceProxy;
+pub(crate) use power::PowerProxy;
 pub(crate) use profiles::ProfilesProxy;
+pub(crate) use switches::SwitchesProxy;
+pub(crate) use telemetry::TelemetryProxy;
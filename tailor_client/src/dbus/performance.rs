@@ -0,0 +1,14 @@
+use zbus::{dbus_proxy, fdo};
+
+#[dbus_proxy(
+    interface = "com.tux.Tailor.Performance",
+    default_service = "com.tux.Tailor",
+    default_path = "/com/tux/Tailor"
+)]
+trait Performance {
+    async fn set_profile(&self, name: &str) -> fdo::Result<()>;
+
+    async fn get_profile(&self) -> fdo::Result<String>;
+
+    async fn list_profiles(&self) -> fdo::Result<Vec<String>>;
+}
@@ -0,0 +1,16 @@
+use tailor_api::PowerRule;
+use zbus::{dbus_proxy, fdo};
+
+#[dbus_proxy(
+    interface = "com.tux.Tailor.Power",
+    default_service = "com.tux.Tailor",
+    default_path = "/com/tux/Tailor"
+)]
+trait Power {
+    async fn set_rule(&self, rule: &PowerRule) -> fdo::Result<()>;
+
+    async fn get_rule(&self) -> fdo::Result<PowerRule>;
+
+    #[dbus_proxy(property)]
+    fn on_battery(&self) -> fdo::Result<bool>;
+}
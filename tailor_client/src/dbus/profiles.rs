@@ -1,14 +1,15 @@
+use tailor_api::ProfileInfo;
 use zbus::{dbus_proxy, fdo};
 
 #[dbus_proxy(
-    interface = "com.tux.Tailor.Profiles",
+    interface = "com.tux.Tailor2.Profiles",
     default_service = "com.tux.Tailor",
     default_path = "/com/tux/Tailor"
 )]
 trait Profiles {
-    async fn add_profile(&self, name: &str, value: &str) -> fdo::Result<()>;
+    async fn add_profile(&self, name: &str, profile: &ProfileInfo) -> fdo::Result<()>;
 
-    async fn get_profile(&self, name: &str) -> fdo::Result<String>;
+    async fn get_profile(&self, name: &str) -> fdo::Result<ProfileInfo>;
 
     async fn list_profiles(&self) -> fdo::Result<Vec<String>>;
 
@@ -21,4 +22,7 @@ trait Profiles {
     async fn get_active_profile_name(&self) -> fdo::Result<String>;
 
     async fn reload(&self) -> fdo::Result<()>;
+
+    #[dbus_proxy(signal)]
+    fn active_profile_changed(&self, name: &str) -> fdo::Result<()>;
 }
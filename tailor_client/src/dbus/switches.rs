@@ -0,0 +1,26 @@
+use zbus::{dbus_proxy, fdo};
+
+#[dbus_proxy(
+    interface = "com.tux.Tailor.Switches",
+    default_service = "com.tux.Tailor",
+    default_path = "/com/tux/Tailor"
+)]
+trait Switches {
+    #[dbus_proxy(property)]
+    fn webcam(&self) -> fdo::Result<bool>;
+
+    #[dbus_proxy(property)]
+    fn set_webcam(&self, enabled: bool) -> fdo::Result<()>;
+
+    #[dbus_proxy(property)]
+    fn touchpad(&self) -> fdo::Result<bool>;
+
+    #[dbus_proxy(property)]
+    fn set_touchpad(&self, enabled: bool) -> fdo::Result<()>;
+
+    #[dbus_proxy(property)]
+    fn flight_mode(&self) -> fdo::Result<bool>;
+
+    #[dbus_proxy(property)]
+    fn set_flight_mode(&self, enabled: bool) -> fdo::Result<()>;
+}
@@ -0,0 +1,20 @@
+use zbus::{dbus_proxy, fdo};
+
+#[dbus_proxy(
+    interface = "com.tux.Tailor.Telemetry",
+    default_service = "com.tux.Tailor",
+    default_path = "/com/tux/Tailor"
+)]
+trait Telemetry {
+    #[dbus_proxy(property)]
+    fn temperatures(&self) -> fdo::Result<Vec<u8>>;
+
+    #[dbus_proxy(property)]
+    fn fan_speeds(&self) -> fdo::Result<Vec<u8>>;
+
+    #[dbus_proxy(property)]
+    fn target_fan_speeds(&self) -> fdo::Result<Vec<u8>>;
+
+    #[dbus_proxy(property)]
+    fn active_override(&self) -> fdo::Result<Vec<(u8, u8)>>;
+}
@@ -5,6 +5,8 @@ use zbus::fdo;
 pub enum ClientError {
     #[error("Bus response error: `{0}`")]
     BusError(#[from] fdo::Error),
+    #[error("Bus error: `{0}`")]
+    Bus(#[from] zbus::Error),
     #[error("Serialization error: `{0}`")]
     Serialization(#[from] serde_json::Error),
 }
@@ -4,8 +4,12 @@ mod dbus;
 mod error;
 
 pub use error::ClientError;
-use tailor_api::{Color, ColorProfile, FanProfilePoint, ProfileInfo};
-use zbus::Connection;
+use futures_util::{Stream, StreamExt};
+use tailor_api::{
+    Capabilities, Color, ColorProfile, ColorProfileVariant, CpuProfile, FanProfilePoint, PowerRule,
+    ProfileChange, ProfileInfo, SubsystemStatus, Switch, ThermalAlarm,
+};
+use zbus::{zvariant::OwnedValue, Connection, PropertyStream};
 
 pub type ClientResult<T> = Result<T, ClientError>;
 
@@ -13,7 +17,14 @@ pub type ClientResult<T> = Result<T, ClientError>;
 pub struct TailorConnection<'a> {
     profiles: dbus::ProfilesProxy<'a>,
     keyboard: dbus::KeyboardProxy<'a>,
+    backlight: dbus::KeyboardBacklightProxy<'a>,
     fan: dbus::FanProxy<'a>,
+    cpu: dbus::CpuProxy<'a>,
+    telemetry: dbus::TelemetryProxy<'a>,
+    power: dbus::PowerProxy<'a>,
+    performance: dbus::PerformanceProxy<'a>,
+    switches: dbus::SwitchesProxy<'a>,
+    device: dbus::DeviceProxy<'a>,
 }
 
 impl<'a> TailorConnection<'a> {
@@ -22,12 +33,26 @@ impl<'a> TailorConnection<'a> {
 
         let profiles = dbus::ProfilesProxy::new(&connection).await?;
         let keyboard = dbus::KeyboardProxy::new(&connection).await?;
+        let backlight = dbus::KeyboardBacklightProxy::new(&connection).await?;
         let fan = dbus::FanProxy::new(&connection).await?;
+        let cpu = dbus::CpuProxy::new(&connection).await?;
+        let telemetry = dbus::TelemetryProxy::new(&connection).await?;
+        let power = dbus::PowerProxy::new(&connection).await?;
+        let performance = dbus::PerformanceProxy::new(&connection).await?;
+        let switches = dbus::SwitchesProxy::new(&connection).await?;
+        let device = dbus::DeviceProxy::new(&connection).await?;
 
         Ok(Self {
             profiles,
             keyboard,
+            backlight,
             fan,
+            cpu,
+            telemetry,
+            power,
+            performance,
+            switches,
+            device,
         })
     }
 }
@@ -38,13 +63,13 @@ impl<'a> TailorConnection<'a> {
         name: &str,
         profile: &ColorProfile,
     ) -> ClientResult<()> {
-        let value = serde_json::to_string(profile)?;
-        Ok(self.keyboard.add_profile(name, &value).await?)
+        let profile = ColorProfileVariant::from(profile);
+        Ok(self.keyboard.add_profile(name, &profile).await?)
     }
 
     pub async fn get_keyboard_profile(&self, name: &str) -> ClientResult<ColorProfile> {
-        let profile_data = self.keyboard.get_profile(name).await?;
-        Ok(serde_json::from_str(&profile_data)?)
+        let profile = self.keyboard.get_profile(name).await?;
+        Ok(ColorProfile::try_from(profile).map_err(zbus::Error::from)?)
     }
 
     pub async fn list_keyboard_profiles(&self) -> ClientResult<Vec<String>> {
@@ -64,9 +89,36 @@ impl<'a> TailorConnection<'a> {
         Ok(self.keyboard.remove_profile(name).await?)
     }
 
+    /// Receive a message whenever a keyboard profile is added, removed or renamed.
+    pub async fn receive_keyboard_profile_changes(
+        &self,
+    ) -> ClientResult<impl Stream<Item = ProfileChange> + 'a> {
+        receive_profile_changes(self.keyboard.inner()).await
+    }
+
     pub async fn override_keyboard_color(&self, color: &Color) -> ClientResult<()> {
-        let value = serde_json::to_string(color)?;
-        Ok(self.keyboard.override_color(&value).await?)
+        Ok(self.keyboard.override_color(color).await?)
+    }
+
+    /// Change the keyboard brightness until another global profile is activated.
+    ///
+    /// Fails if the device has no brightness control,
+    /// see [`Capabilities::keyboard_brightness`].
+    pub async fn set_keyboard_brightness(&self, brightness: u8) -> ClientResult<()> {
+        Ok(self.backlight.set_brightness(brightness).await?)
+    }
+
+    pub async fn get_keyboard_brightness(&self) -> ClientResult<u8> {
+        Ok(self.backlight.brightness().await?)
+    }
+
+    pub async fn get_max_keyboard_brightness(&self) -> ClientResult<u8> {
+        Ok(self.backlight.max_brightness().await?)
+    }
+
+    /// Receive the keyboard brightness whenever it changes.
+    pub async fn receive_keyboard_brightness(&self) -> impl Stream<Item = u8> + 'a {
+        property_values(self.backlight.receive_brightness_changed().await)
     }
 }
 
@@ -76,13 +128,11 @@ impl<'a> TailorConnection<'a> {
         name: &str,
         profile: &[FanProfilePoint],
     ) -> ClientResult<()> {
-        let value = serde_json::to_string(profile)?;
-        Ok(self.fan.add_profile(name, &value).await?)
+        Ok(self.fan.add_profile(name, profile).await?)
     }
 
     pub async fn get_fan_profile(&self, name: &str) -> ClientResult<Vec<FanProfilePoint>> {
-        let profile_data = self.fan.get_profile(name).await?;
-        Ok(serde_json::from_str(&profile_data)?)
+        Ok(self.fan.get_profile(name).await?)
     }
 
     pub async fn list_fan_profiles(&self) -> ClientResult<Vec<String>> {
@@ -102,20 +152,85 @@ impl<'a> TailorConnection<'a> {
         Ok(self.fan.remove_profile(name).await?)
     }
 
+    /// Receive a message whenever a fan profile is added, removed or renamed.
+    pub async fn receive_fan_profile_changes(
+        &self,
+    ) -> ClientResult<impl Stream<Item = ProfileChange> + 'a> {
+        receive_profile_changes(self.fan.inner()).await
+    }
+
+    /// Override the speed of all fans for 1s.
     pub async fn override_fan_speed(&self, speed: u8) -> ClientResult<()> {
         Ok(self.fan.override_speed(speed).await?)
     }
+
+    /// Override the speed of a single fan (indexed from 0) for 1s.
+    pub async fn override_single_fan_speed(&self, fan: u8, speed: u8) -> ClientResult<()> {
+        Ok(self.fan.override_single_speed(fan, speed).await?)
+    }
+
+    pub async fn get_number_of_fans(&self) -> ClientResult<u8> {
+        Ok(self.fan.get_number_of_fans().await?)
+    }
+
+    /// Receive a message whenever the thermal watchdog of a fan
+    /// raises or clears an alarm.
+    pub async fn receive_thermal_alarms(
+        &self,
+    ) -> ClientResult<impl Stream<Item = ThermalAlarm> + 'a> {
+        let stream = self.fan.receive_thermal_alarm().await?;
+        Ok(stream.filter_map(|signal| async move {
+            let args = signal.args().ok()?;
+            Some(ThermalAlarm {
+                fan: args.fan,
+                active: args.active,
+                reason: args.reason.to_string(),
+            })
+        }))
+    }
+}
+
+impl<'a> TailorConnection<'a> {
+    pub async fn add_cpu_profile(&self, name: &str, profile: &CpuProfile) -> ClientResult<()> {
+        Ok(self.cpu.add_profile(name, profile).await?)
+    }
+
+    pub async fn get_cpu_profile(&self, name: &str) -> ClientResult<CpuProfile> {
+        Ok(self.cpu.get_profile(name).await?)
+    }
+
+    pub async fn list_cpu_profiles(&self) -> ClientResult<Vec<String>> {
+        Ok(self.cpu.list_profiles().await?)
+    }
+
+    pub async fn copy_cpu_profile(&self, from: &str, to: &str) -> ClientResult<()> {
+        let profile = self.get_cpu_profile(from).await?;
+        self.add_cpu_profile(to, &profile).await
+    }
+
+    pub async fn rename_cpu_profile(&self, from: &str, to: &str) -> ClientResult<Vec<String>> {
+        Ok(self.cpu.rename_profile(from, to).await?)
+    }
+
+    pub async fn remove_cpu_profile(&self, name: &str) -> ClientResult<()> {
+        Ok(self.cpu.remove_profile(name).await?)
+    }
+
+    /// Receive a message whenever a CPU profile is added, removed or renamed.
+    pub async fn receive_cpu_profile_changes(
+        &self,
+    ) -> ClientResult<impl Stream<Item = ProfileChange> + 'a> {
+        receive_profile_changes(self.cpu.inner()).await
+    }
 }
 
 impl<'a> TailorConnection<'a> {
     pub async fn add_global_profile(&self, name: &str, profile: &ProfileInfo) -> ClientResult<()> {
-        let value = serde_json::to_string(profile)?;
-        Ok(self.profiles.add_profile(name, &value).await?)
+        Ok(self.profiles.add_profile(name, profile).await?)
     }
 
     pub async fn get_global_profile(&self, name: &str) -> ClientResult<ProfileInfo> {
-        let profile_data = self.profiles.get_profile(name).await?;
-        Ok(serde_json::from_str(&profile_data)?)
+        Ok(self.profiles.get_profile(name).await?)
     }
 
     pub async fn list_global_profiles(&self) -> ClientResult<Vec<String>> {
@@ -135,6 +250,21 @@ impl<'a> TailorConnection<'a> {
         Ok(self.profiles.remove_profile(name).await?)
     }
 
+    /// Receive a message whenever a global profile is added, removed or renamed.
+    pub async fn receive_global_profile_changes(
+        &self,
+    ) -> ClientResult<impl Stream<Item = ProfileChange> + 'a> {
+        receive_profile_changes(self.profiles.inner()).await
+    }
+
+    /// Receive the name of the active global profile whenever it changes.
+    pub async fn receive_active_global_profile_changes(
+        &self,
+    ) -> ClientResult<impl Stream<Item = String> + 'a> {
+        let stream = self.profiles.receive_active_profile_changed().await?;
+        Ok(stream.filter_map(|signal| async move { Some(signal.args().ok()?.name.to_string()) }))
+    }
+
     pub async fn get_active_global_profile_name(&self) -> ClientResult<String> {
         Ok(self.profiles.get_active_profile_name().await?)
     }
@@ -147,3 +277,171 @@ impl<'a> TailorConnection<'a> {
         Ok(self.profiles.reload().await?)
     }
 }
+
+/// Live values of the fan control.
+///
+/// All values are indexed by fan, starting at 0.
+impl<'a> TailorConnection<'a> {
+    /// The current temperatures in °C, `0` for fans that weren't read yet.
+    pub async fn get_temperatures(&self) -> ClientResult<Vec<u8>> {
+        Ok(self.telemetry.temperatures().await?)
+    }
+
+    /// The current fan speeds in percent.
+    pub async fn get_fan_speeds(&self) -> ClientResult<Vec<u8>> {
+        Ok(self.telemetry.fan_speeds().await?)
+    }
+
+    /// The fan speeds (in percent) the fans approach according to their fan curves.
+    pub async fn get_target_fan_speeds(&self) -> ClientResult<Vec<u8>> {
+        Ok(self.telemetry.target_fan_speeds().await?)
+    }
+
+    /// `(fan, speed)` pairs of all fans whose speed is currently overridden.
+    pub async fn get_active_fan_override(&self) -> ClientResult<Vec<(u8, u8)>> {
+        Ok(self.telemetry.active_override().await?)
+    }
+
+    /// Receive the temperatures whenever they change.
+    pub async fn receive_temperatures(&self) -> impl Stream<Item = Vec<u8>> + 'a {
+        property_values(self.telemetry.receive_temperatures_changed().await)
+    }
+
+    /// Receive the fan speeds whenever they change.
+    pub async fn receive_fan_speeds(&self) -> impl Stream<Item = Vec<u8>> + 'a {
+        property_values(self.telemetry.receive_fan_speeds_changed().await)
+    }
+
+    /// Receive the target fan speeds whenever they change.
+    pub async fn receive_target_fan_speeds(&self) -> impl Stream<Item = Vec<u8>> + 'a {
+        property_values(self.telemetry.receive_target_fan_speeds_changed().await)
+    }
+
+    /// Receive the overridden fans whenever an override starts or ends.
+    pub async fn receive_active_fan_override(&self) -> impl Stream<Item = Vec<(u8, u8)>> + 'a {
+        property_values(self.telemetry.receive_active_override_changed().await)
+    }
+}
+
+impl<'a> TailorConnection<'a> {
+    /// Set the global profiles that are activated automatically
+    /// when the laptop is plugged in or unplugged.
+    pub async fn set_power_rule(&self, rule: &PowerRule) -> ClientResult<()> {
+        Ok(self.power.set_rule(rule).await?)
+    }
+
+    pub async fn get_power_rule(&self) -> ClientResult<PowerRule> {
+        Ok(self.power.get_rule().await?)
+    }
+
+    /// `false` on AC power or if tailord couldn't find a charger.
+    pub async fn is_on_battery(&self) -> ClientResult<bool> {
+        Ok(self.power.on_battery().await?)
+    }
+
+    /// Receive the power state whenever the laptop is plugged in or unplugged.
+    pub async fn receive_on_battery(&self) -> impl Stream<Item = bool> + 'a {
+        property_values(self.power.receive_on_battery_changed().await)
+    }
+}
+
+impl<'a> TailorConnection<'a> {
+    /// Set the performance profile until another global profile is activated.
+    pub async fn set_performance_profile(&self, name: &str) -> ClientResult<()> {
+        Ok(self.performance.set_profile(name).await?)
+    }
+
+    /// Returns an empty string if tailord doesn't know the current performance profile.
+    pub async fn get_performance_profile(&self) -> ClientResult<String> {
+        Ok(self.performance.get_profile().await?)
+    }
+
+    /// List the performance profiles that are supported by the device.
+    pub async fn list_performance_profiles(&self) -> ClientResult<Vec<String>> {
+        Ok(self.performance.list_profiles().await?)
+    }
+}
+
+impl<'a> TailorConnection<'a> {
+    /// Fails if the switch isn't available on the device.
+    pub async fn set_switch(&self, switch: Switch, enabled: bool) -> ClientResult<()> {
+        match switch {
+            Switch::Webcam => self.switches.set_webcam(enabled).await?,
+            Switch::Touchpad => self.switches.set_touchpad(enabled).await?,
+            Switch::FlightMode => self.switches.set_flight_mode(enabled).await?,
+        }
+        Ok(())
+    }
+
+    /// Returns `false` if the switch isn't available on the device.
+    pub async fn get_switch(&self, switch: Switch) -> ClientResult<bool> {
+        Ok(match switch {
+            Switch::Webcam => self.switches.webcam().await?,
+            Switch::Touchpad => self.switches.touchpad().await?,
+            Switch::FlightMode => self.switches.flight_mode().await?,
+        })
+    }
+
+    /// Receive the state of a switch whenever it changes.
+    pub async fn receive_switch(&self, switch: Switch) -> impl Stream<Item = bool> + 'a {
+        property_values(match switch {
+            Switch::Webcam => self.switches.receive_webcam_changed().await,
+            Switch::Touchpad => self.switches.receive_touchpad_changed().await,
+            Switch::FlightMode => self.switches.receive_flight_mode_changed().await,
+        })
+    }
+}
+
+impl<'a> TailorConnection<'a> {
+    /// Returns the hardware features of the device, which
+    /// allows hiding everything that isn't supported.
+    pub async fn get_capabilities(&self) -> ClientResult<Capabilities> {
+        Ok(self.device.get_capabilities().await?)
+    }
+
+    /// Returns which parts of the hardware tailord is controlling.
+    ///
+    /// Missing devices are looked for again periodically,
+    /// so unavailable subsystems may become available later on.
+    pub async fn get_subsystems(&self) -> ClientResult<Vec<SubsystemStatus>> {
+        Ok(self.device.get_subsystems().await?)
+    }
+
+    /// Emits the state of all subsystems whenever one of them changes.
+    ///
+    /// The capabilities should be fetched again afterwards.
+    pub async fn receive_subsystem_changes(
+        &self,
+    ) -> ClientResult<impl Stream<Item = Vec<SubsystemStatus>> + 'a> {
+        let stream = self.device.receive_subsystems_changed().await?;
+        Ok(stream.filter_map(|signal| async move { Some(signal.args().ok()?.subsystems) }))
+    }
+}
+
+fn property_values<'a, T>(stream: PropertyStream<'a, T>) -> impl Stream<Item = T> + 'a
+where
+    T: TryFrom<OwnedValue> + Unpin + 'a,
+    T::Error: Into<zbus::Error>,
+{
+    stream.filter_map(|change| async move { change.get().await.ok() })
+}
+
+/// Receive the signals about added, removed and renamed profiles of an interface.
+///
+/// All signals are received through a single stream to preserve their order.
+async fn receive_profile_changes<'a>(
+    proxy: &zbus::Proxy<'a>,
+) -> ClientResult<impl Stream<Item = ProfileChange> + 'a> {
+    let stream = proxy.receive_all_signals().await?;
+    Ok(stream.filter_map(|message| async move {
+        match message.member()?.as_str() {
+            "ProfileAdded" => Some(ProfileChange::Added(message.body().ok()?)),
+            "ProfileRemoved" => Some(ProfileChange::Removed(message.body().ok()?)),
+            "ProfileRenamed" => {
+                let (from, to) = message.body().ok()?;
+                Some(ProfileChange::Renamed { from, to })
+            }
+            _ => None,
+        }
+    }))
+}
@@ -0,0 +1,41 @@
+#!/bin/sh
+
+# Run the client tests against a tailord instance that uses a
+# simulated device, a temporary configuration directory and a
+# private D-Bus daemon. No TUXEDO hardware or root access is needed.
+
+set -e
+
+cd "$(dirname "$0")/../.."
+
+TMP_DIR=$(mktemp -d)
+CONFIG_DIR="$TMP_DIR/config"
+BUS_ADDRESS="unix:path=$TMP_DIR/bus"
+
+cleanup() {
+    [ -n "$TAILORD_PID" ] && kill "$TAILORD_PID" 2>/dev/null || true
+    [ -n "$DBUS_PID" ] && kill "$DBUS_PID" 2>/dev/null || true
+    rm -rf "$TMP_DIR"
+}
+trap cleanup EXIT
+
+mkdir -p "$CONFIG_DIR"
+cp -r tailord/default_configs/* "$CONFIG_DIR"
+ln -s profiles/default.json "$CONFIG_DIR/active_profile.json"
+
+DBUS_PID=$(dbus-daemon --session --address="$BUS_ADDRESS" --fork --print-pid)
+export DBUS_SYSTEM_BUS_ADDRESS="$BUS_ADDRESS"
+
+cargo build -p tux-tailord
+target/debug/tux-tailord --simulated --upower-kbd-backlight --config-dir "$CONFIG_DIR" &
+TAILORD_PID=$!
+
+# Wait until tailord owns its bus name.
+for _ in $(seq 50); do
+    if busctl --address="$BUS_ADDRESS" status com.tux.Tailor >/dev/null 2>&1; then
+        break
+    fi
+    sleep 0.1
+done
+
+cargo test -p tailor_client "$@"
@@ -1,4 +1,9 @@
-use tailor_api::{Color, ColorPoint, ColorProfile, ColorTransition, FanProfilePoint};
+use futures_util::StreamExt;
+use tailor_api::{
+    Color, ColorPoint, ColorProfile, ColorSpace, ColorTransition, CpuProfile, FanProfilePoint,
+    KeyboardEffect, KeyboardZone, PowerRule, ProfileChange, ProfileInfo, Switch, TemperatureColor,
+    TemperatureGradient, ZoneColors,
+};
 use tailor_client::TailorConnection;
 
 #[tokio::test]
@@ -118,6 +123,16 @@ async fn test_fan() {
         FanProfilePoint { temp: 70, fan: 100 },
     ];
 
+    // Override the speed of all fans and of a single fan
+    let fans = connection.get_number_of_fans().await.unwrap();
+    assert!(fans > 0);
+    connection.override_fan_speed(50).await.unwrap();
+    connection.override_single_fan_speed(0, 60).await.unwrap();
+    connection
+        .override_single_fan_speed(fans, 60)
+        .await
+        .unwrap_err();
+
     // Add profile
     connection.add_fan_profile(name, &profile).await.unwrap();
     // Overwrite profile
@@ -131,11 +146,30 @@ async fn test_fan() {
         .unwrap()
         .contains(&name.to_owned()));
 
+    // Assign the profile to the second fan of a global profile
+    let global_name = "__test_fan_global_profile";
+    let mut global_profile = ProfileInfo::default();
+    global_profile.fan_assignments.insert(1, name.to_owned());
+    connection
+        .add_global_profile(global_name, &global_profile)
+        .await
+        .unwrap();
+
     // Rename profile
     connection
         .rename_fan_profile(name, second_name)
         .await
         .unwrap();
+    // Renaming should update the fan assignment
+    assert_eq!(
+        connection
+            .get_global_profile(global_name)
+            .await
+            .unwrap()
+            .fan_profile(1),
+        second_name
+    );
+    connection.remove_global_profile(global_name).await.unwrap();
     // List should contain new name
     assert!(connection
         .list_fan_profiles()
@@ -184,16 +218,24 @@ async fn test_keyboard() {
             color: Color { r: 0, g: 255, b: 0 },
             transition: ColorTransition::Linear,
             transition_time: 3000,
+            color_space: ColorSpace::Rgb,
         },
         ColorPoint {
             color: Color { r: 255, g: 0, b: 0 },
-            transition: ColorTransition::Linear,
+            transition: ColorTransition::EaseInOut,
             transition_time: 3000,
+            color_space: ColorSpace::Hsv,
         },
         ColorPoint {
             color: Color { r: 0, g: 0, b: 255 },
-            transition: ColorTransition::Linear,
+            transition: ColorTransition::CubicBezier {
+                x1: 250,
+                y1: 100,
+                x2: 250,
+                y2: 1000,
+            },
             transition_time: 3000,
+            color_space: ColorSpace::Oklab,
         },
     ]);
 
@@ -262,4 +304,371 @@ async fn test_keyboard() {
         .remove_keyboard_profile(second_name)
         .await
         .unwrap_err();
+
+    // Firmware effects and zones are stored like any other profile
+    let zone = |zone, offset| ZoneColors {
+        zone,
+        points: vec![ColorPoint {
+            color: Color { r: 0, g: 0, b: 255 },
+            transition: ColorTransition::Steps(4),
+            transition_time: 1000,
+            color_space: ColorSpace::Rgb,
+        }],
+        offset,
+    };
+    let profiles = [
+        ColorProfile::Effect(KeyboardEffect::Wave),
+        ColorProfile::Zones(vec![
+            zone(KeyboardZone::Left, 0),
+            zone(KeyboardZone::Right, 500),
+        ]),
+        ColorProfile::Temperature(TemperatureGradient {
+            points: vec![
+                TemperatureColor {
+                    temp: 40,
+                    color: Color { r: 0, g: 0, b: 255 },
+                },
+                TemperatureColor {
+                    temp: 90,
+                    color: Color { r: 255, g: 0, b: 0 },
+                },
+            ],
+            color_space: ColorSpace::Oklab,
+        }),
+    ];
+    for profile in profiles {
+        connection
+            .add_keyboard_profile(name, &profile)
+            .await
+            .unwrap();
+        assert_eq!(
+            connection.get_keyboard_profile(name).await.unwrap(),
+            profile
+        );
+    }
+    connection.remove_keyboard_profile(name).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_telemetry() {
+    let connection = TailorConnection::new().await.unwrap();
+    let fans = connection.get_number_of_fans().await.unwrap() as usize;
+
+    assert_eq!(connection.get_temperatures().await.unwrap().len(), fans);
+    assert_eq!(connection.get_fan_speeds().await.unwrap().len(), fans);
+    assert_eq!(
+        connection.get_target_fan_speeds().await.unwrap().len(),
+        fans
+    );
+
+    // Overrides are visible while they are active
+    let overrides = connection.receive_active_fan_override().await;
+    connection.override_single_fan_speed(0, 77).await.unwrap();
+    let mut overrides = Box::pin(overrides);
+    tokio::time::timeout(std::time::Duration::from_secs(3), async {
+        while let Some(active_override) = overrides.next().await {
+            if active_override.contains(&(0, 77)) {
+                break;
+            }
+        }
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_profile_signals() {
+    let connection = TailorConnection::new().await.unwrap();
+    let name = "__test_signal_profile";
+    let second_name = "__test_signal_profile2";
+
+    let profile = vec![FanProfilePoint { temp: 50, fan: 50 }];
+    let changes = connection.receive_fan_profile_changes().await.unwrap();
+    let active_changes = connection
+        .receive_active_global_profile_changes()
+        .await
+        .unwrap();
+
+    connection.add_fan_profile(name, &profile).await.unwrap();
+    connection
+        .rename_fan_profile(name, second_name)
+        .await
+        .unwrap();
+    connection.remove_fan_profile(second_name).await.unwrap();
+
+    // Only look at changes caused by this test
+    let changes: Vec<ProfileChange> = tokio::time::timeout(
+        std::time::Duration::from_secs(3),
+        changes
+            .filter(|change| {
+                let own = match change {
+                    ProfileChange::Added(profile) | ProfileChange::Removed(profile) => {
+                        profile.starts_with(name)
+                    }
+                    ProfileChange::Renamed { from, .. } => from == name,
+                };
+                async move { own }
+            })
+            .take(3)
+            .collect(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        changes,
+        [
+            ProfileChange::Added(name.to_owned()),
+            ProfileChange::Renamed {
+                from: name.to_owned(),
+                to: second_name.to_owned()
+            },
+            ProfileChange::Removed(second_name.to_owned()),
+        ]
+    );
+
+    // Activating the already active profile is announced as well
+    let active_name = connection.get_active_global_profile_name().await.unwrap();
+    connection
+        .set_active_global_profile_name(&active_name)
+        .await
+        .unwrap();
+    let mut active_changes = Box::pin(active_changes);
+    let changed = tokio::time::timeout(std::time::Duration::from_secs(3), active_changes.next())
+        .await
+        .unwrap();
+    assert_eq!(changed, Some(active_name));
+}
+
+#[tokio::test]
+async fn test_power_rule() {
+    let connection = TailorConnection::new().await.unwrap();
+    let previous_rule = connection.get_power_rule().await.unwrap();
+    let active_name = connection.get_active_global_profile_name().await.unwrap();
+
+    let rule = PowerRule {
+        ac: String::new(),
+        battery: active_name,
+    };
+    connection.set_power_rule(&rule).await.unwrap();
+    assert_eq!(connection.get_power_rule().await.unwrap(), rule);
+
+    // Profiles must exist
+    let rule = PowerRule {
+        ac: "__test_missing_profile".to_owned(),
+        battery: String::new(),
+    };
+    connection.set_power_rule(&rule).await.unwrap_err();
+
+    connection.is_on_battery().await.unwrap();
+    connection.set_power_rule(&previous_rule).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_cpu() {
+    let connection = TailorConnection::new().await.unwrap();
+    let name = "__test_cpu_profile";
+    let second_name = "__test_cpu_profile2";
+    let global_name = "__test_cpu_global_profile";
+
+    let profile = CpuProfile {
+        governor: "powersave".to_owned(),
+        max_freq: 2_000_000,
+        boost: Some(false),
+        ..CpuProfile::default()
+    };
+
+    // Add profile
+    connection.add_cpu_profile(name, &profile).await.unwrap();
+    assert_eq!(connection.get_cpu_profile(name).await.unwrap(), profile);
+    assert!(connection
+        .list_cpu_profiles()
+        .await
+        .unwrap()
+        .contains(&name.to_owned()));
+
+    // Renaming updates global profiles that use the CPU profile
+    let global_profile = ProfileInfo {
+        cpu: name.to_owned(),
+        ..ProfileInfo::default()
+    };
+    connection
+        .add_global_profile(global_name, &global_profile)
+        .await
+        .unwrap();
+    connection
+        .rename_cpu_profile(name, second_name)
+        .await
+        .unwrap();
+    assert_eq!(
+        connection
+            .get_global_profile(global_name)
+            .await
+            .unwrap()
+            .cpu,
+        second_name
+    );
+    connection.get_cpu_profile(name).await.unwrap_err();
+
+    // Copy and remove
+    connection
+        .copy_cpu_profile(second_name, name)
+        .await
+        .unwrap();
+    connection.remove_cpu_profile(name).await.unwrap();
+    connection.remove_cpu_profile(second_name).await.unwrap();
+    connection
+        .remove_cpu_profile(second_name)
+        .await
+        .unwrap_err();
+    connection.remove_global_profile(global_name).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_performance_profile() {
+    let connection = TailorConnection::new().await.unwrap();
+    let profiles = connection.list_performance_profiles().await.unwrap();
+    let Some(name) = profiles.first() else {
+        return;
+    };
+
+    connection.set_performance_profile(name).await.unwrap();
+    assert_eq!(connection.get_performance_profile().await.unwrap(), *name);
+
+    // Unknown profiles are rejected
+    connection
+        .set_performance_profile("__test_missing_performance_profile")
+        .await
+        .unwrap_err();
+
+    // Global profiles are validated as well
+    let global_profile = ProfileInfo {
+        performance_profile: "__test_missing_performance_profile".to_owned(),
+        ..ProfileInfo::default()
+    };
+    connection
+        .add_global_profile("__test_performance_global_profile", &global_profile)
+        .await
+        .unwrap_err();
+}
+
+#[tokio::test]
+async fn test_switches() {
+    let connection = TailorConnection::new().await.unwrap();
+    let switch = Switch::Touchpad;
+    let enabled = connection.get_switch(switch).await.unwrap();
+    let changes = connection.receive_switch(switch).await;
+
+    connection.set_switch(switch, !enabled).await.unwrap();
+    assert_eq!(connection.get_switch(switch).await.unwrap(), !enabled);
+    let mut changes = Box::pin(changes);
+    tokio::time::timeout(std::time::Duration::from_secs(3), async {
+        while let Some(value) = changes.next().await {
+            if value != enabled {
+                break;
+            }
+        }
+    })
+    .await
+    .unwrap();
+    connection.set_switch(switch, enabled).await.unwrap();
+
+    // Global profiles may only contain known switches
+    let mut global_profile = ProfileInfo::default();
+    global_profile
+        .switches
+        .insert("__test_missing_switch".to_owned(), false);
+    connection
+        .add_global_profile("__test_switch_global_profile", &global_profile)
+        .await
+        .unwrap_err();
+}
+
+#[tokio::test]
+async fn test_keyboard_brightness() {
+    let connection = TailorConnection::new().await.unwrap();
+    let capabilities = connection.get_capabilities().await.unwrap();
+    if !capabilities.keyboard_brightness {
+        connection.set_keyboard_brightness(0).await.unwrap_err();
+        return;
+    }
+
+    let brightness = connection.get_keyboard_brightness().await.unwrap();
+    let new_brightness = connection.get_max_keyboard_brightness().await.unwrap() - brightness;
+    let mut changes = Box::pin(connection.receive_keyboard_brightness().await);
+
+    connection
+        .set_keyboard_brightness(new_brightness)
+        .await
+        .unwrap();
+    tokio::time::timeout(std::time::Duration::from_secs(3), async {
+        while let Some(value) = changes.next().await {
+            if value == new_brightness {
+                break;
+            }
+        }
+    })
+    .await
+    .unwrap();
+    assert_eq!(
+        connection.get_keyboard_brightness().await.unwrap(),
+        new_brightness
+    );
+    connection
+        .set_keyboard_brightness(brightness)
+        .await
+        .unwrap();
+
+    // Global profiles can keep the brightness or set it
+    for keyboard_brightness in [None, Some(new_brightness)] {
+        let global_profile = ProfileInfo {
+            keyboard_brightness,
+            ..ProfileInfo::default()
+        };
+        connection
+            .add_global_profile("__test_brightness_global_profile", &global_profile)
+            .await
+            .unwrap();
+        assert_eq!(
+            connection
+                .get_global_profile("__test_brightness_global_profile")
+                .await
+                .unwrap(),
+            global_profile
+        );
+    }
+    connection
+        .remove_global_profile("__test_brightness_global_profile")
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_capabilities() {
+    let connection = TailorConnection::new().await.unwrap();
+    let capabilities = connection.get_capabilities().await.unwrap();
+
+    assert!(["Clevo", "Uniwill"].contains(&capabilities.platform.as_str()));
+    assert_eq!(
+        capabilities.fans,
+        connection.get_number_of_fans().await.unwrap()
+    );
+    assert_eq!(
+        capabilities.performance_profiles,
+        connection.list_performance_profiles().await.unwrap()
+    );
+    for switch in Switch::ALL {
+        if !capabilities.has_switch(switch) {
+            connection.set_switch(switch, true).await.unwrap_err();
+        }
+    }
+
+    let subsystems = connection.get_subsystems().await.unwrap();
+    let names: Vec<_> = subsystems
+        .iter()
+        .map(|status| status.name.as_str())
+        .collect();
+    assert_eq!(names, ["tuxedo_io", "keyboard", "cpu"]);
+    for status in subsystems {
+        assert_eq!(status.available, status.error.is_empty());
+    }
 }
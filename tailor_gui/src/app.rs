@@ -10,7 +10,7 @@ use relm4::{
     adw, gtk, main_application, Component, ComponentController, ComponentParts, ComponentSender,
     Controller,
 };
-use tailor_api::ProfileInfo;
+use tailor_api::{Capabilities, ProfileInfo};
 
 use crate::components::fan_list::FanList;
 use crate::components::keyboard_list::KeyboardList;
@@ -42,12 +42,14 @@ pub struct FullProfileInfo {
 pub(super) struct App {
     about_dialog: Controller<AboutDialog>,
     connection_state: ConnectionState,
+    /// Hides the keyboard page on devices without keyboard colors.
+    keyboard_supported: bool,
     error: Option<adw::Toast>,
 }
 
 #[derive(Debug)]
 pub(super) enum Command {
-    SetInitializedState(bool),
+    SetInitializedState(Option<Capabilities>),
 }
 
 #[derive(Debug)]
@@ -150,6 +152,8 @@ impl Component for App {
                                     #[local_ref]
                                     add_titled[Some("keyboard"), "Keyboard"] = keyboard_list_widget -> gtk::ScrolledWindow {} -> {
                                         set_icon_name: Some("keyboard-color"),
+                                        #[watch]
+                                        set_visible: model.keyboard_supported,
                                     },
                                     #[local_ref]
                                     add_titled[Some("fan"), "Fan control"] = fan_list -> gtk::ScrolledWindow {} -> {
@@ -255,6 +259,7 @@ impl Component for App {
         let model = Self {
             about_dialog,
             connection_state: ConnectionState::Connecting,
+            keyboard_supported: true,
             error: None,
         };
 
@@ -307,8 +312,9 @@ impl Component for App {
         _root: &Self::Root,
     ) {
         match message {
-            Command::SetInitializedState(initialized) => {
-                if initialized {
+            Command::SetInitializedState(capabilities) => {
+                if let Some(capabilities) = capabilities {
+                    self.keyboard_supported = capabilities.has_keyboard_colors();
                     self.connection_state = ConnectionState::Ok;
                 } else {
                     self.connection_state = ConnectionState::Error;
@@ -357,7 +363,7 @@ impl App {
             if let Some(delay) = delay {
                 tokio::time::sleep(delay).await;
             }
-            Command::SetInitializedState(initialize_tailor_state().await.is_ok())
+            Command::SetInitializedState(initialize_tailor_state().await.ok())
         });
     }
 }
@@ -9,6 +9,8 @@ use crate::components::keyboard_edit::KeyboardEditInput;
 
 pub struct ColorRow {
     pub inner: ColorPoint,
+    /// The list of the keyboard editor that contains this row.
+    list: usize,
     color_button: Controller<ColorButton>,
 }
 
@@ -20,15 +22,15 @@ pub enum ColorInput {
 
 #[derive(Debug)]
 pub enum ColorOutput {
-    Up(DynamicIndex),
-    Down(DynamicIndex),
-    Remove(DynamicIndex),
+    Up(usize, DynamicIndex),
+    Down(usize, DynamicIndex),
+    Remove(usize, DynamicIndex),
 }
 
 #[factory(pub)]
 impl FactoryComponent for ColorRow {
     type CommandOutput = ();
-    type Init = ColorPoint;
+    type Init = (usize, ColorPoint);
     type Input = ColorInput;
     type Output = ColorOutput;
     type ParentInput = KeyboardEditInput;
@@ -71,20 +73,20 @@ impl FactoryComponent for ColorRow {
                     gtk::Button {
                         set_icon_name: "go-up",
                         connect_clicked[sender, index] => move |_| {
-                            sender.output(ColorOutput::Up(index.clone()));
+                            sender.output(ColorOutput::Up(list, index.clone()));
                         }
                     },
                     gtk::Button {
                         set_icon_name: "go-down",
                         connect_clicked[sender, index] => move |_| {
-                            sender.output(ColorOutput::Down(index.clone()));
+                            sender.output(ColorOutput::Down(list, index.clone()));
                         }
                     },
                     gtk::Button {
                         set_icon_name: "remove",
                         add_css_class: "destructive-action",
                         connect_clicked[sender, index] => move |_| {
-                            sender.output(ColorOutput::Remove(index.clone()));
+                            sender.output(ColorOutput::Remove(list, index.clone()));
                         }
                     }
                 }
@@ -94,19 +96,24 @@ impl FactoryComponent for ColorRow {
 
     fn output_to_parent_input(output: Self::Output) -> Option<KeyboardEditInput> {
         Some(match output {
-            ColorOutput::Up(index) => KeyboardEditInput::Up(index),
-            ColorOutput::Down(index) => KeyboardEditInput::Down(index),
-            ColorOutput::Remove(index) => KeyboardEditInput::Remove(index),
+            ColorOutput::Up(list, index) => KeyboardEditInput::Up(list, index),
+            ColorOutput::Down(list, index) => KeyboardEditInput::Down(list, index),
+            ColorOutput::Remove(list, index) => KeyboardEditInput::Remove(list, index),
         })
     }
 
-    fn init_model(inner: Self::Init, _index: &DynamicIndex, sender: FactorySender<Self>) -> Self {
+    fn init_model(
+        (list, inner): Self::Init,
+        _index: &DynamicIndex,
+        sender: FactorySender<Self>,
+    ) -> Self {
         let color_button = ColorButton::builder()
             .launch(inner.color.clone())
             .forward(sender.input_sender(), ColorInput::SetColor);
 
         Self {
             color_button,
+            list,
             inner,
         }
     }
@@ -119,6 +126,7 @@ impl FactoryComponent for ColorRow {
         sender: FactorySender<Self>,
     ) -> Self::Widgets {
         let color_button = self.color_button.widget();
+        let list = self.list;
 
         let widgets = view_output!();
 
@@ -211,7 +211,9 @@ impl FactoryComponent for Profile {
                     .unwrap()
                     .clone();
 
-                self.info = ProfileInfo { keyboard, fan };
+                // Keep the parts of the profile that can't be edited here.
+                self.info.keyboard = keyboard;
+                self.info.fan = fan;
 
                 let profile = self.info.clone();
 
@@ -1,13 +1,16 @@
 use gtk::prelude::{ButtonExt, WidgetExt};
 use relm4::factory::FactoryVecDeque;
-use relm4::gtk::traits::OrientableExt;
+use relm4::gtk::traits::{BoxExt, OrientableExt};
 use relm4::prelude::DynamicIndex;
 use relm4::{
     adw, component, gtk, Component, ComponentController, ComponentParts, ComponentSender,
     Controller, RelmWidgetExt,
 };
 use relm4_components::simple_combo_box::{SimpleComboBox, SimpleComboBoxMsg};
-use tailor_api::{Color, ColorPoint, ColorProfile, ColorTransition};
+use tailor_api::{
+    Color, ColorPoint, ColorProfile, ColorSpace, ColorTransition, KeyboardEffect, KeyboardZone,
+    TemperatureColor, TemperatureGradient, ZoneColors,
+};
 
 use super::color_button::{ColorButton, ColorButtonInput};
 use super::factories::color::ColorRow;
@@ -20,6 +23,9 @@ pub enum ColorProfileType {
     None,
     Single,
     Multiple,
+    Effect,
+    Zones,
+    Temperature,
 }
 
 impl std::fmt::Display for ColorProfileType {
@@ -29,16 +35,51 @@ impl std::fmt::Display for ColorProfileType {
             Self::None => "None",
             Self::Single => "Single",
             Self::Multiple => "Multiple",
+            Self::Effect => "Effect",
+            Self::Zones => "Zones",
+            Self::Temperature => "Temperature",
         })
     }
 }
 
+/// Index of the color list of [`ColorProfileType::Multiple`],
+/// the lists of the zones follow after it.
+const PATTERN_LIST: usize = 0;
+
+/// Colors of a single zone, shown side by side with the other zones.
+struct ZoneEditor {
+    zone: KeyboardZone,
+    offset_button: gtk::SpinButton,
+    colors: FactoryVecDeque<ColorRow>,
+}
+
+/// The color at one end of a temperature gradient.
+struct TemperatureEditor {
+    color_button: Controller<ColorButton>,
+    temp_button: gtk::SpinButton,
+}
+
+fn zone_label(zone: KeyboardZone) -> &'static str {
+    match zone {
+        KeyboardZone::Left => "Left",
+        KeyboardZone::Center => "Center",
+        KeyboardZone::Right => "Right",
+        KeyboardZone::Extra => "Extra",
+    }
+}
+
 pub struct KeyboardEdit {
     profile_name: Option<String>,
     color_profile_type: ColorProfileType,
     colors: FactoryVecDeque<ColorRow>,
+    zones: Vec<ZoneEditor>,
+    /// The cool and the hot end of the gradient.
+    temperature: [TemperatureEditor; 2],
+    /// Kept from the loaded profile because it can't be edited yet.
+    temperature_space: ColorSpace,
     color_button: Controller<ColorButton>,
     type_selector: Controller<SimpleComboBox<ColorProfileType>>,
+    effect_selector: Controller<SimpleComboBox<KeyboardEffect>>,
     visible: bool,
 }
 
@@ -46,10 +87,10 @@ pub struct KeyboardEdit {
 pub enum KeyboardEditInput {
     Load(String),
     SetType(ColorProfileType),
-    Up(DynamicIndex),
-    Down(DynamicIndex),
-    Remove(DynamicIndex),
-    Add,
+    Up(usize, DynamicIndex),
+    Down(usize, DynamicIndex),
+    Remove(usize, DynamicIndex),
+    Add(usize),
     Apply,
     Cancel,
 }
@@ -132,7 +173,7 @@ impl Component for KeyboardEdit {
                                         gtk::Button {
                                             set_icon_name: "plus",
                                             set_halign: gtk::Align::End,
-                                            connect_clicked => KeyboardEditInput::Add,
+                                            connect_clicked => KeyboardEditInput::Add(PATTERN_LIST),
                                         }
                                     },
 
@@ -142,6 +183,55 @@ impl Component for KeyboardEdit {
                                         add_css_class: "boxed-list",
                                     }
                                 }
+                            },
+                            ColorProfileType::Effect => {
+                                gtk::Box {
+                                    set_orientation: gtk::Orientation::Vertical,
+                                    set_halign: gtk::Align::Center,
+                                    set_valign: gtk::Align::Center,
+                                    set_spacing: 12,
+
+                                    gtk::Label {
+                                        set_label: "Let the keyboard animate the colors on its own",
+                                    },
+
+                                    #[local_ref]
+                                    effect_selector_widget -> gtk::ComboBoxText {},
+                                }
+                            },
+                            ColorProfileType::Zones => {
+                                gtk::Box {
+                                    set_orientation: gtk::Orientation::Vertical,
+
+                                    gtk::Label {
+                                        set_margin_bottom: 6,
+                                        set_label: "Increasing delays sweep the colors across the keyboard",
+                                    },
+
+                                    #[local_ref]
+                                    zone_columns -> gtk::Box {
+                                        set_spacing: 12,
+                                        set_homogeneous: true,
+                                    }
+                                }
+                            }
+                            ColorProfileType::Temperature => {
+                                gtk::Box {
+                                    set_orientation: gtk::Orientation::Vertical,
+                                    set_halign: gtk::Align::Center,
+                                    set_valign: gtk::Align::Center,
+                                    set_spacing: 12,
+
+                                    gtk::Label {
+                                        set_label: "Blend the colors by the CPU temperature",
+                                    },
+
+                                    #[local_ref]
+                                    temperature_rows -> gtk::Box {
+                                        set_orientation: gtk::Orientation::Vertical,
+                                        set_spacing: 12,
+                                    }
+                                }
                             }
                         }
                     }
@@ -194,26 +284,112 @@ impl Component for KeyboardEdit {
                     ColorProfileType::None,
                     ColorProfileType::Single,
                     ColorProfileType::Multiple,
+                    ColorProfileType::Effect,
+                    ColorProfileType::Zones,
+                    ColorProfileType::Temperature,
                 ],
             })
             .forward(sender.input_sender(), |idx| {
                 KeyboardEditInput::SetType(match idx {
                     0 => ColorProfileType::None,
                     1 => ColorProfileType::Single,
-                    _ => ColorProfileType::Multiple,
+                    2 => ColorProfileType::Multiple,
+                    3 => ColorProfileType::Effect,
+                    4 => ColorProfileType::Zones,
+                    _ => ColorProfileType::Temperature,
                 })
             });
 
+        let zone_columns = gtk::Box::default();
+        let zones = KeyboardZone::ALL
+            .into_iter()
+            .enumerate()
+            .map(|(idx, zone)| {
+                let list = PATTERN_LIST + 1 + idx;
+
+                let label = gtk::Label::new(Some(zone_label(zone)));
+                label.set_hexpand(true);
+                label.set_xalign(0.0);
+
+                let offset_button = gtk::SpinButton::with_range(0.0, 10.0, 0.1);
+                offset_button.set_digits(2);
+                offset_button.set_tooltip_text(Some("Delay of the animation in s"));
+
+                let add_button = gtk::Button::from_icon_name("plus");
+                let input_sender = sender.input_sender().clone();
+                add_button.connect_clicked(move |_| {
+                    input_sender.emit(KeyboardEditInput::Add(list));
+                });
+
+                let header = gtk::Box::new(gtk::Orientation::Horizontal, 6);
+                header.append(&label);
+                header.append(&offset_button);
+                header.append(&add_button);
+
+                let color_points = gtk::ListBox::default();
+                color_points.set_valign(gtk::Align::Start);
+                color_points.add_css_class("boxed-list");
+
+                let column = gtk::Box::new(gtk::Orientation::Vertical, 6);
+                column.append(&header);
+                column.append(&color_points);
+                zone_columns.append(&column);
+
+                ZoneEditor {
+                    zone,
+                    offset_button,
+                    colors: FactoryVecDeque::new(color_points, sender.input_sender()),
+                }
+            })
+            .collect();
+
+        let temperature_rows = gtk::Box::default();
+        let temperature = [
+            (40.0, Color { r: 0, g: 0, b: 255 }),
+            (90.0, Color { r: 255, g: 0, b: 0 }),
+        ]
+        .map(|(temp, color)| {
+            let color_button = ColorButton::builder().launch(color).detach();
+
+            let temp_button = gtk::SpinButton::with_range(0.0, 110.0, 1.0);
+            temp_button.set_value(temp);
+            temp_button.set_tooltip_text(Some("Temperature in °C"));
+
+            let row = gtk::Box::new(gtk::Orientation::Horizontal, 12);
+            row.append(color_button.widget());
+            row.append(&temp_button);
+            temperature_rows.append(&row);
+
+            TemperatureEditor {
+                color_button,
+                temp_button,
+            }
+        });
+
+        let effect_selector = SimpleComboBox::builder()
+            .launch(SimpleComboBox {
+                active_index: Some(0),
+                variants: KeyboardEffect::ALL.to_vec(),
+            })
+            .detach();
+
         let model = Self {
             profile_name: None,
             color_profile_type: ColorProfileType::Loading,
             colors,
+            zones,
+            temperature,
+            temperature_space: ColorSpace::default(),
             color_button,
             type_selector,
+            effect_selector,
             visible: false,
         };
 
         let type_selector_widget = model.type_selector.widget();
+        let effect_selector_widget = model.effect_selector.widget();
+        let zone_columns = &zone_columns;
+        let temperature_rows = &temperature_rows;
         let color_button = model.color_button.widget();
         let widgets = view_output!();
 
@@ -242,7 +418,50 @@ impl Component for KeyboardEdit {
                 let mut guard = self.colors.guard();
                 guard.clear();
                 for color_point in color_profile {
-                    guard.push_back(color_point);
+                    guard.push_back((PATTERN_LIST, color_point));
+                }
+            }
+            ColorProfile::Effect(effect) => {
+                self.color_profile_type = ColorProfileType::Effect;
+                self.type_selector.emit(SimpleComboBoxMsg::SetActiveIdx(3));
+                let index = KeyboardEffect::ALL
+                    .iter()
+                    .position(|other| *other == effect)
+                    .unwrap_or_default();
+                self.effect_selector
+                    .emit(SimpleComboBoxMsg::SetActiveIdx(index));
+            }
+            ColorProfile::Zones(zones) => {
+                self.color_profile_type = ColorProfileType::Zones;
+                self.type_selector.emit(SimpleComboBoxMsg::SetActiveIdx(4));
+                for (idx, editor) in self.zones.iter_mut().enumerate() {
+                    let zone = zones.iter().find(|zone| zone.zone == editor.zone);
+                    let offset = zone.map(|zone| zone.offset).unwrap_or_default();
+                    editor.offset_button.set_value(offset as f64 / 1000.0);
+
+                    let list = PATTERN_LIST + 1 + idx;
+                    let mut guard = editor.colors.guard();
+                    guard.clear();
+                    for color_point in zone.into_iter().flat_map(|zone| zone.points.clone()) {
+                        guard.push_back((list, color_point));
+                    }
+                }
+            }
+            ColorProfile::Temperature(mut gradient) => {
+                self.color_profile_type = ColorProfileType::Temperature;
+                self.type_selector.emit(SimpleComboBoxMsg::SetActiveIdx(5));
+                self.temperature_space = gradient.color_space;
+
+                // Only the coolest and the hottest color can be edited.
+                gradient.points.sort_by_key(|point| point.temp);
+                let ends = [gradient.points.first(), gradient.points.last()];
+                for (editor, point) in self.temperature.iter().zip(ends) {
+                    if let Some(point) = point {
+                        editor.temp_button.set_value(point.temp as f64);
+                        editor
+                            .color_button
+                            .emit(ColorButtonInput::UpdateColor(point.color.clone()));
+                    }
                 }
             }
         }
@@ -276,9 +495,9 @@ impl Component for KeyboardEdit {
             KeyboardEditInput::Cancel => {
                 self.visible = false;
             }
-            KeyboardEditInput::Add => {
-                let last_elem = self
-                    .colors
+            KeyboardEditInput::Add(list) => {
+                let colors = self.list_mut(list);
+                let last_elem = colors
                     .back()
                     .map(|row| row.inner.clone())
                     .unwrap_or_else(|| ColorPoint {
@@ -289,33 +508,42 @@ impl Component for KeyboardEdit {
                             b: 255,
                         },
                         transition_time: 1000,
+                        color_space: ColorSpace::Rgb,
                     });
-                self.colors.guard().push_back(last_elem);
+                colors.guard().push_back((list, last_elem));
             }
-            KeyboardEditInput::Up(index) => {
+            KeyboardEditInput::Up(list, index) => {
                 let index = index.current_index();
                 if index != 0 {
-                    self.colors.guard().move_to(index, index.saturating_sub(1));
+                    self.list_mut(list)
+                        .guard()
+                        .move_to(index, index.saturating_sub(1));
                 }
             }
-            KeyboardEditInput::Down(index) => {
+            KeyboardEditInput::Down(list, index) => {
+                let colors = self.list_mut(list);
                 let index = index.current_index();
-                let last_idx = self.colors.len().saturating_sub(1);
+                let last_idx = colors.len().saturating_sub(1);
                 if index != last_idx {
-                    self.colors
-                        .guard()
-                        .move_to(index, (index + 1).min(last_idx));
+                    colors.guard().move_to(index, (index + 1).min(last_idx));
                 }
             }
-            KeyboardEditInput::Remove(index) => {
+            KeyboardEditInput::Remove(list, index) => {
                 let index = index.current_index();
-                self.colors.guard().remove(index);
+                self.list_mut(list).guard().remove(index);
             }
         }
     }
 }
 
 impl KeyboardEdit {
+    fn list_mut(&mut self, list: usize) -> &mut FactoryVecDeque<ColorRow> {
+        match list {
+            PATTERN_LIST => &mut self.colors,
+            zone => &mut self.zones[zone - PATTERN_LIST - 1].colors,
+        }
+    }
+
     fn compile(&self) -> ColorProfile {
         match self.color_profile_type {
             ColorProfileType::Loading | ColorProfileType::None => ColorProfile::None,
@@ -325,6 +553,33 @@ impl KeyboardEdit {
             ColorProfileType::Multiple => {
                 ColorProfile::Multiple(self.colors.iter().map(|row| row.inner.clone()).collect())
             }
+            ColorProfileType::Effect => {
+                let model = self.effect_selector.model();
+                let index = model.active_index.unwrap_or_default();
+                ColorProfile::Effect(model.variants[index])
+            }
+            ColorProfileType::Zones => ColorProfile::Zones(
+                self.zones
+                    .iter()
+                    .filter(|editor| !editor.colors.is_empty())
+                    .map(|editor| ZoneColors {
+                        zone: editor.zone,
+                        points: editor.colors.iter().map(|row| row.inner.clone()).collect(),
+                        offset: (editor.offset_button.value() * 1000.0) as u32,
+                    })
+                    .collect(),
+            ),
+            ColorProfileType::Temperature => ColorProfile::Temperature(TemperatureGradient {
+                points: self
+                    .temperature
+                    .iter()
+                    .map(|editor| TemperatureColor {
+                        temp: editor.temp_button.value() as u8,
+                        color: editor.color_button.model().color.clone(),
+                    })
+                    .collect(),
+                color_space: self.temperature_space,
+            }),
         }
     }
 }
@@ -150,6 +150,7 @@ impl SimpleComponent for NewProfileDialog {
                     data: ProfileInfo {
                         keyboard: self.keyboard.model().get_active_elem().unwrap().to_string(),
                         fan: self.fan.model().get_active_elem().unwrap().to_string(),
+                        ..Default::default()
                     },
                 }))
                 .unwrap(),
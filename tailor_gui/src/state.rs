@@ -2,7 +2,7 @@ use std::time::Duration;
 
 use relm4::tokio::sync::OnceCell;
 use relm4::{Reducer, Reducible};
-use tailor_api::{Color, ColorProfile, FanProfilePoint, ProfileInfo};
+use tailor_api::{Capabilities, Color, ColorProfile, FanProfilePoint, ProfileInfo};
 use tailor_client::{ClientError, TailorConnection};
 
 use crate::app::FullProfileInfo;
@@ -53,6 +53,7 @@ pub struct TailorStateInner {
     pub profiles: Vec<FullProfileInfo>,
     pub keyboard_profiles: Vec<String>,
     pub fan_profiles: Vec<String>,
+    pub capabilities: Capabilities,
     pub error: Option<String>,
 }
 
@@ -309,7 +310,7 @@ impl Reducible for TailorState {
     }
 }
 
-pub async fn initialize_tailor_state() -> Result<(), String> {
+pub async fn initialize_tailor_state() -> Result<Capabilities, String> {
     let connection = TailorConnection::new().await.map_err(|e| e.to_string())?;
     CONNECTION
         .set(connection.clone())
@@ -330,6 +331,11 @@ pub async fn initialize_tailor_state() -> Result<(), String> {
         .await
         .map_err(|e| e.to_string())?;
 
+    let capabilities = connection
+        .get_capabilities()
+        .await
+        .map_err(|e| e.to_string())?;
+
     let profiles = futures::future::try_join_all(
         connection
             .list_global_profiles()
@@ -353,6 +359,7 @@ pub async fn initialize_tailor_state() -> Result<(), String> {
         profiles,
         keyboard_profiles,
         fan_profiles,
+        capabilities: capabilities.clone(),
         tracker: 0,
         error: None,
     };
@@ -361,7 +368,7 @@ pub async fn initialize_tailor_state() -> Result<(), String> {
 
     STATE.emit(TailorStateMsg::Load(state));
 
-    Ok(())
+    Ok(capabilities)
 }
 
 fn handle_result<T>(result: Result<T, ClientError>) -> Option<T> {
@@ -0,0 +1,500 @@
+mod simulated;
+
+use std::{
+    io,
+    path::PathBuf,
+    sync::{Arc, PoisonError, RwLock},
+};
+
+use async_trait::async_trait;
+use tailor_api::{Color, KeyboardZone};
+use tuxedo_ioctl::{
+    high_level::{Fan, Hw, IoInterface, PerformanceProfile},
+    IoctlError,
+};
+use tuxedo_sysfs::{
+    cpu::{CpuController, LogicalCore},
+    keyboard::{KeyboardController, KeyboardState},
+};
+
+pub use simulated::{SimulatedCpu, SimulatedIo, SimulatedKeyboard};
+
+/// Fans, temperatures and other settings that are
+/// controlled through the `tuxedo_io` ioctl interface.
+pub trait IoBackend: Send + Sync {
+    /// Open the device if it isn't open yet.
+    ///
+    /// Devices that are always present don't need to do anything.
+    fn connect(&self) -> Result<(), IoctlError> {
+        Ok(())
+    }
+
+    fn hw(&self) -> Result<Hw, IoctlError>;
+
+    /// Returns the version of the kernel module.
+    fn driver_version(&self) -> Result<String, IoctlError>;
+
+    /// Returns all fans that report a temperature.
+    fn available_fans(&self) -> Vec<Fan> {
+        [Fan::Fan1, Fan::Fan2, Fan::Fan3]
+            .into_iter()
+            .filter(|fan| self.get_fan_temperature(*fan).is_ok())
+            .collect()
+    }
+
+    /// Set the fan speed in percent from 0 to 100.
+    fn set_fan_speed_percent(&self, fan: Fan, fan_speed_percent: u8) -> Result<(), IoctlError>;
+
+    fn get_fan_speed_percent(&self, fan: Fan) -> Result<u8, IoctlError>;
+
+    fn get_fan_temperature(&self, fan: Fan) -> Result<u8, IoctlError>;
+
+    /// Hand the fan control back to the firmware.
+    fn set_fans_auto(&self) -> Result<(), IoctlError>;
+
+    /// Take over the fan control from the firmware.
+    fn set_fans_manual(&self) -> Result<(), IoctlError>;
+
+    fn set_web_cam_enabled(&self, status: bool) -> Result<(), IoctlError>;
+
+    fn get_web_cam_enabled(&self) -> Result<bool, IoctlError>;
+
+    fn set_touchpad_enabled(&self, status: bool) -> Result<(), IoctlError>;
+
+    fn get_touchpad_enabled(&self) -> Result<bool, IoctlError>;
+
+    fn set_flight_mode(&self, status: bool) -> Result<(), IoctlError>;
+
+    fn get_flight_mode(&self) -> Result<bool, IoctlError>;
+
+    /// Returns the performance profiles that are supported by the device.
+    fn available_performance_profiles(&self) -> Vec<PerformanceProfile>;
+
+    fn set_performance_profile(&self, profile: PerformanceProfile) -> Result<(), IoctlError>;
+
+    /// `None` if the current performance profile is unknown.
+    fn get_performance_profile(&self) -> Result<Option<PerformanceProfile>, IoctlError>;
+}
+
+/// Keyboard color zones and effects that are
+/// controlled through sysfs.
+#[async_trait(?Send)]
+pub trait KeyboardBackend {
+    fn zones(&self) -> Vec<KeyboardZone>;
+
+    /// Returns the effects that are built into the keyboard firmware.
+    fn effects(&self) -> Vec<KeyboardState>;
+
+    async fn set_color_left(&self, color: &Color) -> Result<(), io::Error>;
+
+    async fn get_color_left(&self) -> Result<Color, io::Error>;
+
+    async fn set_color_right(&self, color: &Color) -> Result<(), io::Error>;
+
+    async fn get_color_right(&self) -> Result<Color, io::Error>;
+
+    async fn set_color_center(&self, color: &Color) -> Result<(), io::Error>;
+
+    async fn get_color_center(&self) -> Result<Color, io::Error>;
+
+    async fn set_color_extra(&self, color: &Color) -> Result<(), io::Error>;
+
+    async fn get_color_extra(&self) -> Result<Color, io::Error>;
+
+    async fn set_color_all(&self, color: &Color) -> Result<(), io::Error>;
+
+    async fn set_color(&self, zone: KeyboardZone, color: &Color) -> Result<(), io::Error> {
+        match zone {
+            KeyboardZone::Left => self.set_color_left(color).await,
+            KeyboardZone::Center => self.set_color_center(color).await,
+            KeyboardZone::Right => self.set_color_right(color).await,
+            KeyboardZone::Extra => self.set_color_extra(color).await,
+        }
+    }
+
+    async fn set_brightness(&self, brightness: u8) -> Result<(), io::Error>;
+
+    async fn get_brightness(&self) -> Result<u8, io::Error>;
+
+    async fn set_mode(&self, mode: bool) -> Result<(), io::Error>;
+
+    async fn get_mode(&self) -> Result<bool, io::Error>;
+
+    async fn set_state(&self, state: KeyboardState) -> Result<(), io::Error>;
+
+    async fn get_state(&self) -> Result<KeyboardState, io::Error>;
+}
+
+/// CPU frequency scaling that is controlled through sysfs.
+///
+/// Cores are addressed by their position in the list of
+/// logical cores that can be controlled, starting at 0.
+/// All frequencies are in kHz.
+#[async_trait(?Send)]
+pub trait CpuBackend {
+    fn core_count(&self) -> usize;
+
+    async fn set_core_online(&mut self, core: usize, online: bool) -> Result<(), io::Error>;
+
+    async fn is_core_online(&self, core: usize) -> Result<bool, io::Error>;
+
+    /// Returns `true` if turbo boost can be enabled or disabled.
+    fn has_boost(&self) -> bool;
+
+    async fn set_boost(&self, boost: bool) -> Result<(), io::Error>;
+
+    async fn get_boost(&self) -> Result<bool, io::Error>;
+
+    async fn set_governor(&self, core: usize, governor: &str) -> Result<(), io::Error>;
+
+    async fn get_governor(&self, core: usize) -> Result<String, io::Error>;
+
+    async fn get_available_governors(&self, core: usize) -> Result<Vec<String>, io::Error>;
+
+    async fn set_min_freq(&self, core: usize, freq: u32) -> Result<(), io::Error>;
+
+    async fn set_max_freq(&self, core: usize, freq: u32) -> Result<(), io::Error>;
+
+    /// The current minimum and maximum frequency.
+    async fn get_freq_limits(&self, core: usize) -> Result<(u32, u32), io::Error>;
+
+    /// The lowest and highest frequency supported by the hardware.
+    async fn get_hardware_freq_limits(&self, core: usize) -> Result<(u32, u32), io::Error>;
+
+    async fn set_energy_performance_preference(
+        &self,
+        core: usize,
+        preference: &str,
+    ) -> Result<(), io::Error>;
+
+    async fn get_energy_performance_preference(&self, core: usize) -> Result<String, io::Error>;
+
+    async fn get_available_energy_performance_preferences(
+        &self,
+        core: usize,
+    ) -> Result<Vec<String>, io::Error>;
+}
+
+/// Opens the hardware interfaces used by the daemon.
+///
+/// The devices are opened independently, so that a missing kernel
+/// module only disables the parts of the daemon that depend on it.
+#[derive(Debug, Clone)]
+pub enum Backend {
+    /// The interfaces of the TUXEDO kernel modules and sysfs.
+    Hardware {
+        io_device: PathBuf,
+        sys_fs_root: PathBuf,
+    },
+    /// An in-memory device that works without any TUXEDO hardware.
+    Simulated,
+}
+
+impl Backend {
+    /// The device isn't opened until [`IoBackend::connect`] succeeds.
+    pub fn io(&self) -> Arc<dyn IoBackend> {
+        match self {
+            Self::Hardware { io_device, .. } => Arc::new(HotplugIo::new(io_device.clone())),
+            Self::Simulated => Arc::<SimulatedIo>::default(),
+        }
+    }
+
+    /// Fails if the keyboard has no color zones.
+    pub async fn keyboard(&self) -> Result<Box<dyn KeyboardBackend>, io::Error> {
+        match self {
+            Self::Hardware { sys_fs_root, .. } => {
+                let keyboard = KeyboardController::with_root(sys_fs_root).await?;
+                if keyboard.zones().is_empty() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::NotFound,
+                        "No keyboard color zones found, is the tuxedo_keyboard kernel module loaded?",
+                    ));
+                }
+                Ok(Box::new(keyboard))
+            }
+            Self::Simulated => Ok(Box::<SimulatedKeyboard>::default()),
+        }
+    }
+
+    pub async fn cpu(&self) -> Result<Box<dyn CpuBackend>, io::Error> {
+        match self {
+            Self::Hardware { sys_fs_root, .. } => {
+                Ok(Box::new(CpuController::with_root(sys_fs_root).await?))
+            }
+            Self::Simulated => Ok(Box::<SimulatedCpu>::default()),
+        }
+    }
+}
+
+/// The `tuxedo_io` device, which can be opened
+/// after the kernel module was loaded.
+struct HotplugIo {
+    path: PathBuf,
+    device: RwLock<Option<IoInterface>>,
+}
+
+impl HotplugIo {
+    fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            device: RwLock::new(None),
+        }
+    }
+
+    fn with_device<T>(
+        &self,
+        f: impl FnOnce(&IoInterface) -> Result<T, IoctlError>,
+    ) -> Result<T, IoctlError> {
+        // The panic hook restores the fans through this,
+        // so a lock poisoned by a panic must not panic again.
+        match &*self.device.read().unwrap_or_else(PoisonError::into_inner) {
+            Some(io) => f(io),
+            None => Err(IoctlError::DeviceMissing(self.path.clone())),
+        }
+    }
+}
+
+impl IoBackend for HotplugIo {
+    fn connect(&self) -> Result<(), IoctlError> {
+        let mut device = self.device.write().unwrap_or_else(PoisonError::into_inner);
+        if device.is_none() {
+            *device = Some(IoInterface::with_device_file(&self.path)?);
+        }
+        Ok(())
+    }
+
+    fn hw(&self) -> Result<Hw, IoctlError> {
+        self.with_device(|io| Ok(io.hw()))
+    }
+
+    fn driver_version(&self) -> Result<String, IoctlError> {
+        self.with_device(|io| io.get_module_version())
+    }
+
+    fn set_fan_speed_percent(&self, fan: Fan, fan_speed_percent: u8) -> Result<(), IoctlError> {
+        self.with_device(|io| io.set_fan_speed_percent(fan, fan_speed_percent))
+    }
+
+    fn get_fan_speed_percent(&self, fan: Fan) -> Result<u8, IoctlError> {
+        self.with_device(|io| io.get_fan_speed_percent(fan))
+    }
+
+    fn get_fan_temperature(&self, fan: Fan) -> Result<u8, IoctlError> {
+        self.with_device(|io| io.get_fan_temperature(fan))
+    }
+
+    fn set_fans_auto(&self) -> Result<(), IoctlError> {
+        self.with_device(|io| io.set_fans_auto())
+    }
+
+    fn set_fans_manual(&self) -> Result<(), IoctlError> {
+        self.with_device(|io| io.set_fans_manual())
+    }
+
+    fn set_web_cam_enabled(&self, status: bool) -> Result<(), IoctlError> {
+        self.with_device(|io| io.set_web_cam_enabled(status))
+    }
+
+    fn get_web_cam_enabled(&self) -> Result<bool, IoctlError> {
+        self.with_device(|io| io.get_web_cam_enabled())
+    }
+
+    fn set_touchpad_enabled(&self, status: bool) -> Result<(), IoctlError> {
+        self.with_device(|io| io.set_touchpad_enabled(status))
+    }
+
+    fn get_touchpad_enabled(&self) -> Result<bool, IoctlError> {
+        self.with_device(|io| io.get_touchpad_enabled())
+    }
+
+    fn set_flight_mode(&self, status: bool) -> Result<(), IoctlError> {
+        self.with_device(|io| io.set_flight_mode(status))
+    }
+
+    fn get_flight_mode(&self) -> Result<bool, IoctlError> {
+        self.with_device(|io| io.get_flight_mode())
+    }
+
+    fn available_performance_profiles(&self) -> Vec<PerformanceProfile> {
+        self.with_device(|io| Ok(io.available_performance_profiles()))
+            .unwrap_or_default()
+    }
+
+    fn set_performance_profile(&self, profile: PerformanceProfile) -> Result<(), IoctlError> {
+        self.with_device(|io| io.set_performance_profile(profile))
+    }
+
+    fn get_performance_profile(&self) -> Result<Option<PerformanceProfile>, IoctlError> {
+        self.with_device(|io| io.get_performance_profile())
+    }
+}
+
+#[async_trait(?Send)]
+impl KeyboardBackend for KeyboardController {
+    fn zones(&self) -> Vec<KeyboardZone> {
+        KeyboardController::zones(self)
+    }
+
+    fn effects(&self) -> Vec<KeyboardState> {
+        KeyboardController::effects(self)
+    }
+
+    async fn set_color_left(&self, color: &Color) -> Result<(), io::Error> {
+        KeyboardController::set_color_left(self, color).await
+    }
+
+    async fn get_color_left(&self) -> Result<Color, io::Error> {
+        KeyboardController::get_color_left(self).await
+    }
+
+    async fn set_color_right(&self, color: &Color) -> Result<(), io::Error> {
+        KeyboardController::set_color_right(self, color).await
+    }
+
+    async fn get_color_right(&self) -> Result<Color, io::Error> {
+        KeyboardController::get_color_right(self).await
+    }
+
+    async fn set_color_center(&self, color: &Color) -> Result<(), io::Error> {
+        KeyboardController::set_color_center(self, color).await
+    }
+
+    async fn get_color_center(&self) -> Result<Color, io::Error> {
+        KeyboardController::get_color_center(self).await
+    }
+
+    async fn set_color_extra(&self, color: &Color) -> Result<(), io::Error> {
+        KeyboardController::set_color_extra(self, color).await
+    }
+
+    async fn get_color_extra(&self) -> Result<Color, io::Error> {
+        KeyboardController::get_color_extra(self).await
+    }
+
+    async fn set_color_all(&self, color: &Color) -> Result<(), io::Error> {
+        KeyboardController::set_color_all(self, color).await
+    }
+
+    async fn set_brightness(&self, brightness: u8) -> Result<(), io::Error> {
+        KeyboardController::set_brightness(self, brightness).await
+    }
+
+    async fn get_brightness(&self) -> Result<u8, io::Error> {
+        KeyboardController::get_brightness(self).await
+    }
+
+    async fn set_mode(&self, mode: bool) -> Result<(), io::Error> {
+        KeyboardController::set_mode(self, mode).await
+    }
+
+    async fn get_mode(&self) -> Result<bool, io::Error> {
+        KeyboardController::get_mode(self).await
+    }
+
+    async fn set_state(&self, state: KeyboardState) -> Result<(), io::Error> {
+        KeyboardController::set_state(self, state).await
+    }
+
+    async fn get_state(&self) -> Result<KeyboardState, io::Error> {
+        KeyboardController::get_state(self).await
+    }
+}
+
+fn logical_core(cores: &[LogicalCore], core: usize) -> Result<&LogicalCore, io::Error> {
+    cores.get(core).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("CPU core {core} doesn't exist"),
+        )
+    })
+}
+
+#[async_trait(?Send)]
+impl CpuBackend for CpuController {
+    fn core_count(&self) -> usize {
+        self.cores().len()
+    }
+
+    async fn set_core_online(&mut self, core: usize, online: bool) -> Result<(), io::Error> {
+        logical_core(self.cores(), core)?;
+        self.cores_mut()[core].set_online(online).await
+    }
+
+    async fn is_core_online(&self, core: usize) -> Result<bool, io::Error> {
+        logical_core(self.cores(), core)?.is_online().await
+    }
+
+    fn has_boost(&self) -> bool {
+        CpuController::has_boost(self)
+    }
+
+    async fn set_boost(&self, boost: bool) -> Result<(), io::Error> {
+        CpuController::set_boost(self, boost).await
+    }
+
+    async fn get_boost(&self) -> Result<bool, io::Error> {
+        CpuController::get_boost(self).await
+    }
+
+    async fn set_governor(&self, core: usize, governor: &str) -> Result<(), io::Error> {
+        logical_core(self.cores(), core)?
+            .set_governor(governor)
+            .await
+    }
+
+    async fn get_governor(&self, core: usize) -> Result<String, io::Error> {
+        logical_core(self.cores(), core)?.get_governor().await
+    }
+
+    async fn get_available_governors(&self, core: usize) -> Result<Vec<String>, io::Error> {
+        logical_core(self.cores(), core)?
+            .get_available_governors()
+            .await
+    }
+
+    async fn set_min_freq(&self, core: usize, freq: u32) -> Result<(), io::Error> {
+        logical_core(self.cores(), core)?.set_min_freq(freq).await
+    }
+
+    async fn set_max_freq(&self, core: usize, freq: u32) -> Result<(), io::Error> {
+        logical_core(self.cores(), core)?.set_max_freq(freq).await
+    }
+
+    async fn get_freq_limits(&self, core: usize) -> Result<(u32, u32), io::Error> {
+        let core = logical_core(self.cores(), core)?;
+        Ok((core.get_min_freq().await?, core.get_max_freq().await?))
+    }
+
+    async fn get_hardware_freq_limits(&self, core: usize) -> Result<(u32, u32), io::Error> {
+        let core = logical_core(self.cores(), core)?;
+        Ok((
+            core.get_hardware_min_freq().await?,
+            core.get_hardware_max_freq().await?,
+        ))
+    }
+
+    async fn set_energy_performance_preference(
+        &self,
+        core: usize,
+        preference: &str,
+    ) -> Result<(), io::Error> {
+        logical_core(self.cores(), core)?
+            .set_energy_performance_preference(preference)
+            .await
+    }
+
+    async fn get_energy_performance_preference(&self, core: usize) -> Result<String, io::Error> {
+        logical_core(self.cores(), core)?
+            .get_energy_performance_preference()
+            .await
+    }
+
+    async fn get_available_energy_performance_preferences(
+        &self,
+        core: usize,
+    ) -> Result<Vec<String>, io::Error> {
+        logical_core(self.cores(), core)?
+            .get_available_energy_performance_preferences()
+            .await
+    }
+}
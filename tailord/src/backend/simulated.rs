@@ -0,0 +1,580 @@
+use std::{
+    io,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use tailor_api::{Color, KeyboardZone};
+use tuxedo_ioctl::{
+    high_level::{Fan, Hw, PerformanceProfile},
+    IoctlError,
+};
+use tuxedo_sysfs::keyboard::KeyboardState;
+
+use super::{CpuBackend, IoBackend, KeyboardBackend};
+
+/// Temperature of the environment in °C.
+const AMBIENT_TEMP: f64 = 30.0;
+/// Temperature of the device when the daemon starts in °C.
+const START_TEMP: f64 = 45.0;
+/// Heat capacity of the device in J/°C.
+const HEAT_CAPACITY: f64 = 20.0;
+/// Heat dissipated without any fan activity in W/°C.
+const PASSIVE_COOLING: f64 = 0.6;
+/// Additional heat dissipated at 100% fan speed in W/°C.
+const FAN_COOLING: f64 = 0.6;
+/// Constant heat produced by the component below each fan in W.
+/// The first fan cools the CPU, the second one the GPU.
+const HEAT_LOAD: [f64; 2] = [40.0, 25.0];
+
+#[derive(Debug)]
+struct SimulatedFan {
+    speed: u8,
+    temp: f64,
+    heat_load: f64,
+}
+
+impl SimulatedFan {
+    fn new(heat_load: f64) -> Self {
+        Self {
+            speed: 0,
+            temp: START_TEMP,
+            heat_load,
+        }
+    }
+
+    /// Move the temperature towards the equilibrium of
+    /// produced and dissipated heat at the current fan speed.
+    fn advance(&mut self, duration: Duration, fans_auto: bool) {
+        if fans_auto {
+            // Mimic a simple firmware fan curve.
+            self.speed = ((self.temp - 40.0) * 2.0).clamp(0.0, 100.0).round() as u8;
+        }
+
+        let cooling = PASSIVE_COOLING + FAN_COOLING * f64::from(self.speed) / 100.0;
+        let equilibrium = AMBIENT_TEMP + self.heat_load / cooling;
+        let decay = (-duration.as_secs_f64() * cooling / HEAT_CAPACITY).exp();
+        self.temp = equilibrium + (self.temp - equilibrium) * decay;
+    }
+}
+
+#[derive(Debug)]
+struct IoState {
+    fans: Vec<SimulatedFan>,
+    fans_auto: bool,
+    web_cam_enabled: bool,
+    touchpad_enabled: bool,
+    flight_mode: bool,
+    performance_profile: PerformanceProfile,
+    last_update: Instant,
+}
+
+impl IoState {
+    fn advance(&mut self, duration: Duration) {
+        let fans_auto = self.fans_auto;
+        for fan in &mut self.fans {
+            fan.advance(duration, fans_auto);
+        }
+    }
+
+    fn update(&mut self) {
+        let now = Instant::now();
+        self.advance(now - self.last_update);
+        self.last_update = now;
+    }
+
+    fn fan(&mut self, fan: Fan) -> Result<&mut SimulatedFan, IoctlError> {
+        let idx = match fan {
+            Fan::Fan1 => 0,
+            Fan::Fan2 => 1,
+            Fan::Fan3 => 2,
+        };
+        self.fans.get_mut(idx).ok_or(IoctlError::SensorNotAvailable)
+    }
+}
+
+/// A Clevo device with two fans that lives entirely in memory.
+///
+/// The temperature below each fan rises under a constant
+/// heat load and drops as the fan speed increases.
+#[derive(Debug)]
+pub struct SimulatedIo {
+    state: Mutex<IoState>,
+}
+
+impl Default for SimulatedIo {
+    fn default() -> Self {
+        Self {
+            state: Mutex::new(IoState {
+                fans: HEAT_LOAD.into_iter().map(SimulatedFan::new).collect(),
+                fans_auto: true,
+                web_cam_enabled: true,
+                touchpad_enabled: true,
+                flight_mode: false,
+                performance_profile: PerformanceProfile::default(),
+                last_update: Instant::now(),
+            }),
+        }
+    }
+}
+
+impl SimulatedIo {
+    fn with_state<T>(&self, f: impl FnOnce(&mut IoState) -> T) -> T {
+        let mut state = self.state.lock().unwrap();
+        state.update();
+        f(&mut state)
+    }
+
+    #[cfg(test)]
+    pub fn fans_auto(&self) -> bool {
+        self.with_state(|state| state.fans_auto)
+    }
+}
+
+impl IoBackend for SimulatedIo {
+    fn hw(&self) -> Result<Hw, IoctlError> {
+        Ok(Hw::Clevo)
+    }
+
+    fn driver_version(&self) -> Result<String, IoctlError> {
+        Ok("simulated".to_string())
+    }
+
+    fn set_fan_speed_percent(&self, fan: Fan, fan_speed_percent: u8) -> Result<(), IoctlError> {
+        self.with_state(|state| {
+            state.fan(fan)?.speed = fan_speed_percent.clamp(0, 100);
+            Ok(())
+        })
+    }
+
+    fn get_fan_speed_percent(&self, fan: Fan) -> Result<u8, IoctlError> {
+        self.with_state(|state| Ok(state.fan(fan)?.speed))
+    }
+
+    fn get_fan_temperature(&self, fan: Fan) -> Result<u8, IoctlError> {
+        self.with_state(|state| Ok(state.fan(fan)?.temp.round() as u8))
+    }
+
+    fn set_fans_auto(&self) -> Result<(), IoctlError> {
+        self.with_state(|state| state.fans_auto = true);
+        Ok(())
+    }
+
+    fn set_fans_manual(&self) -> Result<(), IoctlError> {
+        self.with_state(|state| state.fans_auto = false);
+        Ok(())
+    }
+
+    fn set_web_cam_enabled(&self, status: bool) -> Result<(), IoctlError> {
+        self.with_state(|state| state.web_cam_enabled = status);
+        Ok(())
+    }
+
+    fn get_web_cam_enabled(&self) -> Result<bool, IoctlError> {
+        self.with_state(|state| Ok(state.web_cam_enabled))
+    }
+
+    fn set_touchpad_enabled(&self, status: bool) -> Result<(), IoctlError> {
+        self.with_state(|state| state.touchpad_enabled = status);
+        Ok(())
+    }
+
+    fn get_touchpad_enabled(&self) -> Result<bool, IoctlError> {
+        self.with_state(|state| Ok(state.touchpad_enabled))
+    }
+
+    fn set_flight_mode(&self, status: bool) -> Result<(), IoctlError> {
+        self.with_state(|state| state.flight_mode = status);
+        Ok(())
+    }
+
+    fn get_flight_mode(&self) -> Result<bool, IoctlError> {
+        self.with_state(|state| Ok(state.flight_mode))
+    }
+
+    fn available_performance_profiles(&self) -> Vec<PerformanceProfile> {
+        Hw::Clevo.performance_profiles().to_vec()
+    }
+
+    fn set_performance_profile(&self, profile: PerformanceProfile) -> Result<(), IoctlError> {
+        if !Hw::Clevo.performance_profiles().contains(&profile) {
+            return Err(IoctlError::Unsupported);
+        }
+        self.with_state(|state| state.performance_profile = profile);
+        Ok(())
+    }
+
+    fn get_performance_profile(&self) -> Result<Option<PerformanceProfile>, IoctlError> {
+        self.with_state(|state| Ok(Some(state.performance_profile)))
+    }
+}
+
+/// Number of logical cores of the simulated CPU.
+const CPU_CORES: usize = 8;
+/// Frequency limits of the simulated CPU in kHz.
+const CPU_FREQ_LIMITS: (u32, u32) = (400_000, 4_800_000);
+const CPU_GOVERNORS: [&str; 2] = ["performance", "powersave"];
+const CPU_ENERGY_PERFORMANCE_PREFERENCES: [&str; 5] = [
+    "default",
+    "performance",
+    "balance_performance",
+    "balance_power",
+    "power",
+];
+
+#[derive(Debug)]
+struct SimulatedCore {
+    online: bool,
+    governor: String,
+    min_freq: u32,
+    max_freq: u32,
+    energy_performance_preference: String,
+}
+
+#[derive(Debug)]
+struct CpuState {
+    cores: Vec<SimulatedCore>,
+    boost: bool,
+}
+
+/// A CPU with an `intel_pstate` like frequency scaling that lives entirely in memory.
+///
+/// Invalid values are rejected just like the kernel does.
+#[derive(Debug)]
+pub struct SimulatedCpu {
+    state: Mutex<CpuState>,
+}
+
+impl Default for SimulatedCpu {
+    fn default() -> Self {
+        let cores = (0..CPU_CORES)
+            .map(|_| SimulatedCore {
+                online: true,
+                governor: "powersave".to_string(),
+                min_freq: CPU_FREQ_LIMITS.0,
+                max_freq: CPU_FREQ_LIMITS.1,
+                energy_performance_preference: "balance_performance".to_string(),
+            })
+            .collect();
+        Self {
+            state: Mutex::new(CpuState { cores, boost: true }),
+        }
+    }
+}
+
+fn invalid_input(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+impl SimulatedCpu {
+    fn with_core<T>(
+        &self,
+        core: usize,
+        f: impl FnOnce(&mut SimulatedCore) -> Result<T, io::Error>,
+    ) -> Result<T, io::Error> {
+        let mut state = self.state.lock().unwrap();
+        let core = state.cores.get_mut(core).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("CPU core {core} doesn't exist"),
+            )
+        })?;
+        f(core)
+    }
+}
+
+#[async_trait(?Send)]
+impl CpuBackend for SimulatedCpu {
+    fn core_count(&self) -> usize {
+        CPU_CORES
+    }
+
+    async fn set_core_online(&mut self, core: usize, online: bool) -> Result<(), io::Error> {
+        if core == 0 && !online {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "The first CPU core can't be taken offline",
+            ));
+        }
+        self.with_core(core, |core| {
+            core.online = online;
+            Ok(())
+        })
+    }
+
+    async fn is_core_online(&self, core: usize) -> Result<bool, io::Error> {
+        self.with_core(core, |core| Ok(core.online))
+    }
+
+    fn has_boost(&self) -> bool {
+        true
+    }
+
+    async fn set_boost(&self, boost: bool) -> Result<(), io::Error> {
+        self.state.lock().unwrap().boost = boost;
+        Ok(())
+    }
+
+    async fn get_boost(&self) -> Result<bool, io::Error> {
+        Ok(self.state.lock().unwrap().boost)
+    }
+
+    async fn set_governor(&self, core: usize, governor: &str) -> Result<(), io::Error> {
+        if !CPU_GOVERNORS.contains(&governor) {
+            return Err(invalid_input(format!("Unknown governor `{governor}`")));
+        }
+        self.with_core(core, |core| {
+            core.governor = governor.to_string();
+            Ok(())
+        })
+    }
+
+    async fn get_governor(&self, core: usize) -> Result<String, io::Error> {
+        self.with_core(core, |core| Ok(core.governor.clone()))
+    }
+
+    async fn get_available_governors(&self, core: usize) -> Result<Vec<String>, io::Error> {
+        self.with_core(core, |_| {
+            Ok(CPU_GOVERNORS.iter().map(ToString::to_string).collect())
+        })
+    }
+
+    async fn set_min_freq(&self, core: usize, freq: u32) -> Result<(), io::Error> {
+        self.with_core(core, |core| {
+            if freq < CPU_FREQ_LIMITS.0 || freq > core.max_freq {
+                return Err(invalid_input(format!("Invalid minimum frequency {freq}")));
+            }
+            core.min_freq = freq;
+            Ok(())
+        })
+    }
+
+    async fn set_max_freq(&self, core: usize, freq: u32) -> Result<(), io::Error> {
+        self.with_core(core, |core| {
+            if freq > CPU_FREQ_LIMITS.1 || freq < core.min_freq {
+                return Err(invalid_input(format!("Invalid maximum frequency {freq}")));
+            }
+            core.max_freq = freq;
+            Ok(())
+        })
+    }
+
+    async fn get_freq_limits(&self, core: usize) -> Result<(u32, u32), io::Error> {
+        self.with_core(core, |core| Ok((core.min_freq, core.max_freq)))
+    }
+
+    async fn get_hardware_freq_limits(&self, core: usize) -> Result<(u32, u32), io::Error> {
+        self.with_core(core, |_| Ok(CPU_FREQ_LIMITS))
+    }
+
+    async fn set_energy_performance_preference(
+        &self,
+        core: usize,
+        preference: &str,
+    ) -> Result<(), io::Error> {
+        if !CPU_ENERGY_PERFORMANCE_PREFERENCES.contains(&preference) {
+            return Err(invalid_input(format!(
+                "Unknown energy performance preference `{preference}`"
+            )));
+        }
+        self.with_core(core, |core| {
+            core.energy_performance_preference = preference.to_string();
+            Ok(())
+        })
+    }
+
+    async fn get_energy_performance_preference(&self, core: usize) -> Result<String, io::Error> {
+        self.with_core(core, |core| Ok(core.energy_performance_preference.clone()))
+    }
+
+    async fn get_available_energy_performance_preferences(
+        &self,
+        core: usize,
+    ) -> Result<Vec<String>, io::Error> {
+        self.with_core(core, |_| {
+            Ok(CPU_ENERGY_PERFORMANCE_PREFERENCES
+                .iter()
+                .map(ToString::to_string)
+                .collect())
+        })
+    }
+}
+
+#[derive(Debug)]
+struct KeyboardZones {
+    color_left: Color,
+    color_center: Color,
+    color_right: Color,
+    color_extra: Color,
+    brightness: u8,
+    mode: bool,
+    state: KeyboardState,
+}
+
+/// A keyboard with all four color zones that lives entirely in memory.
+#[derive(Debug)]
+pub struct SimulatedKeyboard {
+    zones: Mutex<KeyboardZones>,
+}
+
+impl Default for SimulatedKeyboard {
+    fn default() -> Self {
+        let white = Color {
+            r: 255,
+            g: 255,
+            b: 255,
+        };
+        Self {
+            zones: Mutex::new(KeyboardZones {
+                color_left: white.clone(),
+                color_center: white.clone(),
+                color_right: white.clone(),
+                color_extra: white,
+                brightness: 255,
+                mode: false,
+                state: KeyboardState::Custom,
+            }),
+        }
+    }
+}
+
+impl SimulatedKeyboard {
+    fn with_zones<T>(&self, f: impl FnOnce(&mut KeyboardZones) -> T) -> Result<T, io::Error> {
+        Ok(f(&mut self.zones.lock().unwrap()))
+    }
+}
+
+#[async_trait(?Send)]
+impl KeyboardBackend for SimulatedKeyboard {
+    fn zones(&self) -> Vec<KeyboardZone> {
+        KeyboardZone::ALL.to_vec()
+    }
+
+    fn effects(&self) -> Vec<KeyboardState> {
+        KeyboardState::EFFECTS.to_vec()
+    }
+
+    async fn set_color_left(&self, color: &Color) -> Result<(), io::Error> {
+        self.with_zones(|zones| zones.color_left = color.clone())
+    }
+
+    async fn get_color_left(&self) -> Result<Color, io::Error> {
+        self.with_zones(|zones| zones.color_left.clone())
+    }
+
+    async fn set_color_right(&self, color: &Color) -> Result<(), io::Error> {
+        self.with_zones(|zones| zones.color_right = color.clone())
+    }
+
+    async fn get_color_right(&self) -> Result<Color, io::Error> {
+        self.with_zones(|zones| zones.color_right.clone())
+    }
+
+    async fn set_color_center(&self, color: &Color) -> Result<(), io::Error> {
+        self.with_zones(|zones| zones.color_center = color.clone())
+    }
+
+    async fn get_color_center(&self) -> Result<Color, io::Error> {
+        self.with_zones(|zones| zones.color_center.clone())
+    }
+
+    async fn set_color_extra(&self, color: &Color) -> Result<(), io::Error> {
+        self.with_zones(|zones| zones.color_extra = color.clone())
+    }
+
+    async fn get_color_extra(&self) -> Result<Color, io::Error> {
+        self.with_zones(|zones| zones.color_extra.clone())
+    }
+
+    async fn set_color_all(&self, color: &Color) -> Result<(), io::Error> {
+        self.with_zones(|zones| {
+            zones.color_left = color.clone();
+            zones.color_center = color.clone();
+            zones.color_right = color.clone();
+            zones.color_extra = color.clone();
+        })
+    }
+
+    async fn set_brightness(&self, brightness: u8) -> Result<(), io::Error> {
+        self.with_zones(|zones| zones.brightness = brightness)
+    }
+
+    async fn get_brightness(&self) -> Result<u8, io::Error> {
+        self.with_zones(|zones| zones.brightness)
+    }
+
+    async fn set_mode(&self, mode: bool) -> Result<(), io::Error> {
+        self.with_zones(|zones| zones.mode = mode)
+    }
+
+    async fn get_mode(&self) -> Result<bool, io::Error> {
+        self.with_zones(|zones| zones.mode)
+    }
+
+    async fn set_state(&self, state: KeyboardState) -> Result<(), io::Error> {
+        self.with_zones(|zones| zones.state = state)
+    }
+
+    async fn get_state(&self) -> Result<KeyboardState, io::Error> {
+        self.with_zones(|zones| zones.state)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use tuxedo_ioctl::{high_level::Fan, IoctlError};
+
+    use super::SimulatedIo;
+    use crate::backend::IoBackend;
+
+    fn advance(io: &SimulatedIo, secs: u64) {
+        io.state.lock().unwrap().advance(Duration::from_secs(secs));
+    }
+
+    #[test]
+    fn temperature_follows_fan_speed() {
+        let io = SimulatedIo::default();
+        io.set_fans_manual().unwrap();
+        let start_temp = io.get_fan_temperature(Fan::Fan1).unwrap();
+
+        // Without any fan activity the device heats up.
+        io.set_fan_speed_percent(Fan::Fan1, 0).unwrap();
+        advance(&io, 60);
+        let hot_temp = io.get_fan_temperature(Fan::Fan1).unwrap();
+        assert!(hot_temp > start_temp);
+
+        // Full fan speed cools it down again.
+        io.set_fan_speed_percent(Fan::Fan1, 100).unwrap();
+        advance(&io, 60);
+        let cool_temp = io.get_fan_temperature(Fan::Fan1).unwrap();
+        assert!(cool_temp < hot_temp);
+
+        // The temperature settles at an equilibrium.
+        advance(&io, 3600);
+        let settled_temp = io.get_fan_temperature(Fan::Fan1).unwrap();
+        advance(&io, 60);
+        assert_eq!(io.get_fan_temperature(Fan::Fan1).unwrap(), settled_temp);
+
+        assert_eq!(io.get_fan_speed_percent(Fan::Fan1).unwrap(), 100);
+        assert_eq!(io.available_fans(), [Fan::Fan1, Fan::Fan2]);
+        assert_eq!(
+            io.get_fan_temperature(Fan::Fan3).unwrap_err(),
+            IoctlError::SensorNotAvailable
+        );
+    }
+
+    #[test]
+    fn firmware_controls_fans_in_auto_mode() {
+        let io = SimulatedIo::default();
+        io.set_fans_manual().unwrap();
+        io.set_fan_speed_percent(Fan::Fan1, 0).unwrap();
+        advance(&io, 60);
+
+        io.set_fans_auto().unwrap();
+        advance(&io, 1);
+        assert!(io.get_fan_speed_percent(Fan::Fan1).unwrap() > 0);
+    }
+}
@@ -0,0 +1,240 @@
+use std::io;
+
+use tailor_api::CpuProfile;
+use tokio::sync::mpsc;
+
+use crate::backend::CpuBackend;
+
+pub struct CpuRuntime {
+    io: Box<dyn CpuBackend>,
+    profile: CpuProfile,
+}
+
+impl CpuRuntime {
+    pub fn new(io: Box<dyn CpuBackend>, profile: CpuProfile) -> Self {
+        Self { io, profile }
+    }
+
+    pub async fn run(mut self, mut cpu_receiver: mpsc::Receiver<CpuProfile>) {
+        self.apply_profile().await;
+
+        while let Some(profile) = cpu_receiver.recv().await {
+            self.profile = profile;
+            self.apply_profile().await;
+        }
+    }
+
+    /// Apply all settings of the profile.
+    ///
+    /// Settings that aren't supported by the CPU or its
+    /// driver are skipped and logged once per setting.
+    async fn apply_profile(&mut self) {
+        tracing::info!("Applying CPU profile {:?}", self.profile);
+
+        // Bring the cores online first so that they receive the other settings as well.
+        if let Some(online_cores) = self.requested_online_cores() {
+            for core in 0..self.io.core_count() {
+                let online = core < online_cores;
+                if self.io.is_core_online(core).await.ok() != Some(online) {
+                    if let Err(err) = self.io.set_core_online(core, online).await {
+                        tracing::error!(
+                            "Failed to set CPU core {core} online to {online}: `{err}`"
+                        );
+                    }
+                }
+            }
+        }
+        let online_cores = self.online_cores().await;
+
+        if let Some(boost) = self.profile.boost {
+            if self.io.has_boost() && self.io.get_boost().await.ok() != Some(boost) {
+                if let Err(err) = self.io.set_boost(boost).await {
+                    tracing::error!("Failed to set CPU boost: `{err}`");
+                }
+            }
+        }
+
+        if !self.profile.governor.is_empty() {
+            for &core in &online_cores {
+                if !log_core_error(self.apply_governor(core).await, core, "governor") {
+                    break;
+                }
+            }
+        }
+        for &core in &online_cores {
+            if !log_core_error(self.apply_freq_limits(core).await, core, "frequency limits") {
+                break;
+            }
+        }
+        if !self.profile.energy_performance_preference.is_empty() {
+            for &core in &online_cores {
+                let result = self.apply_energy_performance_preference(core).await;
+                if !log_core_error(result, core, "energy performance preference") {
+                    break;
+                }
+            }
+        }
+    }
+
+    /// The number of cores the profile wants online
+    /// or `None` to keep the current cores online.
+    fn requested_online_cores(&self) -> Option<usize> {
+        match self.profile.online_cores as usize {
+            0 => None,
+            // The first core can't be taken offline.
+            cores => Some(cores.clamp(1, self.io.core_count())),
+        }
+    }
+
+    async fn online_cores(&self) -> Vec<usize> {
+        let mut online_cores = Vec::new();
+        for core in 0..self.io.core_count() {
+            if self.io.is_core_online(core).await.unwrap_or(false) {
+                online_cores.push(core);
+            }
+        }
+        online_cores
+    }
+
+    async fn apply_governor(&self, core: usize) -> Result<(), io::Error> {
+        let governor = &self.profile.governor;
+        if self.io.get_governor(core).await.ok().as_ref() == Some(governor) {
+            return Ok(());
+        }
+        if !self
+            .io
+            .get_available_governors(core)
+            .await?
+            .contains(governor)
+        {
+            return Err(unsupported(governor));
+        }
+        self.io.set_governor(core, governor).await
+    }
+
+    async fn apply_energy_performance_preference(&self, core: usize) -> Result<(), io::Error> {
+        let preference = &self.profile.energy_performance_preference;
+        if self
+            .io
+            .get_energy_performance_preference(core)
+            .await
+            .ok()
+            .as_ref()
+            == Some(preference)
+        {
+            return Ok(());
+        }
+        let available = self
+            .io
+            .get_available_energy_performance_preferences(core)
+            .await?;
+        if !available.contains(preference) {
+            return Err(unsupported(preference));
+        }
+        self.io
+            .set_energy_performance_preference(core, preference)
+            .await
+    }
+
+    async fn apply_freq_limits(&self, core: usize) -> Result<(), io::Error> {
+        let (hardware_min, hardware_max) = self.io.get_hardware_freq_limits(core).await?;
+        let limit = |freq: u32, default: u32| match freq {
+            0 => default,
+            freq => freq.clamp(hardware_min, hardware_max),
+        };
+        let min_freq = limit(self.profile.min_freq, hardware_min);
+        let max_freq = limit(self.profile.max_freq, hardware_max);
+
+        if min_freq > max_freq {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("The minimum frequency {min_freq} is above the maximum {max_freq}"),
+            ));
+        }
+
+        if self.io.get_freq_limits(core).await.ok() == Some((min_freq, max_freq)) {
+            return Ok(());
+        }
+
+        // The minimum can't exceed the current maximum, so widen the range first.
+        self.io.set_max_freq(core, hardware_max).await?;
+        self.io.set_min_freq(core, min_freq).await?;
+        self.io.set_max_freq(core, max_freq).await
+    }
+}
+
+fn unsupported(value: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!("`{value}` isn't supported by this CPU"),
+    )
+}
+
+/// Returns `false` if the setting failed.
+fn log_core_error(result: Result<(), io::Error>, core: usize, setting: &str) -> bool {
+    if let Err(err) = &result {
+        tracing::error!("Failed to set the {setting} of CPU core {core}: `{err}`");
+    }
+    result.is_ok()
+}
+
+#[cfg(test)]
+mod test {
+    use tailor_api::CpuProfile;
+
+    use super::CpuRuntime;
+    use crate::backend::SimulatedCpu;
+
+    #[test]
+    fn apply_profile() {
+        futures::executor::block_on(async {
+            let profile = CpuProfile {
+                governor: "performance".to_string(),
+                min_freq: 1_000_000,
+                max_freq: 100_000_000,
+                boost: Some(false),
+                energy_performance_preference: "power".to_string(),
+                online_cores: 2,
+            };
+            let mut runtime = CpuRuntime::new(Box::<SimulatedCpu>::default(), profile);
+            runtime.apply_profile().await;
+
+            let io = &runtime.io;
+            assert!(!io.get_boost().await.unwrap());
+            assert!(io.is_core_online(1).await.unwrap());
+            assert!(!io.is_core_online(2).await.unwrap());
+            assert_eq!(io.get_governor(1).await.unwrap(), "performance");
+            assert_eq!(io.get_freq_limits(1).await.unwrap(), (1_000_000, 4_800_000));
+            assert_eq!(
+                io.get_energy_performance_preference(1).await.unwrap(),
+                "power"
+            );
+            // Offline cores keep their settings.
+            assert_eq!(io.get_governor(2).await.unwrap(), "powersave");
+
+            // Unknown values don't prevent other settings.
+            runtime.profile = CpuProfile {
+                governor: "unknown".to_string(),
+                ..CpuProfile::default()
+            };
+            runtime.apply_profile().await;
+            let io = &runtime.io;
+            assert_eq!(io.get_governor(1).await.unwrap(), "performance");
+            assert_eq!(io.get_freq_limits(1).await.unwrap(), (400_000, 4_800_000));
+            // The default profile keeps the boost and online cores.
+            assert!(!io.get_boost().await.unwrap());
+            assert!(io.is_core_online(1).await.unwrap());
+            assert!(!io.is_core_online(2).await.unwrap());
+
+            runtime.profile = CpuProfile {
+                boost: Some(true),
+                online_cores: 8,
+                ..CpuProfile::default()
+            };
+            runtime.apply_profile().await;
+            let io = &runtime.io;
+            assert!(io.get_boost().await.unwrap());
+            assert!(io.is_core_online(7).await.unwrap());
+        });
+    }
+}
@@ -0,0 +1,140 @@
+use tokio::sync::{mpsc, watch};
+use zbus::{dbus_interface, fdo, Connection, SignalContext};
+
+use crate::DBUS_PATH;
+
+/// Object path of the keyboard backlight in UPower.
+pub const UPOWER_KBD_BACKLIGHT_PATH: &str = "/org/freedesktop/UPower/KbdBacklight";
+
+const MAX_BRIGHTNESS: u8 = u8::MAX;
+
+/// Brightness of the keyboard backlight.
+///
+/// `Brightness` emits `PropertiesChanged`, including changes
+/// caused by activating a global profile.
+#[derive(Clone)]
+pub struct KeyboardBacklightInterface {
+    pub brightness: watch::Receiver<Option<u8>>,
+    pub brightness_sender: mpsc::Sender<u8>,
+}
+
+#[dbus_interface(name = "com.tux.Tailor.KeyboardBacklight")]
+impl KeyboardBacklightInterface {
+    /// `0` if the keyboard has no brightness control.
+    #[dbus_interface(property)]
+    async fn brightness(&self) -> u8 {
+        self.brightness.borrow().unwrap_or_default()
+    }
+
+    #[dbus_interface(property)]
+    async fn max_brightness(&self) -> u8 {
+        MAX_BRIGHTNESS
+    }
+
+    /// Change the brightness until another global profile is activated.
+    pub(super) async fn set_brightness(&self, brightness: u8) -> fdo::Result<()> {
+        if self.brightness.borrow().is_none() {
+            return Err(fdo::Error::NotSupported(
+                "The keyboard brightness can't be changed on this device".to_string(),
+            ));
+        }
+
+        self.brightness_sender
+            .send(brightness)
+            .await
+            .map_err(|err| fdo::Error::Failed(format!("Internal error: `{err}`")))
+    }
+}
+
+/// The keyboard backlight interface of UPower.
+///
+/// Only served with `--upower-kbd-backlight` and only on tailord's
+/// own bus name. Desktops talk to UPower itself for brightness keys
+/// and sliders, so this is for clients that are pointed at tailord.
+pub struct UPowerKbdBacklightInterface(pub KeyboardBacklightInterface);
+
+#[dbus_interface(name = "org.freedesktop.UPower.KbdBacklight")]
+impl UPowerKbdBacklightInterface {
+    async fn get_max_brightness(&self) -> i32 {
+        MAX_BRIGHTNESS as i32
+    }
+
+    async fn get_brightness(&self) -> i32 {
+        self.0.brightness().await as i32
+    }
+
+    async fn set_brightness(&self, value: i32) -> fdo::Result<()> {
+        let brightness = u8::try_from(value).map_err(|_| {
+            fdo::Error::InvalidArgs(format!(
+                "Brightness must be between 0 and {MAX_BRIGHTNESS}, got {value}"
+            ))
+        })?;
+        self.0.set_brightness(brightness).await
+    }
+
+    #[dbus_interface(signal)]
+    async fn brightness_changed(ctxt: &SignalContext<'_>, value: i32) -> zbus::Result<()>;
+
+    /// `source` is always `external` because tailord can't tell
+    /// whether the firmware changed the brightness on its own.
+    #[dbus_interface(signal)]
+    async fn brightness_changed_with_source(
+        ctxt: &SignalContext<'_>,
+        value: i32,
+        source: &str,
+    ) -> zbus::Result<()>;
+}
+
+/// Announce brightness changes of the keyboard runtime on both interfaces.
+pub async fn emit_brightness_changes(
+    connection: Connection,
+    mut receiver: watch::Receiver<Option<u8>>,
+    upower: bool,
+) {
+    let iface_ref = match connection
+        .object_server()
+        .interface::<_, KeyboardBacklightInterface>(DBUS_PATH)
+        .await
+    {
+        Ok(iface_ref) => iface_ref,
+        Err(err) => {
+            tracing::error!("Failed to access the keyboard backlight interface: `{err}`");
+            return;
+        }
+    };
+    let upower_ctxt = match SignalContext::new(&connection, UPOWER_KBD_BACKLIGHT_PATH) {
+        Ok(ctxt) => ctxt,
+        Err(err) => {
+            tracing::error!("Failed to create the signal context: `{err}`");
+            return;
+        }
+    };
+
+    while receiver.changed().await.is_ok() {
+        let Some(brightness) = *receiver.borrow_and_update() else {
+            continue;
+        };
+
+        let result = async {
+            let iface = iface_ref.get().await;
+            iface.brightness_changed(iface_ref.signal_context()).await?;
+
+            if upower {
+                let value = brightness as i32;
+                UPowerKbdBacklightInterface::brightness_changed(&upower_ctxt, value).await?;
+                UPowerKbdBacklightInterface::brightness_changed_with_source(
+                    &upower_ctxt,
+                    value,
+                    "external",
+                )
+                .await?;
+            }
+            zbus::Result::Ok(())
+        }
+        .await;
+
+        if let Err(err) = result {
+            tracing::error!("Failed to emit keyboard brightness changes: `{err}`");
+        }
+    }
+}
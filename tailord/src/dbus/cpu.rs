@@ -0,0 +1,112 @@
+use tailor_api::{CpuProfile, ProfileInfo};
+use tokio::sync::mpsc;
+use zbus::{dbus_interface, fdo, SignalContext};
+
+use crate::{
+    profiles::{paths, Profile},
+    util,
+};
+
+use super::CpuInterfaceV2;
+
+#[derive(Clone)]
+pub struct CpuInterface {
+    pub cpu_sender: mpsc::Sender<CpuProfile>,
+}
+
+#[dbus_interface(name = "com.tux.Tailor.Cpu")]
+impl CpuInterface {
+    pub(super) async fn add_profile(
+        &self,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+        name: &str,
+        value: &str,
+    ) -> fdo::Result<()> {
+        // Verify correctness of the file.
+        serde_json::from_str::<CpuProfile>(value)
+            .map_err(|err| fdo::Error::InvalidArgs(err.to_string()))?;
+        util::write_file(&paths().cpu_dir, name, value.as_bytes()).await?;
+
+        // Reload if the CPU profile is part of the active global profile
+        let info = Profile::get_active_profile_info()?;
+        if info.cpu == name {
+            let info = Profile::reload()?;
+            self.cpu_sender
+                .send(info.cpu)
+                .await
+                .map_err(|err| fdo::Error::Failed(format!("Internal error: `{err}`")))?;
+        }
+
+        emit_signal!(CpuInterface, CpuInterfaceV2, profile_added, &ctxt, name);
+        Ok(())
+    }
+
+    pub(super) async fn get_profile(&self, name: &str) -> fdo::Result<String> {
+        util::read_file(&paths().cpu_dir, name).await
+    }
+
+    pub(super) async fn list_profiles(&self) -> fdo::Result<Vec<String>> {
+        util::get_profiles(&paths().cpu_dir).await
+    }
+
+    pub(super) async fn remove_profile(
+        &self,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+        name: &str,
+    ) -> fdo::Result<()> {
+        util::remove_file(&paths().cpu_dir, name).await?;
+
+        emit_signal!(CpuInterface, CpuInterfaceV2, profile_removed, &ctxt, name);
+        Ok(())
+    }
+
+    pub(super) async fn rename_profile(
+        &self,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+        from: &str,
+        to: &str,
+    ) -> fdo::Result<Vec<String>> {
+        if self.list_profiles().await?.contains(&to.to_string()) {
+            Err(fdo::Error::InvalidArgs(format!(
+                "File `{to}` already exists"
+            )))
+        } else {
+            let profiles = util::get_profiles(&paths().profile_dir).await?;
+
+            for profile in profiles {
+                let mut data =
+                    util::read_json::<ProfileInfo>(&paths().profile_dir, &profile).await?;
+                if data.cpu == from {
+                    data.cpu = to.to_string();
+                    util::write_json(&paths().profile_dir, &profile, &data).await?;
+                }
+            }
+
+            util::move_file(&paths().cpu_dir, from, to).await?;
+
+            emit_signal!(
+                CpuInterface,
+                CpuInterfaceV2,
+                profile_renamed,
+                &ctxt,
+                from,
+                to
+            );
+
+            self.list_profiles().await
+        }
+    }
+
+    #[dbus_interface(signal)]
+    pub(super) async fn profile_added(ctxt: &SignalContext<'_>, name: &str) -> zbus::Result<()>;
+
+    #[dbus_interface(signal)]
+    pub(super) async fn profile_removed(ctxt: &SignalContext<'_>, name: &str) -> zbus::Result<()>;
+
+    #[dbus_interface(signal)]
+    pub(super) async fn profile_renamed(
+        ctxt: &SignalContext<'_>,
+        from: &str,
+        to: &str,
+    ) -> zbus::Result<()>;
+}
@@ -0,0 +1,101 @@
+use tailor_api::{Capabilities, SubsystemStatus};
+use tokio::sync::watch;
+use tuxedo_ioctl::high_level::Fan;
+use zbus::{dbus_interface, Connection, SignalContext};
+
+use crate::{
+    backend::{IoBackend, KeyboardBackend},
+    subsystems::{self, SubsystemStates},
+    switches::SwitchControl,
+    DBUS_PATH,
+};
+
+pub struct DeviceInterface {
+    pub capabilities: watch::Receiver<Capabilities>,
+    pub subsystems: watch::Receiver<SubsystemStates>,
+}
+
+#[dbus_interface(name = "com.tux.Tailor.Device")]
+impl DeviceInterface {
+    /// Hardware features of all subsystems that are available.
+    async fn get_capabilities(&self) -> Capabilities {
+        self.capabilities.borrow().clone()
+    }
+
+    async fn get_subsystems(&self) -> Vec<SubsystemStatus> {
+        subsystems::statuses(&self.subsystems.borrow())
+    }
+
+    /// Emitted when a subsystem becomes available or fails to start,
+    /// which can change the capabilities as well.
+    #[dbus_interface(signal)]
+    async fn subsystems_changed(
+        ctxt: &SignalContext<'_>,
+        subsystems: Vec<SubsystemStatus>,
+    ) -> zbus::Result<()>;
+}
+
+/// Emit `SubsystemsChanged` whenever the state of a subsystem changes.
+pub async fn emit_subsystem_changes(
+    connection: Connection,
+    mut receiver: watch::Receiver<SubsystemStates>,
+) {
+    let ctxt = match SignalContext::new(&connection, DBUS_PATH) {
+        Ok(ctxt) => ctxt,
+        Err(err) => {
+            tracing::error!("Failed to create the signal context: `{err}`");
+            return;
+        }
+    };
+
+    while receiver.changed().await.is_ok() {
+        let statuses = subsystems::statuses(&receiver.borrow_and_update());
+        if let Err(err) = DeviceInterface::subsystems_changed(&ctxt, statuses).await {
+            tracing::error!("Failed to emit subsystem changes: `{err}`");
+        }
+    }
+}
+
+/// Fill in the capabilities of the `tuxedo_io` device.
+pub fn detect_io_capabilities(
+    capabilities: &mut Capabilities,
+    io: &dyn IoBackend,
+    fans: &[Fan],
+    switches: &SwitchControl,
+) {
+    capabilities.platform = io.hw().map(|hw| format!("{hw:?}")).unwrap_or_default();
+    capabilities.driver_version = io.driver_version().unwrap_or_else(|err| {
+        tracing::warn!("Failed to read the driver version: `{err}`");
+        String::new()
+    });
+    capabilities.fans = fans.len() as u8;
+    capabilities.temperature_sensors = io.available_fans().into_iter().map(Fan::as_u8).collect();
+    capabilities.performance_profiles = io
+        .available_performance_profiles()
+        .into_iter()
+        .map(|profile| profile.to_string())
+        .collect();
+    capabilities.switches = switches
+        .available()
+        .into_iter()
+        .map(|switch| switch.to_string())
+        .collect();
+}
+
+pub fn detect_keyboard_capabilities(
+    capabilities: &mut Capabilities,
+    keyboard: &dyn KeyboardBackend,
+    brightness: Option<u8>,
+) {
+    capabilities.keyboard_zones = keyboard
+        .zones()
+        .into_iter()
+        .map(|zone| zone.as_str().to_string())
+        .collect();
+    capabilities.keyboard_effects = keyboard
+        .effects()
+        .into_iter()
+        .map(|effect| effect.name().to_string())
+        .collect();
+    capabilities.keyboard_brightness = brightness.is_some();
+}
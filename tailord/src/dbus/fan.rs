@@ -1,73 +1,191 @@
 use tailor_api::{FanProfilePoint, ProfileInfo};
-use tokio::sync::mpsc;
-use zbus::{dbus_interface, fdo};
+use tokio::sync::{mpsc, watch};
+use tuxedo_ioctl::high_level::Fan;
+use zbus::{dbus_interface, fdo, Connection, SignalContext};
 
 use crate::{
-    fancontrol::profile::FanProfile,
-    profiles::{Profile, FAN_DIR, PROFILE_DIR},
-    util,
+    fancontrol::{profile::FanProfiles, watchdog::ThermalAlarm, SpeedOverride},
+    profiles::{paths, Profile},
+    util, DBUS_PATH,
 };
 
+use super::FanInterfaceV2;
+
+#[derive(Clone)]
 pub struct FanInterface {
-    pub fan_speed_sender: mpsc::Sender<u8>,
-    pub fan_sender: mpsc::Sender<FanProfile>,
+    pub fan_speed_sender: mpsc::Sender<SpeedOverride>,
+    pub fan_sender: mpsc::Sender<FanProfiles>,
+    /// All fans that are controlled by the daemon,
+    /// empty until the `tuxedo_io` device is available.
+    pub fans: watch::Receiver<Vec<Fan>>,
 }
 
 #[dbus_interface(name = "com.tux.Tailor.Fan")]
 impl FanInterface {
-    async fn add_profile(&self, name: &str, value: &str) -> fdo::Result<()> {
+    pub(super) async fn add_profile(
+        &self,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+        name: &str,
+        value: &str,
+    ) -> fdo::Result<()> {
         // Verify correctness of the file.
         serde_json::from_str::<Vec<FanProfilePoint>>(value)
             .map_err(|err| fdo::Error::InvalidArgs(err.to_string()))?;
-        util::write_file(FAN_DIR, name, value.as_bytes()).await?;
+        util::write_file(&paths().fan_dir, name, value.as_bytes()).await?;
 
         // Reload if the fan profile is part of the active global profile
         let info = Profile::get_active_profile_info()?;
-        if info.fan == name {
+        if info.fan_profiles().any(|fan| fan == name) {
             let info = Profile::reload()?;
             self.fan_sender.send(info.fan).await.unwrap();
         }
+
+        emit_signal!(FanInterface, FanInterfaceV2, profile_added, &ctxt, name);
         Ok(())
     }
 
-    async fn get_profile(&self, name: &str) -> fdo::Result<String> {
-        util::read_file(FAN_DIR, name).await
+    pub(super) async fn get_profile(&self, name: &str) -> fdo::Result<String> {
+        util::read_file(&paths().fan_dir, name).await
     }
 
-    async fn list_profiles(&self) -> fdo::Result<Vec<String>> {
-        util::get_profiles(FAN_DIR).await
+    pub(super) async fn list_profiles(&self) -> fdo::Result<Vec<String>> {
+        util::get_profiles(&paths().fan_dir).await
     }
 
-    async fn remove_profile(&self, name: &str) -> fdo::Result<()> {
-        util::remove_file(FAN_DIR, name).await
+    pub(super) async fn remove_profile(
+        &self,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+        name: &str,
+    ) -> fdo::Result<()> {
+        util::remove_file(&paths().fan_dir, name).await?;
+
+        emit_signal!(FanInterface, FanInterfaceV2, profile_removed, &ctxt, name);
+        Ok(())
     }
 
-    async fn rename_profile(&self, from: &str, to: &str) -> fdo::Result<Vec<String>> {
+    pub(super) async fn rename_profile(
+        &self,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+        from: &str,
+        to: &str,
+    ) -> fdo::Result<Vec<String>> {
         if self.list_profiles().await?.contains(&to.to_string()) {
             Err(fdo::Error::InvalidArgs(format!(
                 "File `{to}` already exists"
             )))
         } else {
-            let profiles = util::get_profiles(PROFILE_DIR).await?;
+            let profiles = util::get_profiles(&paths().profile_dir).await?;
 
             for profile in profiles {
-                let mut data = util::read_json::<ProfileInfo>(PROFILE_DIR, &profile).await?;
-                if data.fan == from {
-                    data.fan = to.to_string();
-                    util::write_json(PROFILE_DIR, &profile, &data).await?;
+                let mut data =
+                    util::read_json::<ProfileInfo>(&paths().profile_dir, &profile).await?;
+                if data.fan_profiles().any(|fan| fan == from) {
+                    if data.fan == from {
+                        data.fan = to.to_string();
+                    }
+                    for fan in data.fan_assignments.values_mut() {
+                        if fan == from {
+                            *fan = to.to_string();
+                        }
+                    }
+                    util::write_json(&paths().profile_dir, &profile, &data).await?;
                 }
             }
 
-            util::move_file(FAN_DIR, from, to).await?;
+            util::move_file(&paths().fan_dir, from, to).await?;
+
+            emit_signal!(
+                FanInterface,
+                FanInterfaceV2,
+                profile_renamed,
+                &ctxt,
+                from,
+                to
+            );
 
             self.list_profiles().await
         }
     }
 
-    async fn override_speed(&mut self, speed: u8) -> fdo::Result<()> {
+    pub(super) async fn override_speed(&mut self, speed: u8) -> fdo::Result<()> {
+        self.send_override(SpeedOverride { fan: None, speed }).await
+    }
+
+    pub(super) async fn override_single_speed(&mut self, fan: u8, speed: u8) -> fdo::Result<()> {
+        let fan = Fan::try_from_u8(fan)
+            .filter(|fan| self.fans.borrow().contains(fan))
+            .ok_or_else(|| fdo::Error::InvalidArgs(format!("Fan {fan} isn't available")))?;
+        self.send_override(SpeedOverride {
+            fan: Some(fan),
+            speed,
+        })
+        .await
+    }
+
+    pub(super) async fn get_number_of_fans(&self) -> u8 {
+        self.fans.borrow().len() as u8
+    }
+
+    #[dbus_interface(signal)]
+    pub(super) async fn profile_added(ctxt: &SignalContext<'_>, name: &str) -> zbus::Result<()>;
+
+    #[dbus_interface(signal)]
+    pub(super) async fn profile_removed(ctxt: &SignalContext<'_>, name: &str) -> zbus::Result<()>;
+
+    #[dbus_interface(signal)]
+    pub(super) async fn profile_renamed(
+        ctxt: &SignalContext<'_>,
+        from: &str,
+        to: &str,
+    ) -> zbus::Result<()>;
+
+    /// Emitted when the watchdog of a fan raises (`active`)
+    /// or clears an alarm.
+    #[dbus_interface(signal)]
+    async fn thermal_alarm(
+        ctxt: &SignalContext<'_>,
+        fan: u8,
+        active: bool,
+        reason: &str,
+    ) -> zbus::Result<()>;
+}
+
+impl FanInterface {
+    async fn send_override(&self, speed_override: SpeedOverride) -> fdo::Result<()> {
         self.fan_speed_sender
-            .send(speed)
+            .send(speed_override)
             .await
             .map_err(|err| fdo::Error::Failed(format!("Internal error: `{err}`")))
     }
 }
+
+/// Emit a signal for every alarm of the fan watchdog.
+pub async fn forward_thermal_alarms(
+    connection: Connection,
+    mut alarm_receiver: mpsc::Receiver<ThermalAlarm>,
+) {
+    let ctxt = match SignalContext::new(&connection, DBUS_PATH) {
+        Ok(ctxt) => ctxt,
+        Err(err) => {
+            tracing::error!("Failed to create signal context: `{err}`");
+            return;
+        }
+    };
+
+    while let Some(ThermalAlarm { fan, reason }) = alarm_receiver.recv().await {
+        let fan = fan.as_u8();
+        let reason = reason.map(|reason| reason.to_string());
+        let active = reason.is_some();
+        let reason = reason.as_deref().unwrap_or_default();
+
+        emit_signal!(
+            FanInterface,
+            FanInterfaceV2,
+            thermal_alarm,
+            &ctxt,
+            fan,
+            active,
+            reason
+        );
+    }
+}
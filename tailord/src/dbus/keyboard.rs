@@ -1,12 +1,15 @@
 use tailor_api::{Color, ColorProfile, ProfileInfo};
 use tokio::sync::mpsc;
-use zbus::{dbus_interface, fdo};
+use zbus::{dbus_interface, fdo, SignalContext};
 
 use crate::{
-    profiles::{Profile, KEYBOARD_DIR, PROFILE_DIR},
+    profiles::{paths, Profile},
     util,
 };
 
+use super::KeyboardInterfaceV2;
+
+#[derive(Clone)]
 pub struct KeyboardInterface {
     pub color_sender: mpsc::Sender<Color>,
     pub keyboard_sender: mpsc::Sender<ColorProfile>,
@@ -14,11 +17,16 @@ pub struct KeyboardInterface {
 
 #[dbus_interface(name = "com.tux.Tailor.Keyboard")]
 impl KeyboardInterface {
-    async fn add_profile(&self, name: &str, value: &str) -> fdo::Result<()> {
+    pub(super) async fn add_profile(
+        &self,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+        name: &str,
+        value: &str,
+    ) -> fdo::Result<()> {
         // Verify correctness of the file.
         serde_json::from_str::<ColorProfile>(value)
             .map_err(|err| fdo::Error::InvalidArgs(err.to_string()))?;
-        util::write_file(KEYBOARD_DIR, name, value.as_bytes()).await?;
+        util::write_file(&paths().keyboard_dir, name, value.as_bytes()).await?;
 
         // Reload if the keyboard profile is part of the active global profile
         let info = Profile::get_active_profile_info()?;
@@ -26,44 +34,80 @@ impl KeyboardInterface {
             let info = Profile::reload()?;
             self.keyboard_sender.send(info.keyboard).await.unwrap();
         }
+
+        emit_signal!(
+            KeyboardInterface,
+            KeyboardInterfaceV2,
+            profile_added,
+            &ctxt,
+            name
+        );
         Ok(())
     }
 
-    async fn get_profile(&self, name: &str) -> fdo::Result<String> {
-        util::read_file(KEYBOARD_DIR, name).await
+    pub(super) async fn get_profile(&self, name: &str) -> fdo::Result<String> {
+        util::read_file(&paths().keyboard_dir, name).await
     }
 
-    async fn list_profiles(&self) -> fdo::Result<Vec<String>> {
-        util::get_profiles(KEYBOARD_DIR).await
+    pub(super) async fn list_profiles(&self) -> fdo::Result<Vec<String>> {
+        util::get_profiles(&paths().keyboard_dir).await
     }
 
-    async fn remove_profile(&self, name: &str) -> fdo::Result<()> {
-        util::remove_file(KEYBOARD_DIR, name).await
+    pub(super) async fn remove_profile(
+        &self,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+        name: &str,
+    ) -> fdo::Result<()> {
+        util::remove_file(&paths().keyboard_dir, name).await?;
+
+        emit_signal!(
+            KeyboardInterface,
+            KeyboardInterfaceV2,
+            profile_removed,
+            &ctxt,
+            name
+        );
+        Ok(())
     }
 
-    async fn rename_profile(&self, from: &str, to: &str) -> fdo::Result<Vec<String>> {
+    pub(super) async fn rename_profile(
+        &self,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+        from: &str,
+        to: &str,
+    ) -> fdo::Result<Vec<String>> {
         if self.list_profiles().await?.contains(&to.to_string()) {
             Err(fdo::Error::InvalidArgs(format!(
                 "File `{to}` already exists"
             )))
         } else {
-            let profiles = util::get_profiles(PROFILE_DIR).await?;
+            let profiles = util::get_profiles(&paths().profile_dir).await?;
 
             for profile in profiles {
-                let mut data = util::read_json::<ProfileInfo>(PROFILE_DIR, &profile).await?;
+                let mut data =
+                    util::read_json::<ProfileInfo>(&paths().profile_dir, &profile).await?;
                 if data.keyboard == from {
                     data.keyboard = to.to_string();
-                    util::write_json(PROFILE_DIR, &profile, &data).await?;
+                    util::write_json(&paths().profile_dir, &profile, &data).await?;
                 }
             }
 
-            util::move_file(KEYBOARD_DIR, from, to).await?;
+            util::move_file(&paths().keyboard_dir, from, to).await?;
+
+            emit_signal!(
+                KeyboardInterface,
+                KeyboardInterfaceV2,
+                profile_renamed,
+                &ctxt,
+                from,
+                to
+            );
 
             self.list_profiles().await
         }
     }
 
-    async fn override_color(&mut self, color: &str) -> fdo::Result<()> {
+    pub(super) async fn override_color(&mut self, color: &str) -> fdo::Result<()> {
         let color: Color =
             serde_json::from_str(color).map_err(|err| fdo::Error::InvalidArgs(err.to_string()))?;
         self.color_sender
@@ -71,4 +115,17 @@ impl KeyboardInterface {
             .await
             .map_err(|err| fdo::Error::Failed(format!("Internal error: `{err}`")))
     }
+
+    #[dbus_interface(signal)]
+    pub(super) async fn profile_added(ctxt: &SignalContext<'_>, name: &str) -> zbus::Result<()>;
+
+    #[dbus_interface(signal)]
+    pub(super) async fn profile_removed(ctxt: &SignalContext<'_>, name: &str) -> zbus::Result<()>;
+
+    #[dbus_interface(signal)]
+    pub(super) async fn profile_renamed(
+        ctxt: &SignalContext<'_>,
+        from: &str,
+        to: &str,
+    ) -> zbus::Result<()>;
 }
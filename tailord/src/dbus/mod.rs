@@ -1,7 +1,45 @@
+/// Emit a signal on the JSON based and the typed version of an interface.
+///
+/// Signals are only notifications, so errors are
+/// logged instead of failing the method call.
+macro_rules! emit_signal {
+    ($v1:ty, $v2:ty, $signal:ident, $ctxt:expr $(, $arg:expr)*) => {{
+        let result = async {
+            <$v1>::$signal($ctxt $(, $arg)*).await?;
+            <$v2>::$signal($ctxt $(, $arg)*).await
+        }
+        .await;
+        if let Err(err) = result {
+            tracing::warn!("Failed to emit `{}` signal: `{err}`", stringify!($signal));
+        }
+    }};
+}
+
+mod backlight;
+mod cpu;
+mod device;
 mod fan;
 mod keyboard;
+mod performance;
+mod power;
 mod profiles;
+mod switches;
+mod telemetry;
+mod v2;
 
-pub use fan::FanInterface;
+pub use backlight::{
+    emit_brightness_changes, KeyboardBacklightInterface, UPowerKbdBacklightInterface,
+    UPOWER_KBD_BACKLIGHT_PATH,
+};
+pub use cpu::CpuInterface;
+pub use device::{
+    detect_io_capabilities, detect_keyboard_capabilities, emit_subsystem_changes, DeviceInterface,
+};
+pub use fan::{forward_thermal_alarms, FanInterface};
 pub use keyboard::KeyboardInterface;
+pub use performance::{set_performance_profile, PerformanceInterface};
+pub use power::{switch_profiles_on_power_change, PowerInterface};
 pub use profiles::ProfileInterface;
+pub use switches::SwitchesInterface;
+pub use telemetry::{emit_telemetry_changes, TelemetryInterface};
+pub use v2::{CpuInterfaceV2, FanInterfaceV2, KeyboardInterfaceV2, ProfileInterfaceV2};
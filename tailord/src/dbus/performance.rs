@@ -0,0 +1,51 @@
+use std::sync::Arc;
+
+use tuxedo_ioctl::high_level::PerformanceProfile;
+use zbus::{dbus_interface, fdo};
+
+use crate::{backend::IoBackend, profiles};
+
+pub struct PerformanceInterface {
+    pub io: Arc<dyn IoBackend>,
+}
+
+#[dbus_interface(name = "com.tux.Tailor.Performance")]
+impl PerformanceInterface {
+    /// Set the performance profile until another global profile is activated.
+    async fn set_profile(&self, name: &str) -> fdo::Result<()> {
+        let profile = profiles::parse_performance_profile(name)
+            .map_err(|err| fdo::Error::InvalidArgs(err.to_string()))?;
+        if let Some(profile) = profile {
+            set_performance_profile(self.io.as_ref(), profile)?;
+        }
+        Ok(())
+    }
+
+    /// Returns an empty string if the current performance profile is unknown.
+    async fn get_profile(&self) -> fdo::Result<String> {
+        let profile = self
+            .io
+            .get_performance_profile()
+            .map_err(|err| fdo::Error::Failed(err.to_string()))?;
+        Ok(profile
+            .map(|profile| profile.to_string())
+            .unwrap_or_default())
+    }
+
+    async fn list_profiles(&self) -> Vec<String> {
+        self.io
+            .available_performance_profiles()
+            .into_iter()
+            .map(|profile| profile.to_string())
+            .collect()
+    }
+}
+
+pub fn set_performance_profile(io: &dyn IoBackend, profile: PerformanceProfile) -> fdo::Result<()> {
+    tracing::info!("Setting performance profile `{profile}`");
+    io.set_performance_profile(profile).map_err(|err| {
+        fdo::Error::Failed(format!(
+            "Failed to set performance profile `{profile}`: `{err}`"
+        ))
+    })
+}
@@ -0,0 +1,90 @@
+use tailor_api::PowerRule;
+use tokio::sync::watch;
+use zbus::{dbus_interface, fdo, Connection};
+
+use crate::{power::PowerSource, profiles, DBUS_PATH};
+
+use super::ProfileInterface;
+
+pub struct PowerInterface {
+    pub power_source: watch::Receiver<Option<PowerSource>>,
+}
+
+#[dbus_interface(name = "com.tux.Tailor.Power")]
+impl PowerInterface {
+    /// Set the global profiles that are activated when
+    /// the laptop is plugged in or unplugged.
+    async fn set_rule(&self, rule: PowerRule) -> fdo::Result<()> {
+        profiles::store_power_rule(&rule).await
+    }
+
+    async fn get_rule(&self) -> fdo::Result<PowerRule> {
+        profiles::load_power_rule()
+    }
+
+    /// `false` on AC power or if no charger could be found.
+    #[dbus_interface(property)]
+    async fn on_battery(&self) -> bool {
+        self.power_source
+            .borrow()
+            .map(PowerSource::on_battery)
+            .unwrap_or_default()
+    }
+}
+
+/// Activate the global profile of the power rule whenever
+/// the laptop is plugged in or unplugged.
+pub async fn switch_profiles_on_power_change(
+    connection: Connection,
+    mut receiver: watch::Receiver<Option<PowerSource>>,
+) {
+    let object_server = connection.object_server();
+    let (power_ref, profile_ref) = match tokio::try_join!(
+        object_server.interface::<_, PowerInterface>(DBUS_PATH),
+        object_server.interface::<_, ProfileInterface>(DBUS_PATH),
+    ) {
+        Ok(refs) => refs,
+        Err(err) => {
+            tracing::error!("Failed to access the D-Bus interfaces for power changes: `{err}`");
+            return;
+        }
+    };
+
+    let mut previous = *receiver.borrow_and_update();
+    while receiver.changed().await.is_ok() {
+        let current = *receiver.borrow_and_update();
+
+        let iface = power_ref.get().await;
+        if let Err(err) = iface.on_battery_changed(power_ref.signal_context()).await {
+            tracing::warn!("Failed to emit power changes: `{err}`");
+        }
+        drop(iface);
+
+        let switched = PowerSource::switched_to(previous, current);
+        // Keep the last known source if the charger briefly disappears.
+        previous = current.or(previous);
+        let Some(source) = switched else {
+            continue;
+        };
+        let result = async {
+            let rule = profiles::load_power_rule()?;
+            let Some(name) = rule.profile(source.on_battery()) else {
+                return Ok(());
+            };
+
+            let mut iface = profile_ref.get_mut().await;
+            if iface.get_active_profile_name().await.ok().as_deref() != Some(name) {
+                tracing::info!("Activating profile `{name}` for {source:?} power");
+                let ctxt = profile_ref.signal_context().clone();
+                iface.set_active_profile_name(ctxt.clone(), name).await?;
+                iface.reload(ctxt).await?;
+            }
+            fdo::Result::Ok(())
+        }
+        .await;
+
+        if let Err(err) = result {
+            tracing::error!("Failed to switch the profile for {source:?} power: `{err}`");
+        }
+    }
+}
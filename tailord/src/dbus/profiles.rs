@@ -1,75 +1,189 @@
-use tailor_api::{ColorProfile, ProfileInfo};
+use std::sync::Arc;
+
+use tailor_api::{ColorProfile, CpuProfile, ProfileInfo};
 use tokio::sync::mpsc;
-use zbus::{dbus_interface, fdo};
+use zbus::{dbus_interface, fdo, SignalContext};
 
 use crate::{
-    fancontrol::profile::FanProfile,
-    profiles::{Profile, PROFILE_DIR},
+    backend::IoBackend,
+    fancontrol::profile::FanProfiles,
+    profiles::{self, paths, Profile},
+    switches::SwitchControl,
     util,
 };
 
+use super::{set_performance_profile, switches::emit_switch_changes, ProfileInterfaceV2};
+
+#[derive(Clone)]
 pub struct ProfileInterface {
-    pub fan_sender: mpsc::Sender<FanProfile>,
+    pub fan_sender: mpsc::Sender<FanProfiles>,
     pub keyboard_sender: mpsc::Sender<ColorProfile>,
+    pub brightness_sender: mpsc::Sender<u8>,
+    pub cpu_sender: mpsc::Sender<CpuProfile>,
+    pub io: Arc<dyn IoBackend>,
+    pub switches: SwitchControl,
 }
 
 #[dbus_interface(name = "com.tux.Tailor.Profiles")]
 impl ProfileInterface {
-    async fn add_profile(&self, name: &str, value: &str) -> fdo::Result<()> {
+    pub(super) async fn add_profile(
+        &self,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+        name: &str,
+        value: &str,
+    ) -> fdo::Result<()> {
         // Verify correctness of the file.
-        serde_json::from_str::<ProfileInfo>(value)
+        let info = serde_json::from_str::<ProfileInfo>(value)
+            .map_err(|err| fdo::Error::InvalidArgs(err.to_string()))?;
+        profiles::parse_performance_profile(&info.performance_profile)
             .map_err(|err| fdo::Error::InvalidArgs(err.to_string()))?;
+        profiles::parse_switches(&info.switches)?;
 
-        util::write_file(PROFILE_DIR, name, value.as_bytes()).await
+        util::write_file(&paths().profile_dir, name, value.as_bytes()).await?;
+
+        emit_signal!(
+            ProfileInterface,
+            ProfileInterfaceV2,
+            profile_added,
+            &ctxt,
+            name
+        );
+        Ok(())
     }
 
-    async fn get_profile(&self, name: &str) -> fdo::Result<String> {
-        util::read_file(PROFILE_DIR, name).await
+    pub(super) async fn get_profile(&self, name: &str) -> fdo::Result<String> {
+        util::read_file(&paths().profile_dir, name).await
     }
 
-    async fn list_profiles(&self) -> fdo::Result<Vec<String>> {
-        util::get_profiles(PROFILE_DIR).await
+    pub(super) async fn list_profiles(&self) -> fdo::Result<Vec<String>> {
+        util::get_profiles(&paths().profile_dir).await
     }
 
-    async fn remove_profile(&self, name: &str) -> fdo::Result<()> {
-        util::remove_file(PROFILE_DIR, name).await
+    pub(super) async fn remove_profile(
+        &self,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+        name: &str,
+    ) -> fdo::Result<()> {
+        util::remove_file(&paths().profile_dir, name).await?;
+
+        emit_signal!(
+            ProfileInterface,
+            ProfileInterfaceV2,
+            profile_removed,
+            &ctxt,
+            name
+        );
+        Ok(())
     }
 
-    async fn rename_profile(&mut self, from: &str, to: &str) -> fdo::Result<Vec<String>> {
+    pub(super) async fn rename_profile(
+        &mut self,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+        from: &str,
+        to: &str,
+    ) -> fdo::Result<Vec<String>> {
         if self.list_profiles().await?.contains(&to.to_string()) {
             Err(fdo::Error::InvalidArgs(format!(
                 "File `{to}` already exists"
             )))
         } else {
-            util::move_file(PROFILE_DIR, from, to).await?;
+            util::move_file(&paths().profile_dir, from, to).await?;
+            profiles::rename_in_power_rule(from, to).await?;
+
+            emit_signal!(
+                ProfileInterface,
+                ProfileInterfaceV2,
+                profile_renamed,
+                &ctxt,
+                from,
+                to
+            );
 
             if self.get_active_profile_name().await? == from {
-                self.set_active_profile_name(to).await?;
-                self.reload().await?;
+                self.set_active_profile_name(ctxt.clone(), to).await?;
+                self.reload(ctxt).await?;
             }
 
             self.list_profiles().await
         }
     }
 
-    async fn set_active_profile_name(&self, name: &str) -> fdo::Result<()> {
-        Profile::set_active_profile_name(name).await
+    pub(super) async fn set_active_profile_name(
+        &self,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+        name: &str,
+    ) -> fdo::Result<()> {
+        Profile::set_active_profile_name(name).await?;
+
+        emit_signal!(
+            ProfileInterface,
+            ProfileInterfaceV2,
+            active_profile_changed,
+            &ctxt,
+            name
+        );
+        Ok(())
     }
 
-    async fn get_active_profile_name(&self) -> fdo::Result<String> {
+    pub(super) async fn get_active_profile_name(&self) -> fdo::Result<String> {
         Profile::get_active_profile_name().await
     }
 
-    async fn reload(&mut self) -> fdo::Result<()> {
-        let Profile { fan, keyboard } = Profile::reload()?;
+    pub(super) async fn reload(
+        &mut self,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+    ) -> fdo::Result<()> {
+        let Profile {
+            fan,
+            keyboard,
+            keyboard_brightness,
+            cpu,
+            performance_profile,
+            switches,
+        } = Profile::reload()?;
         let res1 = self
             .keyboard_sender
             .send(keyboard)
             .await
             .map_err(|e| e.to_string());
         let res2 = self.fan_sender.send(fan).await.map_err(|e| e.to_string());
+        let res3 = self.cpu_sender.send(cpu).await.map_err(|e| e.to_string());
         res1.and(res2)
+            .and(res3)
             .map_err(|err| fdo::Error::Failed(format!("Internal error: `{err}`")))?;
+
+        if let Some(brightness) = keyboard_brightness {
+            self.brightness_sender
+                .send(brightness)
+                .await
+                .map_err(|err| fdo::Error::Failed(format!("Internal error: `{err}`")))?;
+        }
+
+        let changed = self.switches.apply(&switches).await;
+        emit_switch_changes(&ctxt, &changed).await;
+
+        if let Some(profile) = performance_profile {
+            set_performance_profile(self.io.as_ref(), profile)?;
+        }
         Ok(())
     }
+
+    #[dbus_interface(signal)]
+    pub(super) async fn profile_added(ctxt: &SignalContext<'_>, name: &str) -> zbus::Result<()>;
+
+    #[dbus_interface(signal)]
+    pub(super) async fn profile_removed(ctxt: &SignalContext<'_>, name: &str) -> zbus::Result<()>;
+
+    #[dbus_interface(signal)]
+    pub(super) async fn profile_renamed(
+        ctxt: &SignalContext<'_>,
+        from: &str,
+        to: &str,
+    ) -> zbus::Result<()>;
+
+    #[dbus_interface(signal)]
+    pub(super) async fn active_profile_changed(
+        ctxt: &SignalContext<'_>,
+        name: &str,
+    ) -> zbus::Result<()>;
 }
@@ -0,0 +1,78 @@
+use std::collections::HashMap;
+
+use tailor_api::Switch;
+use zbus::{dbus_interface, fdo, zvariant::Value, Interface, SignalContext};
+
+use crate::switches::{SwitchControl, SwitchStates};
+
+/// Hardware switches, which are `false` if they aren't available.
+///
+/// All properties emit `PropertiesChanged`, including
+/// changes caused by activating a global profile.
+pub struct SwitchesInterface {
+    pub switches: SwitchControl,
+}
+
+#[dbus_interface(name = "com.tux.Tailor.Switches")]
+impl SwitchesInterface {
+    #[dbus_interface(property)]
+    async fn webcam(&self) -> bool {
+        self.switches.get(Switch::Webcam).unwrap_or_default()
+    }
+
+    #[dbus_interface(property)]
+    async fn set_webcam(&mut self, enabled: bool) -> fdo::Result<()> {
+        self.switches.set(Switch::Webcam, enabled).await.map(drop)
+    }
+
+    #[dbus_interface(property)]
+    async fn touchpad(&self) -> bool {
+        self.switches.get(Switch::Touchpad).unwrap_or_default()
+    }
+
+    #[dbus_interface(property)]
+    async fn set_touchpad(&mut self, enabled: bool) -> fdo::Result<()> {
+        self.switches.set(Switch::Touchpad, enabled).await.map(drop)
+    }
+
+    #[dbus_interface(property)]
+    async fn flight_mode(&self) -> bool {
+        self.switches.get(Switch::FlightMode).unwrap_or_default()
+    }
+
+    #[dbus_interface(property)]
+    async fn set_flight_mode(&mut self, enabled: bool) -> fdo::Result<()> {
+        self.switches
+            .set(Switch::FlightMode, enabled)
+            .await
+            .map(drop)
+    }
+}
+
+fn property_name(switch: Switch) -> &'static str {
+    match switch {
+        Switch::Webcam => "Webcam",
+        Switch::Touchpad => "Touchpad",
+        Switch::FlightMode => "FlightMode",
+    }
+}
+
+/// Announce switches that were changed without the property setters,
+/// for example by activating a global profile.
+pub async fn emit_switch_changes(ctxt: &SignalContext<'_>, changed: &SwitchStates) {
+    if changed.is_empty() {
+        return;
+    }
+
+    let values: Vec<_> = changed
+        .iter()
+        .map(|(switch, enabled)| (property_name(*switch), Value::from(*enabled)))
+        .collect();
+    let properties: HashMap<_, _> = values.iter().map(|(name, value)| (*name, value)).collect();
+
+    if let Err(err) =
+        fdo::Properties::properties_changed(ctxt, SwitchesInterface::name(), &properties, &[]).await
+    {
+        tracing::warn!("Failed to emit switch changes: `{err}`");
+    }
+}
@@ -0,0 +1,88 @@
+use tokio::sync::watch;
+use zbus::{dbus_interface, Connection};
+
+use crate::{fancontrol::telemetry::FanTelemetry, DBUS_PATH};
+
+/// Read-only live values of the fan control.
+///
+/// All values are indexed by fan and emit `PropertiesChanged`.
+pub struct TelemetryInterface {
+    pub telemetry: watch::Receiver<FanTelemetry>,
+}
+
+#[dbus_interface(name = "com.tux.Tailor.Telemetry")]
+impl TelemetryInterface {
+    /// `0` for fans that weren't read yet.
+    #[dbus_interface(property)]
+    async fn temperatures(&self) -> Vec<u8> {
+        let telemetry = self.telemetry.borrow();
+        telemetry
+            .temperatures
+            .iter()
+            .map(|temp| temp.unwrap_or_default())
+            .collect()
+    }
+
+    #[dbus_interface(property)]
+    async fn fan_speeds(&self) -> Vec<u8> {
+        self.telemetry.borrow().fan_speeds.clone()
+    }
+
+    #[dbus_interface(property)]
+    async fn target_fan_speeds(&self) -> Vec<u8> {
+        self.telemetry.borrow().target_fan_speeds.clone()
+    }
+
+    /// `(fan, speed)` pairs of all fans that are currently overridden.
+    #[dbus_interface(property)]
+    async fn active_override(&self) -> Vec<(u8, u8)> {
+        self.telemetry.borrow().active_override.clone()
+    }
+}
+
+/// Emit `PropertiesChanged` whenever the fan runtime publishes new values.
+pub async fn emit_telemetry_changes(
+    connection: Connection,
+    mut receiver: watch::Receiver<FanTelemetry>,
+) {
+    let iface_ref = match connection
+        .object_server()
+        .interface::<_, TelemetryInterface>(DBUS_PATH)
+        .await
+    {
+        Ok(iface_ref) => iface_ref,
+        Err(err) => {
+            tracing::error!("Failed to access the telemetry interface: `{err}`");
+            return;
+        }
+    };
+
+    let mut last = receiver.borrow_and_update().clone();
+    while receiver.changed().await.is_ok() {
+        let current = receiver.borrow_and_update().clone();
+        let iface = iface_ref.get().await;
+        let ctxt = iface_ref.signal_context();
+
+        let result = async {
+            if current.temperatures != last.temperatures {
+                iface.temperatures_changed(ctxt).await?;
+            }
+            if current.fan_speeds != last.fan_speeds {
+                iface.fan_speeds_changed(ctxt).await?;
+            }
+            if current.target_fan_speeds != last.target_fan_speeds {
+                iface.target_fan_speeds_changed(ctxt).await?;
+            }
+            if current.active_override != last.active_override {
+                iface.active_override_changed(ctxt).await?;
+            }
+            zbus::Result::Ok(())
+        }
+        .await;
+
+        if let Err(err) = result {
+            tracing::error!("Failed to emit telemetry changes: `{err}`");
+        }
+        last = current;
+    }
+}
@@ -0,0 +1,280 @@
+//! Typed versions of the JSON based interfaces.
+//!
+//! Profiles are passed with native D-Bus signatures instead of
+//! serialized JSON strings, everything else behaves exactly like
+//! the original interfaces which are kept for compatibility.
+
+use serde::{de::DeserializeOwned, Serialize};
+use tailor_api::{
+    Color, ColorProfile, ColorProfileVariant, CpuProfile, FanProfilePoint, ProfileInfo,
+};
+use zbus::{dbus_interface, fdo, SignalContext};
+
+use super::{CpuInterface, FanInterface, KeyboardInterface, ProfileInterface};
+
+fn to_json<T: Serialize>(value: &T) -> fdo::Result<String> {
+    serde_json::to_string(value).map_err(|err| fdo::Error::InvalidArgs(err.to_string()))
+}
+
+fn from_json<T: DeserializeOwned>(value: &str) -> fdo::Result<T> {
+    serde_json::from_str(value).map_err(|err| fdo::Error::InvalidFileContent(err.to_string()))
+}
+
+pub struct FanInterfaceV2(pub FanInterface);
+
+#[dbus_interface(name = "com.tux.Tailor2.Fan")]
+impl FanInterfaceV2 {
+    async fn add_profile(
+        &self,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+        name: &str,
+        profile: Vec<FanProfilePoint>,
+    ) -> fdo::Result<()> {
+        self.0.add_profile(ctxt, name, &to_json(&profile)?).await
+    }
+
+    async fn get_profile(&self, name: &str) -> fdo::Result<Vec<FanProfilePoint>> {
+        from_json(&self.0.get_profile(name).await?)
+    }
+
+    async fn list_profiles(&self) -> fdo::Result<Vec<String>> {
+        self.0.list_profiles().await
+    }
+
+    async fn remove_profile(
+        &self,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+        name: &str,
+    ) -> fdo::Result<()> {
+        self.0.remove_profile(ctxt, name).await
+    }
+
+    async fn rename_profile(
+        &self,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+        from: &str,
+        to: &str,
+    ) -> fdo::Result<Vec<String>> {
+        self.0.rename_profile(ctxt, from, to).await
+    }
+
+    async fn override_speed(&mut self, speed: u8) -> fdo::Result<()> {
+        self.0.override_speed(speed).await
+    }
+
+    async fn override_single_speed(&mut self, fan: u8, speed: u8) -> fdo::Result<()> {
+        self.0.override_single_speed(fan, speed).await
+    }
+
+    async fn get_number_of_fans(&self) -> u8 {
+        self.0.get_number_of_fans().await
+    }
+
+    /// Emitted when the watchdog of a fan raises (`active`)
+    /// or clears an alarm.
+    #[dbus_interface(signal)]
+    pub(super) async fn thermal_alarm(
+        ctxt: &SignalContext<'_>,
+        fan: u8,
+        active: bool,
+        reason: &str,
+    ) -> zbus::Result<()>;
+
+    #[dbus_interface(signal)]
+    pub(super) async fn profile_added(ctxt: &SignalContext<'_>, name: &str) -> zbus::Result<()>;
+
+    #[dbus_interface(signal)]
+    pub(super) async fn profile_removed(ctxt: &SignalContext<'_>, name: &str) -> zbus::Result<()>;
+
+    #[dbus_interface(signal)]
+    pub(super) async fn profile_renamed(
+        ctxt: &SignalContext<'_>,
+        from: &str,
+        to: &str,
+    ) -> zbus::Result<()>;
+}
+
+pub struct KeyboardInterfaceV2(pub KeyboardInterface);
+
+#[dbus_interface(name = "com.tux.Tailor2.Keyboard")]
+impl KeyboardInterfaceV2 {
+    async fn add_profile(
+        &self,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+        name: &str,
+        profile: ColorProfileVariant,
+    ) -> fdo::Result<()> {
+        let profile = ColorProfile::try_from(profile)
+            .map_err(|err| fdo::Error::InvalidArgs(err.to_string()))?;
+        self.0.add_profile(ctxt, name, &to_json(&profile)?).await
+    }
+
+    async fn get_profile(&self, name: &str) -> fdo::Result<ColorProfileVariant> {
+        let profile: ColorProfile = from_json(&self.0.get_profile(name).await?)?;
+        Ok(ColorProfileVariant::from(&profile))
+    }
+
+    async fn list_profiles(&self) -> fdo::Result<Vec<String>> {
+        self.0.list_profiles().await
+    }
+
+    async fn remove_profile(
+        &self,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+        name: &str,
+    ) -> fdo::Result<()> {
+        self.0.remove_profile(ctxt, name).await
+    }
+
+    async fn rename_profile(
+        &self,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+        from: &str,
+        to: &str,
+    ) -> fdo::Result<Vec<String>> {
+        self.0.rename_profile(ctxt, from, to).await
+    }
+
+    async fn override_color(&mut self, color: Color) -> fdo::Result<()> {
+        self.0.override_color(&to_json(&color)?).await
+    }
+
+    #[dbus_interface(signal)]
+    pub(super) async fn profile_added(ctxt: &SignalContext<'_>, name: &str) -> zbus::Result<()>;
+
+    #[dbus_interface(signal)]
+    pub(super) async fn profile_removed(ctxt: &SignalContext<'_>, name: &str) -> zbus::Result<()>;
+
+    #[dbus_interface(signal)]
+    pub(super) async fn profile_renamed(
+        ctxt: &SignalContext<'_>,
+        from: &str,
+        to: &str,
+    ) -> zbus::Result<()>;
+}
+
+pub struct CpuInterfaceV2(pub CpuInterface);
+
+#[dbus_interface(name = "com.tux.Tailor2.Cpu")]
+impl CpuInterfaceV2 {
+    async fn add_profile(
+        &self,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+        name: &str,
+        profile: CpuProfile,
+    ) -> fdo::Result<()> {
+        self.0.add_profile(ctxt, name, &to_json(&profile)?).await
+    }
+
+    async fn get_profile(&self, name: &str) -> fdo::Result<CpuProfile> {
+        from_json(&self.0.get_profile(name).await?)
+    }
+
+    async fn list_profiles(&self) -> fdo::Result<Vec<String>> {
+        self.0.list_profiles().await
+    }
+
+    async fn remove_profile(
+        &self,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+        name: &str,
+    ) -> fdo::Result<()> {
+        self.0.remove_profile(ctxt, name).await
+    }
+
+    async fn rename_profile(
+        &self,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+        from: &str,
+        to: &str,
+    ) -> fdo::Result<Vec<String>> {
+        self.0.rename_profile(ctxt, from, to).await
+    }
+
+    #[dbus_interface(signal)]
+    pub(super) async fn profile_added(ctxt: &SignalContext<'_>, name: &str) -> zbus::Result<()>;
+
+    #[dbus_interface(signal)]
+    pub(super) async fn profile_removed(ctxt: &SignalContext<'_>, name: &str) -> zbus::Result<()>;
+
+    #[dbus_interface(signal)]
+    pub(super) async fn profile_renamed(
+        ctxt: &SignalContext<'_>,
+        from: &str,
+        to: &str,
+    ) -> zbus::Result<()>;
+}
+
+pub struct ProfileInterfaceV2(pub ProfileInterface);
+
+#[dbus_interface(name = "com.tux.Tailor2.Profiles")]
+impl ProfileInterfaceV2 {
+    async fn add_profile(
+        &self,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+        name: &str,
+        profile: ProfileInfo,
+    ) -> fdo::Result<()> {
+        self.0.add_profile(ctxt, name, &to_json(&profile)?).await
+    }
+
+    async fn get_profile(&self, name: &str) -> fdo::Result<ProfileInfo> {
+        from_json(&self.0.get_profile(name).await?)
+    }
+
+    async fn list_profiles(&self) -> fdo::Result<Vec<String>> {
+        self.0.list_profiles().await
+    }
+
+    async fn remove_profile(
+        &self,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+        name: &str,
+    ) -> fdo::Result<()> {
+        self.0.remove_profile(ctxt, name).await
+    }
+
+    async fn rename_profile(
+        &mut self,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+        from: &str,
+        to: &str,
+    ) -> fdo::Result<Vec<String>> {
+        self.0.rename_profile(ctxt, from, to).await
+    }
+
+    async fn set_active_profile_name(
+        &self,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+        name: &str,
+    ) -> fdo::Result<()> {
+        self.0.set_active_profile_name(ctxt, name).await
+    }
+
+    async fn get_active_profile_name(&self) -> fdo::Result<String> {
+        self.0.get_active_profile_name().await
+    }
+
+    async fn reload(&mut self, #[zbus(signal_context)] ctxt: SignalContext<'_>) -> fdo::Result<()> {
+        self.0.reload(ctxt).await
+    }
+
+    #[dbus_interface(signal)]
+    pub(super) async fn profile_added(ctxt: &SignalContext<'_>, name: &str) -> zbus::Result<()>;
+
+    #[dbus_interface(signal)]
+    pub(super) async fn profile_removed(ctxt: &SignalContext<'_>, name: &str) -> zbus::Result<()>;
+
+    #[dbus_interface(signal)]
+    pub(super) async fn profile_renamed(
+        ctxt: &SignalContext<'_>,
+        from: &str,
+        to: &str,
+    ) -> zbus::Result<()>;
+
+    #[dbus_interface(signal)]
+    pub(super) async fn active_profile_changed(
+        ctxt: &SignalContext<'_>,
+        name: &str,
+    ) -> zbus::Result<()>;
+}
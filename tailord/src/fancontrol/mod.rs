@@ -1,105 +1,421 @@
-use std::time::Duration;
+use std::{cell::Cell, sync::Arc, time::Duration};
 
-use tokio::sync::{broadcast, mpsc};
-use tuxedo_ioctl::high_level::{Fan, IoInterface};
+use tokio::sync::{broadcast, mpsc, watch};
+use tuxedo_ioctl::{high_level::Fan, IoctlError};
 
-use self::{buffer::TemperatureBuffer, profile::FanProfile};
+use crate::backend::IoBackend;
+
+use self::{
+    buffer::TemperatureBuffer,
+    profile::{FanProfile, FanProfiles},
+    telemetry::FanTelemetry,
+    watchdog::{ThermalAlarm, Watchdog, WatchdogEvent},
+};
 
 mod buffer;
 pub mod profile;
 mod runtime;
+pub mod telemetry;
+pub mod watchdog;
+
+/// Override the speed of one or all fans for 1s.
+#[derive(Debug, Clone, Copy)]
+pub struct SpeedOverride {
+    /// The fan to override or `None` for all fans.
+    pub fan: Option<Fan>,
+    pub speed: u8,
+}
 
+/// The state of the control loop of a single fan.
 #[derive(Debug)]
-pub struct FanRuntime {
+struct FanControl {
+    fan: Fan,
+    /// Position of the fan in the telemetry data.
+    index: usize,
     /// Stores the temperature history.
     temp_history: TemperatureBuffer,
     /// Percentage of the current fan speed.
     /// This is used to avoid unnecessary updates.
     fan_speed: u8,
-    /// Device i/o interface.
-    io: IoInterface,
     /// The configuration.
     profile: FanProfile,
     suspend_receiver: broadcast::Receiver<bool>,
+    /// Detects failing sensors and fan speed writes.
+    watchdog: Watchdog,
+    /// Whether this fan keeps the [`FirmwareFallback`] active
+    /// because it couldn't be set to full speed.
+    firmware_fallback: bool,
+    alarm_sender: mpsc::Sender<ThermalAlarm>,
 }
 
-impl FanRuntime {
-    // initialize global instance at startup
-    pub fn new(profile: FanProfile, suspend_receiver: broadcast::Receiver<bool>) -> FanRuntime {
-        let io = IoInterface::new().unwrap();
-        let fan_speed = io.get_fan_speed_percent(Fan::Fan1).unwrap();
-        let temp = io.get_fan_temperature(Fan::Fan1).unwrap();
+impl FanControl {
+    fn new(
+        io: &dyn IoBackend,
+        fan: Fan,
+        index: usize,
+        profile: FanProfile,
+        suspend_receiver: broadcast::Receiver<bool>,
+        alarm_sender: mpsc::Sender<ThermalAlarm>,
+    ) -> Self {
+        let fan_speed = io.get_fan_speed_percent(fan).unwrap_or_else(|err| {
+            tracing::error!("Failed reading the current speed of {fan:?}: `{err}`");
+            0
+        });
+        let temp = io.get_fan_temperature(fan).unwrap_or_else(|err| {
+            tracing::error!("Failed reading the current temperature of {fan:?}: `{err}`");
+            0
+        });
         let temp_history = TemperatureBuffer::new(temp);
 
-        io.set_fans_manual().unwrap();
-
-        FanRuntime {
+        Self {
+            fan,
+            index,
             temp_history,
             fan_speed,
-            io,
             profile,
             suspend_receiver,
+            watchdog: Watchdog::default(),
+            firmware_fallback: false,
+            alarm_sender,
+        }
+    }
+
+    /// Adds entries to history ring buffer.
+    /// Returns `None` if the watchdog rejected the reading.
+    fn update_temp(&mut self, io: &dyn IoBackend, fallback: &FirmwareFallback) -> Option<u8> {
+        let reading = io.get_fan_temperature(self.fan);
+        if let Err(err) = &reading {
+            tracing::error!(
+                "Failed reading the current temperature of {:?}: `{err}`",
+                self.fan
+            );
+        }
+
+        let (temp, event) = self.watchdog.check_temp(&reading);
+        self.handle_watchdog_event(io, fallback, event);
+
+        if let Some(temp) = temp {
+            self.temp_history.update(temp);
+        }
+        temp
+    }
+
+    fn set_speed(&mut self, io: &dyn IoBackend, fallback: &FirmwareFallback, new_speed: u8) {
+        if self.fan_speed != new_speed {
+            let result = io.set_fan_speed_percent(self.fan, new_speed);
+            match &result {
+                Ok(()) => self.fan_speed = new_speed,
+                Err(err) => {
+                    tracing::error!("Failed setting new speed of {:?}: `{err}`", self.fan)
+                }
+            }
+
+            let event = self.watchdog.check_write(&result);
+            self.handle_watchdog_event(io, fallback, event);
+        }
+    }
+
+    fn handle_watchdog_event(
+        &mut self,
+        io: &dyn IoBackend,
+        fallback: &FirmwareFallback,
+        event: Option<WatchdogEvent>,
+    ) {
+        match event {
+            None => return,
+            Some(WatchdogEvent::Raised(reason)) => {
+                tracing::error!("Thermal alarm for {:?}: {reason}", self.fan);
+                self.enforce_safe_state(io, fallback);
+            }
+            Some(WatchdogEvent::Cleared) => {
+                tracing::info!("Thermal alarm for {:?} cleared", self.fan);
+                self.leave_firmware_fallback(io, fallback);
+            }
+        }
+
+        let alarm = ThermalAlarm {
+            fan: self.fan,
+            reason: self.watchdog.alarm(),
+        };
+        if let Err(err) = self.alarm_sender.try_send(alarm) {
+            tracing::warn!("Failed to send thermal alarm: `{err}`");
+        }
+    }
+
+    /// The firmware takes over the fans during suspend, so take them
+    /// back and start from the speed the firmware left behind.
+    fn resume(&mut self, io: &dyn IoBackend, fallback: &FirmwareFallback) {
+        if !fallback.is_active() {
+            if let Err(err) = io.set_fans_manual() {
+                tracing::error!("Failed to enable manual fan control after resume: `{err}`");
+            }
+        }
+        self.read_fan_speed(io);
+    }
+
+    /// Take over the current speed, e.g. after the firmware changed it.
+    fn read_fan_speed(&mut self, io: &dyn IoBackend) {
+        match io.get_fan_speed_percent(self.fan) {
+            Ok(speed) => self.fan_speed = speed,
+            Err(err) => {
+                tracing::error!(
+                    "Failed reading the current speed of {:?}: `{err}`",
+                    self.fan
+                );
+            }
+        }
+    }
+
+    /// Run the fan at full speed or, if that fails,
+    /// hand the fan control back to the firmware.
+    ///
+    /// The write is retried during the fallback, so the
+    /// watchdog can notice once writes work again.
+    fn enforce_safe_state(&mut self, io: &dyn IoBackend, fallback: &FirmwareFallback) {
+        let result = io.set_fan_speed_percent(self.fan, 100);
+        match &result {
+            Ok(()) => {
+                self.fan_speed = 100;
+                self.leave_firmware_fallback(io, fallback);
+            }
+            Err(err) if !self.firmware_fallback => {
+                tracing::error!(
+                    "Failed to run {:?} at full speed: `{err}`, handing control back to the firmware",
+                    self.fan
+                );
+                self.firmware_fallback = fallback.enter(io);
+            }
+            Err(_) => {}
+        }
+
+        // The alarm is already raised, so this can only clear it.
+        let event = self.watchdog.check_write(&result);
+        self.handle_watchdog_event(io, fallback, event);
+    }
+
+    fn leave_firmware_fallback(&mut self, io: &dyn IoBackend, fallback: &FirmwareFallback) {
+        if self.firmware_fallback {
+            self.firmware_fallback = false;
+            fallback.leave(io);
+        }
+    }
+}
+
+/// Counts the fans that handed the control back to the firmware.
+///
+/// Switching between manual and automatic mode affects all fans at once,
+/// so the firmware stays in control until the last fan has recovered.
+#[derive(Debug, Default)]
+struct FirmwareFallback {
+    fans: Cell<usize>,
+}
+
+impl FirmwareFallback {
+    fn is_active(&self) -> bool {
+        self.fans.get() > 0
+    }
+
+    /// Returns `false` if the firmware couldn't take over.
+    fn enter(&self, io: &dyn IoBackend) -> bool {
+        if !self.is_active() {
+            if let Err(err) = io.set_fans_auto() {
+                tracing::error!("Failed to enable automatic fan control: `{err}`");
+                return false;
+            }
+        }
+        self.fans.set(self.fans.get() + 1);
+        true
+    }
+
+    fn leave(&self, io: &dyn IoBackend) {
+        self.fans.set(self.fans.get() - 1);
+        if !self.is_active() {
+            if let Err(err) = io.set_fans_manual() {
+                tracing::error!("Failed to enable manual fan control: `{err}`");
+            }
+        }
+    }
+}
+
+pub struct FanRuntime {
+    /// Device i/o interface.
+    io: Arc<dyn IoBackend>,
+    /// One control loop for each available fan.
+    fans: Vec<FanControl>,
+    /// Shared by all control loops.
+    fallback: FirmwareFallback,
+    /// Publishes the live values of all control loops.
+    telemetry: watch::Sender<FanTelemetry>,
+}
+
+impl FanRuntime {
+    // initialize global instance at startup
+    pub fn new(
+        io: Arc<dyn IoBackend>,
+        fans: &[Fan],
+        profiles: FanProfiles,
+        suspend_receiver: broadcast::Receiver<bool>,
+        alarm_sender: mpsc::Sender<ThermalAlarm>,
+        telemetry: watch::Sender<FanTelemetry>,
+    ) -> FanRuntime {
+        telemetry.send_replace(FanTelemetry::new(fans.len()));
+
+        let fans = fans
+            .iter()
+            .enumerate()
+            .map(|(index, &fan)| {
+                FanControl::new(
+                    io.as_ref(),
+                    fan,
+                    index,
+                    profiles.get(fan).clone(),
+                    suspend_receiver.resubscribe(),
+                    alarm_sender.clone(),
+                )
+            })
+            .collect();
+
+        if let Err(err) = io.set_fans_manual() {
+            tracing::error!("Failed to enable manual fan control: `{err}`");
+        }
+
+        FanRuntime {
+            io,
+            fans,
+            fallback: FirmwareFallback::default(),
+            telemetry,
         }
     }
 
     pub async fn run(
         mut self,
-        mut fan_receiver: mpsc::Receiver<FanProfile>,
-        mut fan_speed_receiver: mpsc::Receiver<u8>,
+        mut fan_receiver: mpsc::Receiver<FanProfiles>,
+        mut fan_speed_receiver: mpsc::Receiver<SpeedOverride>,
     ) {
         loop {
             tokio::select! {
                 new_config = fan_receiver.recv() => {
-                    if let Some(config) = new_config {
-                        self.profile = config;
+                    if let Some(profiles) = new_config {
+                        for control in &mut self.fans {
+                            control.profile = profiles.get(control.fan).clone();
+                        }
                     }
                 },
                 // Override the fan speed for 1s
                 override_speed = fan_speed_receiver.recv() => {
-                    if let Some(mut speed) = override_speed {
+                    if let Some(mut speed_override) = override_speed {
                         loop {
-                            // TODO(uniwill): run the check for all fans, as we have 2 on the
-                            // Pulse.
-                            if let Err(err) = self.io.set_fan_speed_percent(Fan::Fan1, speed) {
+                            if let Err(err) = self.override_speed(speed_override) {
                                 tracing::error!("Failed to update fan speed: `{}`", err.to_string());
                                 break;
                             }
                             tokio::select! {
                                 override_speed = fan_speed_receiver.recv() => {
-                                    if let Some(new_speed) = override_speed {
-                                        speed = new_speed
+                                    if let Some(new_override) = override_speed {
+                                        speed_override = new_override
                                     }
                                 }
                                 _ = tokio::time::sleep(Duration::from_millis(1000)) => break,
                             }
                         }
+                        self.telemetry.send_if_modified(|telemetry| {
+                            !std::mem::take(&mut telemetry.active_override).is_empty()
+                        });
                     }
                 }
-                _ = self.fan_control_loop() => {},
+                _ = self.fan_control_loops() => {},
             }
         }
     }
 
-    /// Adds entries to history ring buffer.
-    fn update_temp(&mut self) -> u8 {
-        match self.io.get_fan_temperature(Fan::Fan1) {
-            Ok(temp) => {
-                self.temp_history.update(temp);
-                temp
-            }
-            Err(err) => {
-                tracing::error!("Failed reading the current temperature: `{err}`");
-                self.temp_history.get_latest()
+    fn override_speed(&mut self, speed_override: SpeedOverride) -> Result<(), IoctlError> {
+        let SpeedOverride { fan, speed } = speed_override;
+        let mut active_override = Vec::new();
+        for control in &mut self.fans {
+            if fan.is_none() || fan == Some(control.fan) {
+                self.io.set_fan_speed_percent(control.fan, speed)?;
+                control.fan_speed = speed;
+                active_override.push((control.fan.as_u8(), speed));
             }
         }
-    }
 
-    fn set_speed(&mut self, new_speed: u8) {
-        if self.fan_speed != new_speed {
-            self.fan_speed = new_speed;
-            if let Err(err) = self.io.set_fan_speed_percent(Fan::Fan1, new_speed) {
-                tracing::error!("Failed setting new fan speed: `{err}`");
+        self.telemetry.send_modify(|telemetry| {
+            for control in &self.fans {
+                telemetry.fan_speeds[control.index] = control.fan_speed;
             }
-        }
+            telemetry.active_override = active_override;
+        });
+        Ok(())
+    }
+
+    /// Run the control loops of all fans concurrently.
+    async fn fan_control_loops(&mut self) {
+        let Self {
+            io,
+            fans,
+            fallback,
+            telemetry,
+        } = self;
+        futures::future::join_all(
+            fans.iter_mut()
+                .map(|control| control.fan_control_loop(io.as_ref(), fallback, telemetry)),
+        )
+        .await;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tokio::sync::{broadcast, mpsc};
+    use tuxedo_ioctl::high_level::Fan;
+
+    use super::{profile::FanProfile, FanControl, FirmwareFallback};
+    use crate::backend::{IoBackend, SimulatedIo};
+
+    #[test]
+    fn resume() {
+        let io = SimulatedIo::default();
+        io.set_fans_manual().unwrap();
+        let (_suspend_sender, suspend_receiver) = broadcast::channel(1);
+        let (alarm_sender, _alarm_receiver) = mpsc::channel(1);
+        let mut control = FanControl::new(
+            &io,
+            Fan::Fan1,
+            0,
+            FanProfile::default(),
+            suspend_receiver,
+            alarm_sender,
+        );
+        let fallback = FirmwareFallback::default();
+        control.set_speed(&io, &fallback, 30);
+
+        // The firmware takes over the fans during suspend.
+        io.set_fans_auto().unwrap();
+        let firmware_speed = io.get_fan_speed_percent(Fan::Fan1).unwrap();
+        assert_ne!(firmware_speed, 30);
+
+        control.resume(&io, &fallback);
+        assert!(!io.fans_auto());
+        assert_eq!(control.fan_speed, firmware_speed);
+    }
+
+    #[test]
+    fn shared_firmware_fallback() {
+        let io = SimulatedIo::default();
+        io.set_fans_manual().unwrap();
+        let fallback = FirmwareFallback::default();
+
+        assert!(fallback.enter(&io));
+        assert!(fallback.enter(&io));
+        assert!(io.fans_auto());
+
+        // The firmware stays in control while another fan needs it.
+        fallback.leave(&io);
+        assert!(fallback.is_active());
+        assert!(io.fans_auto());
+
+        fallback.leave(&io);
+        assert!(!fallback.is_active());
+        assert!(!io.fans_auto());
     }
 }
@@ -1,9 +1,25 @@
-use std::path::Path;
+use std::{collections::BTreeMap, path::Path};
 
 use tailor_api::FanProfilePoint;
+use tuxedo_ioctl::high_level::Fan;
 use zbus::fdo;
 
-#[derive(Debug)]
+/// The fan profiles of all fans.
+#[derive(Debug, Default)]
+pub struct FanProfiles {
+    /// Used by all fans without a separate profile.
+    pub default: FanProfile,
+    /// Separate profiles for individual fans, indexed from 0.
+    pub fans: BTreeMap<u8, FanProfile>,
+}
+
+impl FanProfiles {
+    pub fn get(&self, fan: Fan) -> &FanProfile {
+        self.fans.get(&fan.as_u8()).unwrap_or(&self.default)
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct FanProfile {
     inner: Vec<FanProfilePoint>,
 }
@@ -21,26 +37,11 @@ impl FanProfile {
         }
 
         // Make sure the temperature is increasing with each point.
-        let is_sorted = inner
-            .iter()
-            .fold(Some(-1), |prev_value, new_value| {
-                let new_value = i16::from(new_value.temp);
-                if let Some(prev_value) = prev_value {
-                    // If the previous value is smaller than the next, return `None` -> not sorted
-                    if prev_value < new_value {
-                        Some(new_value)
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                }
-            })
-            .is_some();
+        let is_sorted = inner.windows(2).all(|pair| pair[0].temp < pair[1].temp);
 
         if !is_sorted {
             tracing::warn!("Temperature in temperature profile isn't increasing: `{file_name:?}`");
-            inner.sort_by(|first, second| first.temp.cmp(&second.temp));
+            inner.sort_by_key(|point| point.temp);
         }
 
         // Make sure that the fan speed is increasing along with the temperature.
@@ -1,17 +1,52 @@
-use crate::suspend::process_suspend;
+use crate::{backend::IoBackend, suspend::process_suspend};
 
-use super::{buffer::TemperatureBuffer, FanRuntime};
+use super::{buffer::TemperatureBuffer, telemetry::FanTelemetry, FanControl, FirmwareFallback};
 
 use std::time::Duration;
 
-impl FanRuntime {
-    pub async fn fan_control_loop(&mut self) {
+use tokio::sync::watch;
+
+/// Delay of the control loop while it doesn't follow the fan curve,
+/// e.g. during a thermal alarm.
+const ALARM_DELAY: Duration = Duration::from_secs(1);
+
+impl FanControl {
+    pub(super) async fn fan_control_loop(
+        &mut self,
+        io: &dyn IoBackend,
+        fallback: &FirmwareFallback,
+        telemetry: &watch::Sender<FanTelemetry>,
+    ) {
         loop {
             // Add the current temperature to history
-            let current_temp = self.update_temp();
+            let current_temp = self
+                .update_temp(io, fallback)
+                .unwrap_or_else(|| self.temp_history.get_latest());
+
+            // Ignore the fan curve until the watchdog trusts the sensor again.
+            if self.watchdog.alarm().is_some() {
+                self.enforce_safe_state(io, fallback);
+                self.publish(telemetry, current_temp, self.fan_speed);
+                tokio::select! {
+                    _ = tokio::time::sleep(ALARM_DELAY) => {},
+                    _ = process_suspend(&mut self.suspend_receiver) => self.resume(io, fallback),
+                }
+                continue;
+            }
+
+            // Don't interfere while the firmware is in control because of another fan.
+            if fallback.is_active() {
+                self.read_fan_speed(io);
+                self.publish(telemetry, current_temp, self.fan_speed);
+                tokio::select! {
+                    _ = tokio::time::sleep(ALARM_DELAY) => {},
+                    _ = process_suspend(&mut self.suspend_receiver) => self.resume(io, fallback),
+                }
+                continue;
+            }
 
             let target_fan_speed = self.profile.calc_target_fan_speed(current_temp);
-            tracing::debug!("Current temperature is {current_temp}°C at {}% fan speed and {target_fan_speed}% target fan speed", self.fan_speed);
+            tracing::debug!("Current temperature of {:?} is {current_temp}°C at {}% fan speed and {target_fan_speed}% target fan speed", self.fan, self.fan_speed);
 
             let fan_diff = self.fan_speed.abs_diff(target_fan_speed);
 
@@ -22,20 +57,32 @@ impl FanRuntime {
             let fan_increment = fan_diff / 4 + (target_fan_speed / 50);
 
             // Update fan speed
-            self.set_speed(if target_fan_speed > self.fan_speed {
-                self.fan_speed.saturating_add(fan_increment).min(100)
-            } else {
-                self.fan_speed.saturating_sub(fan_increment)
-            });
+            self.set_speed(
+                io,
+                fallback,
+                if target_fan_speed > self.fan_speed {
+                    self.fan_speed.saturating_add(fan_increment).min(100)
+                } else {
+                    self.fan_speed.saturating_sub(fan_increment)
+                },
+            );
+
+            self.publish(telemetry, current_temp, target_fan_speed);
 
             let delay = suitable_delay(&self.temp_history, fan_diff);
 
             tokio::select! {
                 _ = tokio::time::sleep(delay) => {},
-                _ = process_suspend(&mut self.suspend_receiver) => {}
+                _ = process_suspend(&mut self.suspend_receiver) => self.resume(io, fallback),
             }
         }
     }
+
+    fn publish(&self, telemetry: &watch::Sender<FanTelemetry>, temp: u8, target_fan_speed: u8) {
+        telemetry.send_if_modified(|telemetry| {
+            telemetry.update_fan(self.index, temp, self.fan_speed, target_fan_speed)
+        });
+    }
 }
 
 /// Calculate a suitable delay to reduce CPU usage.
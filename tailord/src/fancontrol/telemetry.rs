@@ -0,0 +1,74 @@
+/// Live values of the fan control loops.
+///
+/// All vectors are indexed by the position of the
+/// fan in the list of fans controlled by the daemon.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct FanTelemetry {
+    /// The current temperature of each fan in °C,
+    /// `None` until the fan was read for the first time.
+    pub temperatures: Vec<Option<u8>>,
+    /// The current speed of each fan in percent.
+    pub fan_speeds: Vec<u8>,
+    /// The speed each fan is approaching according to its fan curve.
+    pub target_fan_speeds: Vec<u8>,
+    /// Fans whose speed is currently overridden as `(fan, speed)` pairs.
+    pub active_override: Vec<(u8, u8)>,
+}
+
+impl FanTelemetry {
+    pub(super) fn new(fans: usize) -> Self {
+        Self {
+            temperatures: vec![None; fans],
+            fan_speeds: vec![0; fans],
+            target_fan_speeds: vec![0; fans],
+            active_override: Vec::new(),
+        }
+    }
+
+    /// Temperature of the CPU in °C, which is measured by the first fan.
+    ///
+    /// `None` until the first fan was read.
+    pub fn cpu_temperature(&self) -> Option<u8> {
+        self.temperatures.first().copied().flatten()
+    }
+
+    /// Returns `true` if any value changed.
+    pub(super) fn update_fan(
+        &mut self,
+        index: usize,
+        temperature: u8,
+        fan_speed: u8,
+        target_fan_speed: u8,
+    ) -> bool {
+        let temperature = Some(temperature);
+        let modified = self.temperatures[index] != temperature
+            || self.fan_speeds[index] != fan_speed
+            || self.target_fan_speeds[index] != target_fan_speed;
+
+        self.temperatures[index] = temperature;
+        self.fan_speeds[index] = fan_speed;
+        self.target_fan_speeds[index] = target_fan_speed;
+        modified
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::FanTelemetry;
+
+    #[test]
+    fn cpu_temperature() {
+        assert_eq!(FanTelemetry::default().cpu_temperature(), None);
+
+        // No temperature is known before the first reading.
+        let mut telemetry = FanTelemetry::new(2);
+        assert_eq!(telemetry.cpu_temperature(), None);
+        assert!(telemetry.update_fan(1, 50, 30, 40));
+        assert_eq!(telemetry.cpu_temperature(), None);
+
+        assert!(telemetry.update_fan(0, 60, 30, 40));
+        assert_eq!(telemetry.cpu_temperature(), Some(60));
+        assert!(!telemetry.update_fan(0, 60, 30, 40));
+        assert!(telemetry.update_fan(0, 60, 35, 40));
+    }
+}
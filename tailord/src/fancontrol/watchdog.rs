@@ -0,0 +1,265 @@
+use std::fmt;
+
+use tuxedo_ioctl::{high_level::Fan, IoctlError};
+
+/// Consecutive failed reads or writes until the alarm is raised.
+const MAX_FAILURES: u32 = 5;
+
+/// Consecutive identical readings until a sensor is considered stuck.
+/// The control loop reads the temperature every 0.2 to 2s, so this
+/// corresponds to at least 10 minutes without any change. Idle systems
+/// can have very stable temperatures, so this needs to be conservative.
+const MAX_IDENTICAL_READINGS: u32 = 3000;
+
+/// Consecutive plausible readings until the alarm is cleared again.
+const RECOVERY_READINGS: u32 = 5;
+
+/// Temperatures (in °C) outside of this range can't be real readings.
+const PLAUSIBLE_TEMPS: std::ops::RangeInclusive<u8> = 1..=110;
+
+/// The reason why the watchdog raised an alarm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlarmReason {
+    /// Reading the temperature failed repeatedly.
+    SensorFailure,
+    /// The sensor repeatedly reported an impossible temperature.
+    ImplausibleReading(u8),
+    /// The sensor reported the same temperature for a very long time.
+    StuckReading(u8),
+    /// Setting the fan speed failed repeatedly.
+    WriteFailure,
+}
+
+impl fmt::Display for AlarmReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SensorFailure => write!(f, "Reading the temperature failed"),
+            Self::ImplausibleReading(temp) => write!(f, "Implausible temperature of {temp}°C"),
+            Self::StuckReading(temp) => write!(f, "Temperature is stuck at {temp}°C"),
+            Self::WriteFailure => write!(f, "Setting the fan speed failed"),
+        }
+    }
+}
+
+/// Sent by the fan runtime whenever the alarm state of a fan changes.
+#[derive(Debug, Clone, Copy)]
+pub struct ThermalAlarm {
+    pub fan: Fan,
+    /// The reason of the alarm or `None` if the alarm was cleared.
+    pub reason: Option<AlarmReason>,
+}
+
+/// Changes of the alarm state reported by the [`Watchdog`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum WatchdogEvent {
+    Raised(AlarmReason),
+    Cleared,
+}
+
+/// Detects failing or unreliable temperature sensors
+/// and fan speed writes of a single fan.
+#[derive(Debug, Default)]
+pub(super) struct Watchdog {
+    read_failures: u32,
+    implausible_readings: u32,
+    write_failures: u32,
+    last_temp: Option<u8>,
+    identical_readings: u32,
+    good_readings: u32,
+    alarm: Option<AlarmReason>,
+}
+
+impl Watchdog {
+    pub(super) fn alarm(&self) -> Option<AlarmReason> {
+        self.alarm
+    }
+
+    /// Checks a new temperature reading and returns the
+    /// temperature if it can be used by the control loop.
+    pub(super) fn check_temp(
+        &mut self,
+        reading: &Result<u8, IoctlError>,
+    ) -> (Option<u8>, Option<WatchdogEvent>) {
+        match *reading {
+            Err(_) => {
+                self.read_failures += 1;
+                self.good_readings = 0;
+                let event = self.raise_if(self.read_failures >= MAX_FAILURES, || {
+                    AlarmReason::SensorFailure
+                });
+                (None, event)
+            }
+            Ok(temp) if !PLAUSIBLE_TEMPS.contains(&temp) => {
+                self.read_failures = 0;
+                self.implausible_readings += 1;
+                self.good_readings = 0;
+                let event = self.raise_if(self.implausible_readings >= MAX_FAILURES, || {
+                    AlarmReason::ImplausibleReading(temp)
+                });
+                (None, event)
+            }
+            Ok(temp) => {
+                self.read_failures = 0;
+                self.implausible_readings = 0;
+
+                if self.last_temp == Some(temp) {
+                    self.identical_readings += 1;
+                } else {
+                    self.identical_readings = 0;
+                }
+                self.last_temp = Some(temp);
+
+                if self.identical_readings >= MAX_IDENTICAL_READINGS {
+                    self.good_readings = 0;
+                    let event = self.raise_if(true, || AlarmReason::StuckReading(temp));
+                    (None, event)
+                } else {
+                    self.good_readings += 1;
+                    (Some(temp), self.clear_if_recovered())
+                }
+            }
+        }
+    }
+
+    /// Checks the result of setting a new fan speed.
+    pub(super) fn check_write(&mut self, result: &Result<(), IoctlError>) -> Option<WatchdogEvent> {
+        if result.is_ok() {
+            self.write_failures = 0;
+            self.clear_if_recovered()
+        } else {
+            self.write_failures += 1;
+            self.good_readings = 0;
+            self.raise_if(self.write_failures >= MAX_FAILURES, || {
+                AlarmReason::WriteFailure
+            })
+        }
+    }
+
+    fn raise_if(
+        &mut self,
+        condition: bool,
+        reason: impl FnOnce() -> AlarmReason,
+    ) -> Option<WatchdogEvent> {
+        if condition && self.alarm.is_none() {
+            let reason = reason();
+            self.alarm = Some(reason);
+            Some(WatchdogEvent::Raised(reason))
+        } else {
+            None
+        }
+    }
+
+    fn clear_if_recovered(&mut self) -> Option<WatchdogEvent> {
+        if self.alarm.is_some()
+            && self.write_failures == 0
+            && self.good_readings >= RECOVERY_READINGS
+        {
+            self.alarm = None;
+            Some(WatchdogEvent::Cleared)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tuxedo_ioctl::IoctlError;
+
+    use super::{AlarmReason, Watchdog, WatchdogEvent, MAX_FAILURES, RECOVERY_READINGS};
+
+    #[test]
+    fn sensor_failures() {
+        let mut watchdog = Watchdog::default();
+
+        for _ in 1..MAX_FAILURES {
+            assert_eq!(
+                watchdog.check_temp(&Err(IoctlError::SensorNotAvailable)),
+                (None, None)
+            );
+        }
+        assert_eq!(
+            watchdog.check_temp(&Err(IoctlError::SensorNotAvailable)),
+            (
+                None,
+                Some(WatchdogEvent::Raised(AlarmReason::SensorFailure))
+            )
+        );
+        // Only raised once.
+        assert_eq!(
+            watchdog.check_temp(&Err(IoctlError::SensorNotAvailable)),
+            (None, None)
+        );
+
+        for _ in 1..RECOVERY_READINGS {
+            assert_eq!(watchdog.check_temp(&Ok(50)), (Some(50), None));
+        }
+        assert_eq!(
+            watchdog.check_temp(&Ok(51)),
+            (Some(51), Some(WatchdogEvent::Cleared))
+        );
+        assert_eq!(watchdog.alarm(), None);
+    }
+
+    #[test]
+    fn implausible_and_stuck_readings() {
+        let mut watchdog = Watchdog::default();
+
+        for _ in 0..MAX_FAILURES {
+            assert_eq!(watchdog.check_temp(&Ok(0)).0, None);
+        }
+        assert_eq!(watchdog.alarm(), Some(AlarmReason::ImplausibleReading(0)));
+
+        let mut watchdog = Watchdog::default();
+        while watchdog.check_temp(&Ok(42)).0.is_some() {}
+        assert_eq!(watchdog.alarm(), Some(AlarmReason::StuckReading(42)));
+    }
+
+    #[test]
+    fn write_failures() {
+        let mut watchdog = Watchdog::default();
+
+        for _ in 1..MAX_FAILURES {
+            assert_eq!(
+                watchdog.check_write(&Err(IoctlError::SensorNotAvailable)),
+                None
+            );
+            // Occasional successful reads don't reset write failures.
+            watchdog.check_temp(&Ok(50));
+        }
+        assert_eq!(
+            watchdog.check_write(&Err(IoctlError::SensorNotAvailable)),
+            Some(WatchdogEvent::Raised(AlarmReason::WriteFailure))
+        );
+    }
+
+    #[test]
+    fn write_recovery() {
+        let mut watchdog = Watchdog::default();
+
+        for _ in 0..MAX_FAILURES {
+            watchdog.check_write(&Err(IoctlError::SensorNotAvailable));
+        }
+        assert_eq!(watchdog.alarm(), Some(AlarmReason::WriteFailure));
+
+        // Good readings alone don't clear the alarm while writes fail.
+        for _ in 0..RECOVERY_READINGS {
+            assert_eq!(watchdog.check_temp(&Ok(50)), (Some(50), None));
+        }
+        assert_eq!(
+            watchdog.check_write(&Err(IoctlError::SensorNotAvailable)),
+            None
+        );
+
+        // Writes work again.
+        assert_eq!(watchdog.check_write(&Ok(())), None);
+        for _ in 1..RECOVERY_READINGS {
+            assert_eq!(watchdog.check_temp(&Ok(50)), (Some(50), None));
+        }
+        assert_eq!(
+            watchdog.check_temp(&Ok(50)),
+            (Some(50), Some(WatchdogEvent::Cleared))
+        );
+        assert_eq!(watchdog.alarm(), None);
+    }
+}
@@ -0,0 +1,267 @@
+//! Looping color animations that compute each frame from the elapsed time.
+
+use tailor_api::{Color, ColorPoint, ColorTransition};
+
+use super::transition;
+
+/// Shortest time between two frames of a transition, 80 ms (12.5 fps).
+/// More would be rather CPU intensive for a background job.
+const MIN_FRAME_TIME: u32 = 80;
+
+/// The transition to a color point from the previous one.
+struct Segment {
+    from: Color,
+    point: ColorPoint,
+    /// Start of the transition in ms since the start of the animation.
+    start: u64,
+    /// Number of frames of the transition.
+    /// With `0`, the target color is shown right away.
+    frames: u64,
+}
+
+impl Segment {
+    fn new(from: Color, point: ColorPoint, start: u64) -> Self {
+        let duration = point.transition_time;
+        let frames = match point.transition {
+            ColorTransition::None => 0,
+            ColorTransition::Steps(steps) if duration < steps => 0,
+            ColorTransition::Steps(steps) => steps,
+            _ => {
+                let frames = duration / MIN_FRAME_TIME;
+                if frames == 0 {
+                    0
+                } else {
+                    let path_length =
+                        transition::path_length(&from, &point.color, point.color_space);
+
+                    // Use a lower frame rate if the animation is slow.
+                    // The human eye won't notice the lower fps but
+                    // the CPU usage will drop significantly.
+                    frames.min(decent_linear_steps(duration, &[path_length]))
+                }
+            }
+        };
+
+        Self {
+            from,
+            point,
+            start,
+            frames: frames as u64,
+        }
+    }
+
+    fn duration(&self) -> u64 {
+        self.point.transition_time as u64
+    }
+
+    /// Returns the color `elapsed` ms into the transition
+    /// and how many ms are left until the next frame.
+    fn frame_at(&self, elapsed: u64) -> (Color, u64) {
+        let duration = self.duration();
+        if self.frames == 0 {
+            return (self.point.color.clone(), duration - elapsed);
+        }
+
+        let frame = elapsed * self.frames / duration;
+        let progress = frame as f64 / self.frames as f64;
+        let amount = match self.point.transition {
+            // Every frame is a step already.
+            ColorTransition::Steps(_) => progress,
+            transition => transition::ease(transition, progress),
+        };
+        let color = transition::blend(
+            &self.from,
+            &self.point.color,
+            amount,
+            self.point.color_space,
+        );
+
+        let next_frame = ((frame + 1) * duration).div_ceil(self.frames);
+        (color, next_frame - elapsed)
+    }
+}
+
+/// A color animation that repeats every [`Self::period`] ms.
+///
+/// Colors are computed from the time instead of stepping through
+/// a precomputed list, so long transitions don't take more memory
+/// and callers can skip frames whenever they fall behind.
+pub struct ColorAnimation {
+    segments: Vec<Segment>,
+    period: u64,
+}
+
+impl ColorAnimation {
+    /// Returns `None` if there are no colors.
+    pub fn new(points: &[ColorPoint]) -> Option<Self> {
+        let mut from = points.last()?.color.clone();
+        let mut start = 0;
+        let segments = points
+            .iter()
+            .map(|point| {
+                let segment = Segment::new(from.clone(), point.clone(), start);
+                start += segment.duration();
+                from = point.color.clone();
+                segment
+            })
+            .collect();
+
+        Some(Self {
+            segments,
+            period: start,
+        })
+    }
+
+    pub fn period(&self) -> u64 {
+        self.period
+    }
+
+    /// Returns the color at `time` and how many ms are left until
+    /// the next frame, which is [`u64::MAX`] if the color never changes.
+    pub fn frame_at(&self, time: u64) -> (Color, u64) {
+        if self.period == 0 || self.segments.len() == 1 {
+            return (self.segments[0].point.color.clone(), u64::MAX);
+        }
+
+        let position = time % self.period;
+        let segment = self
+            .segments
+            .iter()
+            .find(|segment| position < segment.start + segment.duration())
+            .expect("the position is always shorter than the period");
+        segment.frame_at(position - segment.start)
+    }
+}
+
+fn decent_linear_steps(transition_time: u32, diffs: &[f64]) -> u32 {
+    let diff_square_sum: f64 = diffs.iter().map(|diff| diff.powi(2)).sum();
+    let diff_rms = diff_square_sum.sqrt();
+
+    if diff_rms <= f64::EPSILON {
+        1
+    } else {
+        // A delta of 15 as rgb value per second should be barely
+        // visible to the human eye.
+        let imperceivable_steps = diff_rms / 15.0;
+
+        // As time becomes larger, make smaller steps because they
+        // might become identifiable as individual steps again.
+        let time_factor = (transition_time as f64 / 1000.0).sqrt().clamp(0.4, 5.0);
+
+        ((imperceivable_steps * time_factor).round() as u32).max(1)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tailor_api::{Color, ColorPoint, ColorSpace, ColorTransition};
+
+    use super::{decent_linear_steps, ColorAnimation};
+
+    const BLACK: Color = Color { r: 0, g: 0, b: 0 };
+    const RED: Color = Color { r: 200, g: 0, b: 0 };
+
+    fn animation(transition: ColorTransition) -> ColorAnimation {
+        ColorAnimation::new(&[
+            ColorPoint {
+                color: BLACK,
+                transition: ColorTransition::None,
+                transition_time: 500,
+                color_space: ColorSpace::Rgb,
+            },
+            ColorPoint {
+                color: RED,
+                transition,
+                transition_time: 1000,
+                color_space: ColorSpace::Rgb,
+            },
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn decent_linear_step() {
+        let decent_steps = decent_linear_steps(1000, &[0.0]);
+        assert_eq!(decent_steps, 1);
+
+        let decent_steps = decent_linear_steps(1000, &[150.0]);
+        assert_eq!(decent_steps, 10);
+
+        let decent_steps = decent_linear_steps(3000, &[150.0]);
+        assert_eq!(decent_steps, 17);
+
+        let decent_steps = decent_linear_steps(1000, &[75.0]);
+        assert_eq!(decent_steps, 5);
+
+        let decent_steps = decent_linear_steps(100, &[75.0]);
+        assert_eq!(decent_steps, 2);
+    }
+
+    #[test]
+    fn transition_steps() {
+        let jumps = animation(ColorTransition::Steps(4));
+        assert_eq!(jumps.period(), 1500);
+        assert_eq!(jumps.frame_at(0), (BLACK, 500));
+        assert_eq!(jumps.frame_at(600), (BLACK, 150));
+        assert_eq!(jumps.frame_at(750), (Color { r: 50, g: 0, b: 0 }, 250));
+        assert_eq!(jumps.frame_at(1499), (Color { r: 150, g: 0, b: 0 }, 1));
+        // The animation loops.
+        assert_eq!(jumps.frame_at(1500), jumps.frame_at(0));
+
+        // Both curves have the same frames,
+        // but ease in is darker during the first half.
+        let linear = animation(ColorTransition::Linear);
+        let ease_in = animation(ColorTransition::EaseIn);
+        assert_eq!(linear.frame_at(500), (BLACK, 84));
+        assert_eq!(ease_in.frame_at(500), (BLACK, 84));
+        let (linear_color, linear_next) = linear.frame_at(950);
+        let (ease_in_color, ease_in_next) = ease_in.frame_at(950);
+        assert_eq!(linear_next, ease_in_next);
+        assert!(ease_in_color.r < linear_color.r);
+    }
+
+    #[test]
+    fn frame_rate() {
+        // Frames are timed from the start of the transition,
+        // no matter when they are requested.
+        let linear = animation(ColorTransition::Linear);
+        assert_eq!(linear.frame_at(530).0, linear.frame_at(510).0);
+        assert_eq!(linear.frame_at(530).1, 54);
+
+        // Transitions without a visible change don't need frames.
+        let unchanged = ColorAnimation::new(&[
+            ColorPoint {
+                color: RED,
+                transition: ColorTransition::Linear,
+                transition_time: 1000,
+                color_space: ColorSpace::Rgb,
+            },
+            ColorPoint {
+                color: RED,
+                transition: ColorTransition::Linear,
+                transition_time: 1000,
+                color_space: ColorSpace::Rgb,
+            },
+        ])
+        .unwrap();
+        assert_eq!(unchanged.frame_at(200), (RED, 800));
+
+        // Transitions shorter than a frame jump to the color.
+        let short = ColorAnimation::new(&[
+            ColorPoint {
+                color: BLACK,
+                transition: ColorTransition::None,
+                transition_time: 1000,
+                color_space: ColorSpace::Rgb,
+            },
+            ColorPoint {
+                color: RED,
+                transition: ColorTransition::Linear,
+                transition_time: 50,
+                color_space: ColorSpace::Rgb,
+            },
+        ])
+        .unwrap();
+        assert_eq!(short.frame_at(1000), (RED, 50));
+    }
+}
@@ -1 +1,4 @@
+mod animation;
 pub mod runtime;
+mod temperature;
+mod transition;
@@ -1,26 +1,50 @@
-use std::{future::pending, time::Duration};
+use std::{collections::BTreeMap, future::pending, time::Duration};
 
-use tailor_api::{Color, ColorPoint, ColorProfile, ColorTransition};
-use tokio::sync::{broadcast, mpsc};
-use tuxedo_ioctl::high_level::Hw;
-use tuxedo_sysfs::keyboard::KeyboardController;
+use tailor_api::{
+    Color, ColorProfile, KeyboardEffect, KeyboardZone, TemperatureGradient, ZoneColors,
+};
+use tokio::{
+    sync::{broadcast, mpsc, watch},
+    time::Instant,
+};
+use tuxedo_sysfs::keyboard::KeyboardState;
 
-use crate::suspend::process_suspend;
+use super::{animation::ColorAnimation, temperature::gradient_color};
+use crate::{
+    backend::KeyboardBackend,
+    fancontrol::telemetry::FanTelemetry,
+    suspend::{wait_for_sleep, wait_for_wake_up},
+};
 
 pub struct KeyboardRuntime {
-    io: KeyboardController,
+    io: Box<dyn KeyboardBackend>,
     profile: ColorProfile,
-    suspend_receiver: broadcast::Receiver<bool>,
-    hw: Hw,
+    /// Last brightness that was read or written,
+    /// `None` if the keyboard has no brightness control.
+    brightness: watch::Sender<Option<u8>>,
+    /// Shared with the fan control, which reads the temperatures.
+    telemetry: watch::Receiver<FanTelemetry>,
+    /// When the animation of the current profile started.
+    ///
+    /// [`Self::update_colors`] restarts after every other event, for
+    /// example a brightness change, and must continue the animation
+    /// instead of starting over.
+    animation_start: Instant,
 }
 
 impl KeyboardRuntime {
-    pub async fn new(profile: ColorProfile, suspend_receiver: broadcast::Receiver<bool>) -> Self {
+    pub fn new(
+        io: Box<dyn KeyboardBackend>,
+        profile: ColorProfile,
+        brightness: watch::Sender<Option<u8>>,
+        telemetry: watch::Receiver<FanTelemetry>,
+    ) -> Self {
         Self {
-            io: KeyboardController::new().await.unwrap(),
+            io,
             profile,
-            suspend_receiver,
-            hw: Hw::new().unwrap(),
+            brightness,
+            telemetry,
+            animation_start: Instant::now(),
         }
     }
 
@@ -28,46 +52,54 @@ impl KeyboardRuntime {
         mut self,
         mut keyboard_receiver: mpsc::Receiver<ColorProfile>,
         mut color_receiver: mpsc::Receiver<Color>,
+        mut brightness_receiver: mpsc::Receiver<u8>,
+        mut suspend_receiver: broadcast::Receiver<bool>,
+        mut shutdown_receiver: broadcast::Receiver<()>,
     ) {
-        match self.hw {
-            Hw::Clevo => {
-                loop {
-                    tokio::select! {
-                        new_colors = keyboard_receiver.recv() => {
-                            if let Some(colors) = new_colors {
-                                self.profile = colors;
+        loop {
+            tokio::select! {
+                new_colors = keyboard_receiver.recv() => {
+                    if let Some(colors) = new_colors {
+                        self.profile = colors;
+                        self.animation_start = Instant::now();
+                    }
+                }
+                Some(brightness) = brightness_receiver.recv() => {
+                    self.set_brightness(brightness).await;
+                }
+                // Override the current color value for 1s
+                override_color = color_receiver.recv() => {
+                    if let Some(mut color) = override_color {
+                        self.stop_effect().await;
+                        loop {
+                            if let Err(err) = self.io.set_color_all(&color).await {
+                                tracing::error!("Failed to update keyboard color: `{}`", err.to_string());
+                                break;
                             }
-                        }
-                        // Override the current color value for 1s
-                        override_color = color_receiver.recv() => {
-                            if let Some(mut color) = override_color {
-                                loop {
-                                    if let Err(err) = self.io.set_color_left(&color).await {
-                                        tracing::error!("Failed to update keyboard color: `{}`", err.to_string());
-                                        break;
-                                    }
-                                    tokio::select! {
-                                        override_color = color_receiver.recv() => {
-                                            if let Some(new_color) = override_color {
-                                                color = new_color
-                                            }
-                                        }
-                                        _ = tokio::time::sleep(Duration::from_millis(1000)) => break,
+                            tokio::select! {
+                                override_color = color_receiver.recv() => {
+                                    if let Some(new_color) = override_color {
+                                        color = new_color
                                     }
                                 }
+                                _ = tokio::time::sleep(Duration::from_millis(1000)) => break,
                             }
                         }
-                        _ = self.update_colors() => {}
                     }
                 }
-            }
-            // TODO(uniwill) There is no(?) keyboard support for uniwill, should we just
-            // prevent this function to run or is it expected to read from the channel?
-            Hw::Uniwill => loop {
-                tokio::select! {
-                    _ = tokio::time::sleep(Duration::from_millis(1000)) => {}
+                // Stop the animation while suspended.
+                _ = wait_for_sleep(&mut suspend_receiver) => {
+                    // Brightness keys change the brightness without tailord.
+                    self.read_brightness().await;
+                    wait_for_wake_up(&mut suspend_receiver).await;
+                    self.resume().await;
                 }
-            },
+                _ = self.update_colors() => {}
+                _ = shutdown_receiver.recv() => {
+                    self.restore_static_color().await;
+                    return;
+                }
+            }
         }
     }
 
@@ -75,140 +107,256 @@ impl KeyboardRuntime {
         match &self.profile {
             ColorProfile::None => pending().await,
             ColorProfile::Single(color) => {
-                self.io.set_color_all(color).await.unwrap();
+                self.stop_effect().await;
+                if let Err(err) = self.io.set_color_all(color).await {
+                    tracing::error!("Failed to set the keyboard color: `{err}`");
+                }
                 pending().await
             }
             ColorProfile::Multiple(colors) => {
-                let color_steps = calculate_color_animation_steps(colors);
-                self.run_color_animation(&color_steps).await;
+                self.stop_effect().await;
+                let animations: Vec<_> = ColorAnimation::new(colors)
+                    .map(|animation| ZoneAnimation {
+                        zone: None,
+                        animation,
+                        offset: 0,
+                    })
+                    .into_iter()
+                    .collect();
+                self.run_animations(&animations).await;
+            }
+            ColorProfile::Effect(effect) => {
+                self.start_effect(*effect).await;
+                pending().await
+            }
+            ColorProfile::Zones(zones) => {
+                self.stop_effect().await;
+                let animations: Vec<_> = zones.iter().filter_map(ZoneAnimation::new).collect();
+                self.run_animations(&animations).await;
+            }
+            ColorProfile::Temperature(gradient) => {
+                self.stop_effect().await;
+                let gradient = gradient.clone();
+                self.show_temperature(&gradient).await;
             }
         }
     }
 
-    /// Infinitely run a color animation and
-    /// stop the animation while suspended.
-    async fn run_color_animation(&mut self, color_steps: &[(Color, u32)]) {
-        for step in color_steps.iter().cycle() {
-            if let Err(err) = self.io.set_color_left(&step.0).await {
-                tracing::error!("Failed setting keyboard colors: `{err}`")
-            }
+    /// Let the firmware animate the keyboard.
+    async fn start_effect(&self, effect: KeyboardEffect) {
+        let state = KeyboardState::from(effect);
+        if !self.io.effects().contains(&state) {
+            tracing::warn!("The keyboard doesn't support the `{effect}` effect");
+        } else if let Err(err) = self.io.set_state(state).await {
+            tracing::error!("Failed to start the `{effect}` keyboard effect: `{err}`");
+        }
+    }
 
-            tokio::select! {
-                _ = tokio::time::sleep(Duration::from_millis(step.1 as u64)) => {}
-                _ = process_suspend(&mut self.suspend_receiver) => {
-                    tracing::warn!("suspending in run_color_animation");
+    /// Return to custom colors after a firmware effect.
+    async fn stop_effect(&self) {
+        if self.io.effects().is_empty() {
+            return;
+        }
+        if let Err(err) = self.io.set_state(KeyboardState::Custom).await {
+            tracing::error!("Failed to stop the keyboard effect: `{err}`");
+        }
+    }
+
+    /// The firmware often resets the keyboard during suspend.
+    ///
+    /// The colors are applied again by [`Self::update_colors`]
+    /// which restarts after this.
+    async fn resume(&mut self) {
+        tracing::info!("Restoring the keyboard after resume");
+        let brightness = *self.brightness.borrow();
+        if let Some(brightness) = brightness {
+            self.set_brightness(brightness).await;
+        }
+    }
+
+    /// Publish the brightness of the hardware, which
+    /// stays `None` if the keyboard has no brightness control.
+    async fn read_brightness(&mut self) {
+        if let Ok(brightness) = self.io.get_brightness().await {
+            self.publish_brightness(brightness);
+        }
+    }
+
+    /// Write the brightness and publish it if that worked.
+    pub async fn set_brightness(&mut self, brightness: u8) {
+        if self.brightness.borrow().is_none() {
+            tracing::warn!("The keyboard brightness can't be changed on this device");
+            return;
+        }
+        match self.io.set_brightness(brightness).await {
+            Ok(()) => self.publish_brightness(brightness),
+            Err(err) => tracing::error!("Failed to set the keyboard brightness: `{err}`"),
+        }
+    }
+
+    fn publish_brightness(&self, brightness: u8) {
+        self.brightness.send_if_modified(|current| {
+            let changed = *current != Some(brightness);
+            *current = Some(brightness);
+            changed
+        });
+    }
+
+    /// Leave the keyboard with a static color so that
+    /// no animation frame remains visible after shutdown.
+    async fn restore_static_color(&mut self) {
+        let color = match &self.profile {
+            // The firmware keeps running the effect on its own
+            // and temperature colors only change between readings.
+            ColorProfile::None | ColorProfile::Effect(_) | ColorProfile::Temperature(_) => return,
+            ColorProfile::Single(color) => color,
+            ColorProfile::Multiple(colors) => match colors.first() {
+                Some(point) => &point.color,
+                None => return,
+            },
+            ColorProfile::Zones(zones) => {
+                for zone in zones {
+                    if let Some(point) = zone.points.first() {
+                        if let Err(err) = self.io.set_color(zone.zone, &point.color).await {
+                            tracing::error!("Failed to restore the keyboard color: `{err}`");
+                        }
+                    }
                 }
+                return;
             }
+        };
+        if let Err(err) = self.io.set_color_all(color).await {
+            tracing::error!("Failed to restore the keyboard color: `{err}`");
         }
     }
-}
 
-fn calculate_color_animation_steps(colors: &[ColorPoint]) -> Vec<(Color, u32)> {
-    let mut color_steps = Vec::new();
-    let mut prev_color = colors.last().unwrap().color.clone();
-
-    for color_point in colors {
-        let ColorPoint {
-            color,
-            transition,
-            transition_time,
-        } = color_point.clone();
+    /// Infinitely run the animations on a shared clock,
+    /// only writing the zones whose color changed.
+    async fn run_animations(&mut self, animations: &[ZoneAnimation]) {
+        let mut current: BTreeMap<Option<KeyboardZone>, Color> = BTreeMap::new();
+        let start = self.animation_start;
+        loop {
+            // Frames follow the clock instead of adding up sleeps, so slow
+            // writes neither delay the animation nor pile up behind it.
+            let time = start.elapsed().as_millis() as u64;
+            let mut next_frame = u64::MAX;
+            for animation in animations {
+                let (color, remaining) = animation.frame_at(time);
+                next_frame = next_frame.min(remaining);
+                if current.get(&animation.zone) == Some(&color) {
+                    continue;
+                }
 
-        match transition {
-            ColorTransition::None => {
-                color_steps.push((color, transition_time));
+                let result = match animation.zone {
+                    Some(zone) => self.io.set_color(zone, &color).await,
+                    None => self.io.set_color_all(&color).await,
+                };
+                if let Err(err) = result {
+                    tracing::error!("Failed setting keyboard colors: `{err}`")
+                }
+                current.insert(animation.zone, color);
             }
-            ColorTransition::Linear => {
-                linear_color_transition(&mut color_steps, color, &prev_color, transition_time);
+
+            if next_frame == u64::MAX {
+                // All zones have a static color.
+                pending::<()>().await;
             }
+            tokio::time::sleep_until(start + Duration::from_millis(time + next_frame)).await;
         }
-
-        prev_color = color_point.color.clone();
     }
-    color_steps
-}
 
-fn linear_color_transition(
-    color_steps: &mut Vec<(Color, u32)>,
-    color: Color,
-    prev_color: &Color,
-    transition_time: u32,
-) {
-    // Max step size 80 ms (12.5 fps).
-    // More would be rather CPU intensive for a background job.
-    let steps = transition_time / 80;
-
-    if steps == 0 {
-        color_steps.push((color, transition_time));
-    } else {
-        let r_diff = color.r as f64 - prev_color.r as f64;
-        let g_diff = color.g as f64 - prev_color.g as f64;
-        let b_diff = color.b as f64 - prev_color.b as f64;
-
-        let decent_steps = decent_linear_steps(transition_time, &[r_diff, g_diff, b_diff]);
-
-        // Use a lower step size if the animation is slow.
-        // The human eye won't notice the lower fps but
-        // the CPU usage will drop significantly.
-        let steps = steps.min(decent_steps);
-
-        let step_time = transition_time / steps;
-
-        for idx in 0..steps {
-            let percent = idx as f64 / steps as f64;
-
-            let r = f64_to_u8(prev_color.r as f64 + r_diff * percent);
-            let g = f64_to_u8(prev_color.g as f64 + g_diff * percent);
-            let b = f64_to_u8(prev_color.b as f64 + b_diff * percent);
-
-            let color = Color { r, g, b };
-            color_steps.push((color, step_time));
+    /// Update the color of all zones whenever the CPU temperature changes.
+    async fn show_temperature(&mut self, gradient: &TemperatureGradient) {
+        let mut telemetry = self.telemetry.clone();
+        let mut current = None;
+        loop {
+            let temp = telemetry.borrow_and_update().cpu_temperature();
+            if let Some(color) = temp.and_then(|temp| gradient_color(gradient, temp)) {
+                if current.as_ref() != Some(&color) {
+                    if let Err(err) = self.io.set_color_all(&color).await {
+                        tracing::error!("Failed setting keyboard colors: `{err}`")
+                    }
+                    current = Some(color);
+                }
+            }
+
+            if telemetry.changed().await.is_err() {
+                // The fan control stopped, so the temperature won't change anymore.
+                pending::<()>().await;
+            }
         }
     }
 }
 
-fn f64_to_u8(float: f64) -> u8 {
-    float.clamp(0.0, 255.0).round() as u8
+/// A color animation together with the zone it is shown on.
+struct ZoneAnimation {
+    /// `None` for all zones at once.
+    zone: Option<KeyboardZone>,
+    animation: ColorAnimation,
+    /// How many ms the zone lags behind.
+    offset: u64,
 }
 
-fn decent_linear_steps(transition_time: u32, diffs: &[f64]) -> u32 {
-    let diff_square_sum: f64 = diffs.iter().map(|diff| diff.powi(2)).sum();
-    let diff_rms = diff_square_sum.sqrt();
-
-    if diff_rms <= f64::EPSILON {
-        1
-    } else {
-        // A delta of 15 as rgb value per second should be barely
-        // visible to the human eye.
-        let imperceivable_steps = diff_rms / 15.0;
-
-        // As time becomes larger, make smaller steps because they
-        // might become identifiable as individual steps again.
-        let time_factor = (transition_time as f64 / 1000.0).sqrt().clamp(0.4, 5.0);
+impl ZoneAnimation {
+    /// Returns `None` for zones without colors.
+    fn new(zone: &ZoneColors) -> Option<Self> {
+        Some(Self {
+            zone: Some(zone.zone),
+            animation: ColorAnimation::new(&zone.points)?,
+            offset: zone.offset as u64,
+        })
+    }
 
-        ((imperceivable_steps * time_factor).round() as u32).max(1)
+    /// See [`ColorAnimation::frame_at`].
+    fn frame_at(&self, time: u64) -> (Color, u64) {
+        let period = self.animation.period().max(1);
+        self.animation
+            .frame_at(time + period - self.offset % period)
     }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::keyboard::runtime::decent_linear_steps;
+    use tailor_api::{Color, ColorPoint, ColorSpace, ColorTransition, KeyboardZone, ZoneColors};
+
+    use crate::keyboard::runtime::ZoneAnimation;
 
     #[test]
-    fn decent_linear_step() {
-        let decent_steps = decent_linear_steps(1000, &[0.0]);
-        assert_eq!(decent_steps, 1);
+    fn zone_offset() {
+        let point = |r, transition_time| ColorPoint {
+            color: Color { r, g: 0, b: 0 },
+            transition: ColorTransition::None,
+            transition_time,
+            color_space: ColorSpace::Rgb,
+        };
+        let zone = |offset| ZoneColors {
+            zone: KeyboardZone::Left,
+            points: vec![point(1, 1000), point(2, 500)],
+            offset,
+        };
 
-        let decent_steps = decent_linear_steps(1000, &[150.0]);
-        assert_eq!(decent_steps, 10);
+        let animation = ZoneAnimation::new(&zone(0)).unwrap();
+        assert_eq!(animation.frame_at(0), (point(1, 0).color, 1000));
+        assert_eq!(animation.frame_at(1200), (point(2, 0).color, 300));
+        assert_eq!(animation.frame_at(1600), (point(1, 0).color, 900));
 
-        let decent_steps = decent_linear_steps(3000, &[150.0]);
-        assert_eq!(decent_steps, 17);
+        // A zone with an offset shows what the first zone showed earlier.
+        let lagging = ZoneAnimation::new(&zone(400)).unwrap();
+        assert_eq!(lagging.frame_at(1600), animation.frame_at(1200));
+        assert_eq!(lagging.frame_at(0), (point(2, 0).color, 400));
 
-        let decent_steps = decent_linear_steps(1000, &[75.0]);
-        assert_eq!(decent_steps, 5);
+        // Single colors never change.
+        let single = ZoneColors {
+            points: vec![point(3, 1000)],
+            ..zone(0)
+        };
+        let single = ZoneAnimation::new(&single).unwrap();
+        assert_eq!(single.frame_at(5000), (point(3, 0).color, u64::MAX));
 
-        let decent_steps = decent_linear_steps(100, &[75.0]);
-        assert_eq!(decent_steps, 2);
+        assert!(ZoneAnimation::new(&ZoneColors {
+            points: Vec::new(),
+            ..zone(0)
+        })
+        .is_none());
     }
 }
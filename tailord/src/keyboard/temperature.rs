@@ -0,0 +1,74 @@
+//! Colors that show the CPU temperature.
+
+use tailor_api::{Color, TemperatureGradient};
+
+use super::transition;
+
+/// The color of `gradient` at `temp` °C, `None` if the gradient has no colors.
+pub fn gradient_color(gradient: &TemperatureGradient, temp: u8) -> Option<Color> {
+    let mut points: Vec<_> = gradient.points.iter().collect();
+    // Profiles are written by hand, so don't rely on the order.
+    points.sort_by_key(|point| point.temp);
+
+    let upper = points.iter().position(|point| point.temp > temp);
+    let (lower, upper) = match upper {
+        // Colder than all points.
+        Some(0) => return Some(points[0].color.clone()),
+        Some(idx) => (points[idx - 1], points[idx]),
+        // Hotter than all points.
+        None => return points.last().map(|point| point.color.clone()),
+    };
+
+    let amount = (temp - lower.temp) as f64 / (upper.temp - lower.temp) as f64;
+    Some(transition::blend(
+        &lower.color,
+        &upper.color,
+        amount,
+        gradient.color_space,
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use tailor_api::{Color, ColorSpace, TemperatureColor, TemperatureGradient};
+
+    use super::gradient_color;
+
+    #[test]
+    fn gradient() {
+        let blue = Color { r: 0, g: 0, b: 255 };
+        let red = Color { r: 255, g: 0, b: 0 };
+        let gradient = TemperatureGradient {
+            points: vec![
+                TemperatureColor {
+                    temp: 90,
+                    color: red.clone(),
+                },
+                TemperatureColor {
+                    temp: 40,
+                    color: blue.clone(),
+                },
+            ],
+            color_space: ColorSpace::Rgb,
+        };
+
+        assert_eq!(gradient_color(&gradient, 20), Some(blue.clone()));
+        assert_eq!(gradient_color(&gradient, 40), Some(blue));
+        assert_eq!(
+            gradient_color(&gradient, 65),
+            Some(Color {
+                r: 128,
+                g: 0,
+                b: 128
+            })
+        );
+        assert_eq!(gradient_color(&gradient, 90), Some(red.clone()));
+        assert_eq!(gradient_color(&gradient, 100), Some(red));
+
+        let empty = TemperatureGradient {
+            points: Vec::new(),
+            color_space: ColorSpace::Rgb,
+        };
+        assert_eq!(gradient_color(&empty, 50), None);
+    }
+}
@@ -0,0 +1,336 @@
+//! Timing curves and color spaces of color transitions.
+
+use tailor_api::{Color, ColorSpace, ColorTransition};
+
+/// Map the elapsed time of a transition from 0 to 1
+/// to how far the color has changed.
+///
+/// Curves may overshoot below 0 or above 1.
+pub fn ease(transition: ColorTransition, time: f64) -> f64 {
+    match transition {
+        ColorTransition::None => 1.0,
+        ColorTransition::Linear => time,
+        // Same curves as in CSS.
+        ColorTransition::EaseIn => cubic_bezier(0.42, 0.0, 1.0, 1.0, time),
+        ColorTransition::EaseOut => cubic_bezier(0.0, 0.0, 0.58, 1.0, time),
+        ColorTransition::EaseInOut => cubic_bezier(0.42, 0.0, 0.58, 1.0, time),
+        ColorTransition::CubicBezier { x1, y1, x2, y2 } => cubic_bezier(
+            x1 as f64 / 1000.0,
+            y1 as f64 / 1000.0,
+            x2 as f64 / 1000.0,
+            y2 as f64 / 1000.0,
+            time,
+        ),
+        ColorTransition::Steps(steps) => (time * steps as f64).floor() / steps as f64,
+    }
+}
+
+fn cubic_bezier(x1: f64, y1: f64, x2: f64, y2: f64, time: f64) -> f64 {
+    let bezier = |p1: f64, p2: f64, t: f64| {
+        let inv = 1.0 - t;
+        3.0 * inv * inv * t * p1 + 3.0 * inv * t * t * p2 + t * t * t
+    };
+
+    // The x values are monotonic because x1 and x2 are between 0 and 1,
+    // so the parameter for `time` can be found by bisection.
+    let (mut low, mut high) = (0.0, 1.0);
+    let mut t = time.clamp(0.0, 1.0);
+    for _ in 0..32 {
+        let x = bezier(x1, x2, t);
+        if (x - time).abs() < 1e-6 {
+            break;
+        }
+        if x < time {
+            low = t;
+        } else {
+            high = t;
+        }
+        t = (low + high) / 2.0;
+    }
+    bezier(y1, y2, t)
+}
+
+/// Blend two colors, where an `amount` of 0 returns `from` and 1 returns `to`.
+pub fn blend(from: &Color, to: &Color, amount: f64, space: ColorSpace) -> Color {
+    let [r, g, b] = blend_rgb(from, to, amount, space);
+    Color {
+        r: f64_to_u8(r),
+        g: f64_to_u8(g),
+        b: f64_to_u8(b),
+    }
+}
+
+/// Length of the way from one color to the other in
+/// RGB values, which is longer than the direct distance
+/// if the color space takes a detour.
+pub fn path_length(from: &Color, to: &Color, space: ColorSpace) -> f64 {
+    const SEGMENTS: usize = 16;
+
+    let points: Vec<_> = (0..=SEGMENTS)
+        .map(|idx| blend_rgb(from, to, idx as f64 / SEGMENTS as f64, space))
+        .collect();
+    points
+        .windows(2)
+        .map(|pair| {
+            pair[0]
+                .iter()
+                .zip(pair[1])
+                .map(|(a, b)| (a - b).powi(2))
+                .sum::<f64>()
+                .sqrt()
+        })
+        .sum()
+}
+
+/// Blend two colors without rounding, in RGB values from 0 to 255.
+fn blend_rgb(from: &Color, to: &Color, amount: f64, space: ColorSpace) -> [f64; 3] {
+    let from = to_rgb(from);
+    let to = to_rgb(to);
+    match space {
+        ColorSpace::Rgb => lerp3(from, to, amount).map(|value| value * 255.0),
+        ColorSpace::Hsv => {
+            let [mut from_hue, mut from_sat, from_val] = rgb_to_hsv(from);
+            let [mut to_hue, mut to_sat, to_val] = rgb_to_hsv(to);
+
+            // Greys don't have a hue and black doesn't have
+            // a saturation either, so keep those of the other color.
+            if from_val <= f64::EPSILON {
+                from_sat = to_sat;
+            } else if to_val <= f64::EPSILON {
+                to_sat = from_sat;
+            }
+            if from_sat <= f64::EPSILON {
+                from_hue = to_hue;
+            } else if to_sat <= f64::EPSILON {
+                to_hue = from_hue;
+            }
+
+            // Go the shorter way around the color wheel.
+            let diff = (to_hue - from_hue + 540.0).rem_euclid(360.0) - 180.0;
+            let hue = (from_hue + diff * amount).rem_euclid(360.0);
+            let sat = lerp(from_sat, to_sat, amount);
+            let val = lerp(from_val, to_val, amount);
+            hsv_to_rgb([hue, sat, val]).map(|value| value * 255.0)
+        }
+        ColorSpace::Oklab => {
+            let lab = lerp3(rgb_to_oklab(from), rgb_to_oklab(to), amount);
+            oklab_to_rgb(lab).map(|value| value * 255.0)
+        }
+    }
+}
+
+fn f64_to_u8(float: f64) -> u8 {
+    float.clamp(0.0, 255.0).round() as u8
+}
+
+fn lerp(from: f64, to: f64, amount: f64) -> f64 {
+    from + (to - from) * amount
+}
+
+fn lerp3(from: [f64; 3], to: [f64; 3], amount: f64) -> [f64; 3] {
+    [
+        lerp(from[0], to[0], amount),
+        lerp(from[1], to[1], amount),
+        lerp(from[2], to[2], amount),
+    ]
+}
+
+fn to_rgb(color: &Color) -> [f64; 3] {
+    [color.r, color.g, color.b].map(|value| value as f64 / 255.0)
+}
+
+/// Returns the hue in degrees and the saturation and value from 0 to 1.
+fn rgb_to_hsv([r, g, b]: [f64; 3]) -> [f64; 3] {
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let delta = max - min;
+
+    let hue = if delta <= f64::EPSILON {
+        0.0
+    } else if max == r {
+        60.0 * ((g - b) / delta).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / delta + 2.0)
+    } else {
+        60.0 * ((r - g) / delta + 4.0)
+    };
+    let sat = if max <= f64::EPSILON {
+        0.0
+    } else {
+        delta / max
+    };
+    [hue, sat, max]
+}
+
+fn hsv_to_rgb([hue, sat, val]: [f64; 3]) -> [f64; 3] {
+    let chroma = val * sat;
+    let sector = hue / 60.0;
+    let x = chroma * (1.0 - (sector.rem_euclid(2.0) - 1.0).abs());
+    let (r, g, b) = match sector as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let min = val - chroma;
+    [r + min, g + min, b + min]
+}
+
+fn srgb_to_linear(value: f64) -> f64 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(value: f64) -> f64 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// See <https://bottosson.github.io/posts/oklab/>.
+fn rgb_to_oklab(rgb: [f64; 3]) -> [f64; 3] {
+    let [r, g, b] = rgb.map(srgb_to_linear);
+
+    let l = (0.4122214708 * r + 0.5363325363 * g + 0.0514459929 * b).cbrt();
+    let m = (0.2119034982 * r + 0.6806995451 * g + 0.1073969566 * b).cbrt();
+    let s = (0.0883024619 * r + 0.2817188376 * g + 0.6299787005 * b).cbrt();
+
+    [
+        0.2104542553 * l + 0.7936177850 * m - 0.0040720468 * s,
+        1.9779984951 * l - 2.4285922050 * m + 0.4505937099 * s,
+        0.0259040371 * l + 0.7827717662 * m - 0.8086757660 * s,
+    ]
+}
+
+fn oklab_to_rgb([lightness, a, b]: [f64; 3]) -> [f64; 3] {
+    let l = (lightness + 0.3963377774 * a + 0.2158037573 * b).powi(3);
+    let m = (lightness - 0.1055613458 * a - 0.0638541728 * b).powi(3);
+    let s = (lightness - 0.0894841775 * a - 1.2914855480 * b).powi(3);
+
+    [
+        4.0767416621 * l - 3.3077115913 * m + 0.2309699292 * s,
+        -1.2684380046 * l + 2.6097574011 * m - 0.3413193965 * s,
+        -0.0041960863 * l - 0.7034186147 * m + 1.7076147010 * s,
+    ]
+    .map(|value| linear_to_srgb(value.clamp(0.0, 1.0)))
+}
+
+#[cfg(test)]
+mod test {
+    use tailor_api::{Color, ColorSpace, ColorTransition};
+
+    use super::{blend, ease, path_length};
+
+    const RED: Color = Color { r: 255, g: 0, b: 0 };
+    const CYAN: Color = Color {
+        r: 0,
+        g: 255,
+        b: 255,
+    };
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-3, "{a} != {b}");
+    }
+
+    #[test]
+    fn curves() {
+        for transition in [
+            ColorTransition::Linear,
+            ColorTransition::EaseIn,
+            ColorTransition::EaseOut,
+            ColorTransition::EaseInOut,
+        ] {
+            assert_close(ease(transition, 0.0), 0.0);
+            assert_close(ease(transition, 1.0), 1.0);
+        }
+
+        assert_close(ease(ColorTransition::Linear, 0.3), 0.3);
+        assert!(ease(ColorTransition::EaseIn, 0.3) < 0.3);
+        assert!(ease(ColorTransition::EaseOut, 0.3) > 0.3);
+        assert_close(ease(ColorTransition::EaseInOut, 0.5), 0.5);
+        assert!(ease(ColorTransition::EaseInOut, 0.2) < 0.2);
+        assert!(ease(ColorTransition::EaseInOut, 0.8) > 0.8);
+
+        // A bezier along the diagonal is linear.
+        let diagonal = ColorTransition::CubicBezier {
+            x1: 250,
+            y1: 250,
+            x2: 750,
+            y2: 750,
+        };
+        assert_close(ease(diagonal, 0.4), 0.4);
+
+        // Values outside of 0 and 1 overshoot.
+        let overshoot = ColorTransition::CubicBezier {
+            x1: 300,
+            y1: 1500,
+            x2: 700,
+            y2: 1500,
+        };
+        assert!(ease(overshoot, 0.5) > 1.0);
+
+        let steps = ColorTransition::Steps(4);
+        assert_close(ease(steps, 0.2), 0.0);
+        assert_close(ease(steps, 0.3), 0.25);
+        assert_close(ease(steps, 0.99), 0.75);
+    }
+
+    #[test]
+    fn color_spaces() {
+        for space in ColorSpace::ALL {
+            assert_eq!(blend(&RED, &CYAN, 0.0, space), RED);
+            assert_eq!(blend(&RED, &CYAN, 1.0, space), CYAN);
+        }
+
+        // RGB turns grey between complementary colors.
+        let grey = Color {
+            r: 128,
+            g: 128,
+            b: 128,
+        };
+        assert_eq!(blend(&RED, &CYAN, 0.5, ColorSpace::Rgb), grey);
+
+        // HSV keeps the colors saturated and takes the shorter way
+        // from red to blue, which is through magenta.
+        let saturated = blend(&RED, &CYAN, 0.5, ColorSpace::Hsv);
+        let channels = [saturated.r, saturated.g, saturated.b];
+        assert!(channels.contains(&255) && channels.contains(&0));
+        let blue = Color { r: 0, g: 0, b: 255 };
+        assert_eq!(
+            blend(&RED, &blue, 0.5, ColorSpace::Hsv),
+            Color {
+                r: 255,
+                g: 0,
+                b: 255
+            }
+        );
+        // Black fades into the hue of the other color.
+        let black = Color { r: 0, g: 0, b: 0 };
+        let dark_red = blend(&black, &RED, 0.5, ColorSpace::Hsv);
+        assert!(dark_red.r > 0 && dark_red.g == 0 && dark_red.b == 0);
+
+        // Oklab blends by perceived lightness, which puts the middle
+        // between black and white at a lower sRGB value than RGB.
+        let white = Color {
+            r: 255,
+            g: 255,
+            b: 255,
+        };
+        let oklab_grey = blend(&black, &white, 0.5, ColorSpace::Oklab);
+        assert_eq!(oklab_grey.r, oklab_grey.g);
+        assert_eq!(oklab_grey.r, 99);
+
+        // Detours make the way longer than the direct distance.
+        let direct = path_length(&RED, &CYAN, ColorSpace::Rgb);
+        assert_close(direct, (3.0 * 255.0_f64.powi(2)).sqrt());
+        assert!(path_length(&RED, &CYAN, ColorSpace::Hsv) > direct);
+        assert_close(path_length(&RED, &RED, ColorSpace::Oklab), 0.0);
+    }
+}
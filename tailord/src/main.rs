@@ -1,26 +1,72 @@
+mod backend;
+mod cpu;
 mod dbus;
 mod fancontrol;
 pub mod keyboard;
+mod power;
 mod profiles;
+mod shutdown;
+mod subsystems;
 mod suspend;
+mod switches;
 pub mod util;
 
-use std::future::pending;
+use std::{path::PathBuf, time::Duration};
 
-use dbus::{FanInterface, KeyboardInterface, ProfileInterface};
+use backend::Backend;
+use clap::Parser;
+use dbus::{
+    detect_io_capabilities, detect_keyboard_capabilities, emit_brightness_changes,
+    emit_subsystem_changes, emit_telemetry_changes, forward_thermal_alarms,
+    set_performance_profile, switch_profiles_on_power_change, CpuInterface, CpuInterfaceV2,
+    DeviceInterface, FanInterface, FanInterfaceV2, KeyboardBacklightInterface, KeyboardInterface,
+    KeyboardInterfaceV2, PerformanceInterface, PowerInterface, ProfileInterface,
+    ProfileInterfaceV2, SwitchesInterface, TelemetryInterface, UPowerKbdBacklightInterface,
+    UPOWER_KBD_BACKLIGHT_PATH,
+};
 use fancontrol::FanRuntime;
 use futures::StreamExt;
 use profiles::Profile;
 use signal_hook::consts::{SIGINT, SIGQUIT, SIGTERM};
 use signal_hook_tokio::Signals;
-use tokio::sync::{broadcast, mpsc};
+use subsystems::Subsystem;
+use tailor_api::Capabilities;
+use tokio::sync::{broadcast, mpsc, watch};
+use tuxedo_ioctl::high_level::Fan;
 use zbus::ConnectionBuilder;
 
-use crate::keyboard::runtime::KeyboardRuntime;
+use crate::{
+    cpu::runtime::CpuRuntime, keyboard::runtime::KeyboardRuntime, switches::SwitchControl,
+};
 
 const DBUS_PATH: &str = "/com/tux/Tailor";
 
+/// Daemon handling fan, keyboard and general HW support for TUXEDO laptops.
+#[derive(Debug, Parser)]
+#[command(version)]
+struct Args {
+    /// Directory that stores all profiles.
+    #[arg(long, env = "TAILORD_CONFIG_DIR", default_value = profiles::DEFAULT_CONFIG_DIR)]
+    config_dir: String,
+    /// Device file of the tuxedo_io kernel module.
+    #[arg(long, env = "TAILORD_IO_DEVICE", default_value = tuxedo_ioctl::TUXEDO_IO_DEVICE_FILE)]
+    io_device: PathBuf,
+    /// Root of the sysfs tree used for the keyboard.
+    #[arg(long, env = "TAILORD_SYS_FS_ROOT", default_value = tuxedo_sysfs::SYS_FS_ROOT)]
+    sys_fs_root: PathBuf,
+    /// Use a simulated device instead of real hardware.
+    #[arg(long, env = "TAILORD_SIMULATED")]
+    simulated: bool,
+    /// Also serve the keyboard brightness with the `org.freedesktop.UPower.KbdBacklight`
+    /// interface at `/org/freedesktop/UPower/KbdBacklight`. This only exposes a UPower-shaped
+    /// interface on tailord's own `com.tux.Tailor` bus name, which desktops don't call.
+    #[arg(long, env = "TAILORD_UPOWER_KBD_BACKLIGHT")]
+    upower_kbd_backlight: bool,
+}
+
 fn main() {
+    let args = Args::parse();
+
     if std::env::var_os("RUST_LOG").is_none() {
         std::env::set_var("RUST_LOG", "info");
     }
@@ -31,67 +77,323 @@ fn main() {
         .without_time()
         .init();
 
-    tokio_uring::start(start_runtime());
+    tokio_uring::start(start_runtime(args));
 }
 
-async fn start_runtime() {
-    let (suspend_sender, suspend_receiver) = broadcast::channel(1);
+async fn start_runtime(args: Args) {
+    profiles::set_config_dir(&args.config_dir);
+
+    let (suspend_sender, _) = broadcast::channel(1);
     let (shutdown_sender, mut shutdown_receiver) = broadcast::channel(1);
 
-    let (keyboard_sender, keyboard_receiver) = mpsc::channel(1);
-    let (fan_sender, fan_receiver) = mpsc::channel(1);
+    let (keyboard_sender, mut keyboard_receiver) = mpsc::channel(1);
+    let (fan_sender, mut fan_receiver) = mpsc::channel(1);
+    let (cpu_sender, mut cpu_receiver) = mpsc::channel(1);
 
-    let (color_sender, color_receiver) = mpsc::channel(1);
-    let (fan_speed_sender, fan_speed_receiver) = mpsc::channel(1);
+    let (color_sender, mut color_receiver) = mpsc::channel(1);
+    let (brightness_sender, mut brightness_receiver) = mpsc::channel(1);
+    let (brightness_watch_sender, brightness_watch) = watch::channel(None);
+    let (fan_speed_sender, mut fan_speed_receiver) = mpsc::channel(1);
+    let (alarm_sender, alarm_receiver) = mpsc::channel(8);
+    let (telemetry_sender, telemetry_receiver) = watch::channel(Default::default());
+    let (power_sender, power_receiver) = watch::channel(None);
 
     let signals = Signals::new([SIGTERM, SIGINT, SIGQUIT]).unwrap();
     tokio_uring::spawn(handle_signals(signals, shutdown_sender));
 
+    let backend = if args.simulated {
+        tracing::warn!("Using a simulated device, no hardware will be touched");
+        Backend::Simulated
+    } else {
+        Backend::Hardware {
+            io_device: args.io_device,
+            sys_fs_root: args.sys_fs_root.clone(),
+        }
+    };
+    let io = backend.io();
+    shutdown::install_panic_hook(io.clone());
+
+    let (subsystem_sender, subsystem_receiver) = watch::channel(subsystems::initial_states());
+    let (capabilities_sender, capabilities_receiver) = watch::channel(Capabilities::default());
+    let (fans_sender, fans_receiver) = watch::channel(Vec::new());
+
+    let switches = SwitchControl::new(io.clone());
+
     let keyboard_interface = KeyboardInterface {
         color_sender,
         keyboard_sender: keyboard_sender.clone(),
     };
 
+    let backlight_interface = KeyboardBacklightInterface {
+        brightness: brightness_watch.clone(),
+        brightness_sender: brightness_sender.clone(),
+    };
+
     let fan_interface = FanInterface {
         fan_speed_sender,
         fan_sender: fan_sender.clone(),
+        fans: fans_receiver,
+    };
+
+    let cpu_interface = CpuInterface {
+        cpu_sender: cpu_sender.clone(),
     };
 
     let profile_interface = ProfileInterface {
         keyboard_sender,
+        brightness_sender,
         fan_sender,
+        cpu_sender,
+        io: io.clone(),
+        switches: switches.clone(),
     };
 
-    let _ = ConnectionBuilder::system()
-        .unwrap()
+    let device_interface = DeviceInterface {
+        capabilities: capabilities_receiver,
+        subsystems: subsystem_receiver.clone(),
+    };
+
+    let switches_interface = SwitchesInterface {
+        switches: switches.clone(),
+    };
+
+    let performance_interface = PerformanceInterface { io: io.clone() };
+
+    let telemetry_interface = TelemetryInterface {
+        telemetry: telemetry_receiver.clone(),
+    };
+
+    let power_interface = PowerInterface {
+        power_source: power_receiver.clone(),
+    };
+
+    let mut builder = ConnectionBuilder::system().unwrap();
+    if args.upower_kbd_backlight {
+        builder = builder
+            .serve_at(
+                UPOWER_KBD_BACKLIGHT_PATH,
+                UPowerKbdBacklightInterface(backlight_interface.clone()),
+            )
+            .unwrap();
+    }
+    let connection = builder
         .name("com.tux.Tailor")
         .unwrap()
+        .serve_at(DBUS_PATH, backlight_interface)
+        .unwrap()
+        .serve_at(DBUS_PATH, KeyboardInterfaceV2(keyboard_interface.clone()))
+        .unwrap()
         .serve_at(DBUS_PATH, keyboard_interface)
         .unwrap()
+        .serve_at(DBUS_PATH, FanInterfaceV2(fan_interface.clone()))
+        .unwrap()
         .serve_at(DBUS_PATH, fan_interface)
         .unwrap()
+        .serve_at(DBUS_PATH, CpuInterfaceV2(cpu_interface.clone()))
+        .unwrap()
+        .serve_at(DBUS_PATH, cpu_interface)
+        .unwrap()
+        .serve_at(DBUS_PATH, ProfileInterfaceV2(profile_interface.clone()))
+        .unwrap()
         .serve_at(DBUS_PATH, profile_interface)
         .unwrap()
+        .serve_at(DBUS_PATH, telemetry_interface)
+        .unwrap()
+        .serve_at(DBUS_PATH, power_interface)
+        .unwrap()
+        .serve_at(DBUS_PATH, performance_interface)
+        .unwrap()
+        .serve_at(DBUS_PATH, switches_interface)
+        .unwrap()
+        .serve_at(DBUS_PATH, device_interface)
+        .unwrap()
         .build()
         .await
         .unwrap();
 
-    let Profile { fan, keyboard } = Profile::load();
+    let Profile {
+        fan,
+        keyboard,
+        keyboard_brightness,
+        cpu,
+        performance_profile,
+        // Switches are restored to their last state instead.
+        switches: _,
+    } = Profile::load();
+
+    tokio_uring::spawn(suspend::wait_for_suspend(suspend_sender.clone()));
+    tokio_uring::spawn(suspend::restore_after_resume(
+        io.clone(),
+        switches.clone(),
+        suspend_sender.subscribe(),
+    ));
+    tokio_uring::spawn(forward_thermal_alarms(connection.clone(), alarm_receiver));
+    tokio_uring::spawn(emit_telemetry_changes(
+        connection.clone(),
+        telemetry_receiver.clone(),
+    ));
+    tokio_uring::spawn(emit_brightness_changes(
+        connection.clone(),
+        brightness_watch,
+        args.upower_kbd_backlight,
+    ));
+    tokio_uring::spawn(emit_subsystem_changes(
+        connection.clone(),
+        subsystem_receiver,
+    ));
+    tokio_uring::spawn(power::watch_power_source(args.sys_fs_root, power_sender));
+    tokio_uring::spawn(switch_profiles_on_power_change(connection, power_receiver));
+
+    // Each subsystem waits for its own device, so that a missing
+    // kernel module doesn't take down the rest of the daemon.
+    let keyboard_handle = tokio_uring::spawn({
+        let backend = backend.clone();
+        let subsystems = subsystem_sender.clone();
+        let capabilities = capabilities_sender.clone();
+        let suspend_receiver = suspend_sender.subscribe();
+        let mut shutdown_receiver = shutdown_receiver.resubscribe();
+        let telemetry = telemetry_receiver;
+        async move {
+            let mut profile = keyboard;
+            let mut brightness = keyboard_brightness;
+            let open = subsystems::open_with_retry(
+                &subsystems,
+                Subsystem::Keyboard,
+                || backend.keyboard(),
+                async {
+                    futures::join!(
+                        subsystems::keep_latest(&mut keyboard_receiver, &mut profile),
+                        subsystems::discard(&mut color_receiver),
+                        async {
+                            while let Some(value) = brightness_receiver.recv().await {
+                                brightness = Some(value);
+                            }
+                        },
+                    );
+                },
+            );
+            let keyboard_io = tokio::select! {
+                keyboard_io = open => keyboard_io,
+                _ = shutdown_receiver.recv() => return,
+            };
+
+            let current_brightness = keyboard_io.get_brightness().await.ok();
+            capabilities.send_modify(|capabilities| {
+                detect_keyboard_capabilities(capabilities, keyboard_io.as_ref(), current_brightness)
+            });
+            brightness_watch_sender.send_replace(current_brightness);
+            subsystems::report_available(&subsystems, Subsystem::Keyboard);
 
-    let keyboard_rt = KeyboardRuntime::new(keyboard, suspend_receiver).await;
-    let fan_rt = FanRuntime::new(fan, suspend_sender.subscribe());
+            let mut runtime =
+                KeyboardRuntime::new(keyboard_io, profile, brightness_watch_sender, telemetry);
+            if let Some(brightness) = brightness {
+                runtime.set_brightness(brightness).await;
+            }
+
+            runtime
+                .run(
+                    keyboard_receiver,
+                    color_receiver,
+                    brightness_receiver,
+                    suspend_receiver,
+                    shutdown_receiver,
+                )
+                .await
+        }
+    });
 
-    tokio_uring::spawn(suspend::wait_for_suspend(suspend_sender));
-    tokio_uring::spawn(keyboard_rt.run(keyboard_receiver, color_receiver));
-    tokio_uring::spawn(fan_rt.run(fan_receiver, fan_speed_receiver));
+    tokio_uring::spawn({
+        let subsystems = subsystem_sender.clone();
+        async move {
+            let mut profile = cpu;
+            let cpu_io = subsystems::open_with_retry(
+                &subsystems,
+                Subsystem::Cpu,
+                || backend.cpu(),
+                subsystems::keep_latest(&mut cpu_receiver, &mut profile),
+            )
+            .await;
+            subsystems::report_available(&subsystems, Subsystem::Cpu);
 
-    tokio::select! {
-        _ = pending() => {}
-        _ = shutdown_receiver.recv() => {
-            tracing::info!("Shutting down, bye!");
-            std::process::abort()
+            CpuRuntime::new(cpu_io, profile).run(cpu_receiver).await
         }
+    });
+
+    let fan_handle = tokio_uring::spawn({
+        let io = io.clone();
+        async move {
+            let mut profiles = fan;
+            subsystems::open_with_retry(
+                &subsystem_sender,
+                Subsystem::Io,
+                || std::future::ready(io.connect()),
+                async {
+                    futures::join!(
+                        subsystems::keep_latest(&mut fan_receiver, &mut profiles),
+                        subsystems::discard(&mut fan_speed_receiver),
+                    );
+                },
+            )
+            .await;
+
+            let fans = io.available_fans();
+            let fans = if fans.is_empty() {
+                tracing::warn!("Couldn't detect any fans, falling back to the first fan");
+                vec![Fan::Fan1]
+            } else {
+                tracing::info!("Detected {} fan(s)", fans.len());
+                fans
+            };
+            fans_sender.send_replace(fans.clone());
+
+            switches.detect();
+            switches.restore().await;
+
+            if let Some(profile) = performance_profile {
+                if let Err(err) = set_performance_profile(io.as_ref(), profile) {
+                    tracing::error!("{err}");
+                }
+            }
+
+            capabilities_sender.send_modify(|capabilities| {
+                detect_io_capabilities(capabilities, io.as_ref(), &fans, &switches)
+            });
+            subsystems::report_available(&subsystem_sender, Subsystem::Io);
+
+            FanRuntime::new(
+                io,
+                &fans,
+                profiles,
+                suspend_sender.subscribe(),
+                alarm_sender,
+                telemetry_sender,
+            )
+            .run(fan_receiver, fan_speed_receiver)
+            .await
+        }
+    });
+
+    shutdown_receiver.recv().await.ok();
+    tracing::info!("Shutting down");
+
+    // Stop the fan control loops first so they can't
+    // take over the fans again after restoring them.
+    fan_handle.abort();
+    fan_handle.await.ok();
+    shutdown::restore_fans(io.as_ref());
+
+    // The keyboard runtime restores a static color on its own.
+    if tokio::time::timeout(Duration::from_secs(1), keyboard_handle)
+        .await
+        .is_err()
+    {
+        tracing::warn!("Timed out while restoring the keyboard");
     }
+
+    // Exit right away because the remaining tasks hold D-Bus connections
+    // that can't be dropped anymore once the runtime is shut down.
+    tracing::info!("Bye!");
+    std::process::exit(0);
 }
 
 async fn handle_signals(mut signals: Signals, shutdown_sender: broadcast::Sender<()>) {
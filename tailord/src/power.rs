@@ -0,0 +1,120 @@
+use std::{path::Path, time::Duration};
+
+use tokio::sync::watch;
+
+/// Power supplies are polled because sysfs doesn't notify about changes.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerSource {
+    Ac,
+    Battery,
+}
+
+impl PowerSource {
+    pub fn on_battery(self) -> bool {
+        self == Self::Battery
+    }
+
+    /// Returns the new power source if the laptop was plugged in or unplugged.
+    ///
+    /// Detecting the power source for the first time isn't a change,
+    /// so the profile picked by the user is kept at startup.
+    pub fn switched_to(previous: Option<Self>, current: Option<Self>) -> Option<Self> {
+        match (previous, current) {
+            (Some(previous), Some(current)) if previous != current => Some(current),
+            _ => None,
+        }
+    }
+}
+
+/// Read the current power source from `class/power_supply` inside the sysfs root.
+///
+/// Returns `None` if there are no chargers, for example on desktops.
+pub fn read_power_source(sys_fs_root: &Path) -> Option<PowerSource> {
+    let entries = std::fs::read_dir(sys_fs_root.join("class/power_supply")).ok()?;
+
+    let mut found_charger = false;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let read = |file: &str| std::fs::read_to_string(path.join(file)).unwrap_or_default();
+
+        // USB-C chargers show up as USB power supplies.
+        if matches!(read("type").trim(), "Mains" | "USB") {
+            found_charger = true;
+            if read("online").trim() == "1" {
+                return Some(PowerSource::Ac);
+            }
+        }
+    }
+
+    found_charger.then_some(PowerSource::Battery)
+}
+
+pub async fn watch_power_source(
+    sys_fs_root: impl AsRef<Path>,
+    sender: watch::Sender<Option<PowerSource>>,
+) {
+    loop {
+        let source = read_power_source(sys_fs_root.as_ref());
+        sender.send_if_modified(|current| {
+            if *current != source {
+                tracing::info!("Power source changed to {source:?}");
+                *current = source;
+                true
+            } else {
+                false
+            }
+        });
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use super::{read_power_source, PowerSource};
+
+    fn add_supply(root: &Path, name: &str, kind: &str, online: bool) {
+        let dir = root.join("class/power_supply").join(name);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("type"), format!("{kind}\n")).unwrap();
+        std::fs::write(dir.join("online"), if online { "1\n" } else { "0\n" }).unwrap();
+    }
+
+    #[test]
+    fn power_source() {
+        let root = std::env::temp_dir().join(format!("tailord-power-{}", std::process::id()));
+        std::fs::remove_dir_all(&root).ok();
+
+        assert_eq!(read_power_source(&root), None);
+
+        add_supply(&root, "BAT0", "Battery", true);
+        assert_eq!(read_power_source(&root), None);
+
+        add_supply(&root, "ACAD", "Mains", false);
+        add_supply(&root, "ucsi-source-psy-USBC000:001", "USB", false);
+        assert_eq!(read_power_source(&root), Some(PowerSource::Battery));
+
+        add_supply(&root, "ucsi-source-psy-USBC000:001", "USB", true);
+        assert_eq!(read_power_source(&root), Some(PowerSource::Ac));
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn power_switch() {
+        use PowerSource::{Ac, Battery};
+
+        assert_eq!(PowerSource::switched_to(None, Some(Battery)), None);
+        assert_eq!(PowerSource::switched_to(None, Some(Ac)), None);
+        assert_eq!(PowerSource::switched_to(Some(Ac), Some(Ac)), None);
+        assert_eq!(PowerSource::switched_to(Some(Ac), None), None);
+        assert_eq!(
+            PowerSource::switched_to(Some(Ac), Some(Battery)),
+            Some(Battery)
+        );
+        assert_eq!(PowerSource::switched_to(Some(Battery), Some(Ac)), Some(Ac));
+    }
+}
@@ -1,30 +1,76 @@
-use std::path::Component;
+use std::{collections::BTreeMap, path::Component, sync::OnceLock};
 
-use crate::fancontrol::profile::FanProfile;
-use tailor_api::{ColorProfile, ProfileInfo};
+use crate::fancontrol::profile::{FanProfile, FanProfiles};
+use tailor_api::{ColorProfile, CpuProfile, PowerRule, ProfileInfo, Switch};
+use tuxedo_ioctl::high_level::{PerformanceProfile, UnknownPerformanceProfile};
 use zbus::fdo;
 
-use super::util;
+use super::{switches::SwitchStates, util};
 
-pub const PROFILE_DIR: &str = "/etc/tailord/profiles/";
-pub const KEYBOARD_DIR: &str = "/etc/tailord/keyboard/";
-pub const FAN_DIR: &str = "/etc/tailord/fan/";
-pub const ACTIVE_PROFILE_PATH: &str = "/etc/tailord/active_profile.json";
+pub const DEFAULT_CONFIG_DIR: &str = "/etc/tailord";
+
+static PATHS: OnceLock<Paths> = OnceLock::new();
+
+/// Locations of all profiles inside the configuration directory.
+#[derive(Debug)]
+pub struct Paths {
+    pub profile_dir: String,
+    pub keyboard_dir: String,
+    pub fan_dir: String,
+    pub cpu_dir: String,
+    pub active_profile_path: String,
+    pub power_rule_path: String,
+    pub switches_path: String,
+}
+
+impl Paths {
+    fn new(config_dir: &str) -> Self {
+        let config_dir = config_dir.trim().trim_end_matches('/');
+        Self {
+            profile_dir: format!("{config_dir}/profiles/"),
+            keyboard_dir: format!("{config_dir}/keyboard/"),
+            fan_dir: format!("{config_dir}/fan/"),
+            cpu_dir: format!("{config_dir}/cpu/"),
+            active_profile_path: format!("{config_dir}/active_profile.json"),
+            power_rule_path: format!("{config_dir}/power_rule.json"),
+            switches_path: format!("{config_dir}/switches.json"),
+        }
+    }
+}
+
+/// Use a different configuration directory than [`DEFAULT_CONFIG_DIR`].
+///
+/// This must be called before any profile is accessed.
+pub fn set_config_dir(config_dir: &str) {
+    if PATHS.set(Paths::new(config_dir)).is_err() {
+        tracing::error!("The configuration directory was already initialized");
+    }
+}
+
+pub fn paths() -> &'static Paths {
+    PATHS.get_or_init(|| Paths::new(DEFAULT_CONFIG_DIR))
+}
 
 fn init_paths() {
-    [PROFILE_DIR, KEYBOARD_DIR, FAN_DIR]
-        .into_iter()
-        .for_each(|dir| {
-            std::fs::create_dir_all(dir).ok();
-        })
+    let paths = paths();
+    [
+        &paths.profile_dir,
+        &paths.keyboard_dir,
+        &paths.fan_dir,
+        &paths.cpu_dir,
+    ]
+    .into_iter()
+    .for_each(|dir| {
+        std::fs::create_dir_all(dir).ok();
+    })
 }
 
 fn keyboard_path(info: &ProfileInfo) -> fdo::Result<String> {
-    util::normalize_json_path(KEYBOARD_DIR, &info.keyboard)
+    util::normalize_json_path(&paths().keyboard_dir, &info.keyboard)
 }
 
-fn fan_path(info: &ProfileInfo) -> fdo::Result<String> {
-    util::normalize_json_path(FAN_DIR, &info.fan)
+fn fan_path(name: &str) -> fdo::Result<String> {
+    util::normalize_json_path(&paths().fan_dir, name)
 }
 
 fn load_keyboard_profile(info: &ProfileInfo) -> fdo::Result<ColorProfile> {
@@ -34,14 +80,56 @@ fn load_keyboard_profile(info: &ProfileInfo) -> fdo::Result<ColorProfile> {
         .map_err(|err| fdo::Error::InvalidFileContent(err.to_string()))
 }
 
-fn load_fan_profile(info: &ProfileInfo) -> fdo::Result<FanProfile> {
-    FanProfile::load_config(fan_path(info)?)
+fn load_cpu_profile(info: &ProfileInfo) -> fdo::Result<CpuProfile> {
+    let path = util::normalize_json_path(&paths().cpu_dir, &info.cpu)?;
+    let cpu_profile_data =
+        std::fs::read(path).map_err(|err| fdo::Error::IOError(err.to_string()))?;
+    serde_json::from_slice(&cpu_profile_data)
+        .map_err(|err| fdo::Error::InvalidFileContent(err.to_string()))
+}
+
+/// An empty name keeps the current performance profile.
+pub fn parse_performance_profile(
+    name: &str,
+) -> Result<Option<PerformanceProfile>, UnknownPerformanceProfile> {
+    if name.is_empty() {
+        Ok(None)
+    } else {
+        name.parse().map(Some)
+    }
+}
+
+pub fn parse_switches(switches: &BTreeMap<String, bool>) -> fdo::Result<SwitchStates> {
+    switches
+        .iter()
+        .map(|(name, enabled)| {
+            let switch = name
+                .parse::<Switch>()
+                .map_err(|err| fdo::Error::InvalidArgs(err.to_string()))?;
+            Ok((switch, *enabled))
+        })
+        .collect()
+}
+
+fn load_fan_profiles(info: &ProfileInfo) -> fdo::Result<FanProfiles> {
+    let default = FanProfile::load_config(fan_path(&info.fan)?)?;
+    let fans = info
+        .fan_assignments
+        .iter()
+        .map(|(fan, name)| Ok((*fan, FanProfile::load_config(fan_path(name)?)?)))
+        .collect::<fdo::Result<_>>()?;
+
+    Ok(FanProfiles { default, fans })
 }
 
 #[derive(Debug, Default)]
 pub struct Profile {
-    pub fan: FanProfile,
+    pub fan: FanProfiles,
     pub keyboard: ColorProfile,
+    pub keyboard_brightness: Option<u8>,
+    pub cpu: CpuProfile,
+    pub performance_profile: Option<PerformanceProfile>,
+    pub switches: SwitchStates,
 }
 
 impl Profile {
@@ -49,7 +137,10 @@ impl Profile {
         init_paths();
 
         let profile_info = Self::get_active_profile_info().unwrap_or_else(|_| {
-            tracing::error!("Failed to load active profile at `{ACTIVE_PROFILE_PATH}`");
+            tracing::error!(
+                "Failed to load active profile at `{}`",
+                paths().active_profile_path
+            );
             ProfileInfo::default()
         });
 
@@ -65,36 +156,67 @@ impl Profile {
             }
         };
 
-        let fan = match load_fan_profile(&profile_info) {
+        let fan = match load_fan_profiles(&profile_info) {
             Ok(fan) => fan,
             Err(err) => {
                 tracing::error!(
-                    "Failed to load fan color profile called `{}`: `{}`",
-                    profile_info.fan,
+                    "Failed to load fan profiles `{}`: `{}`",
+                    profile_info.fan_profiles().collect::<Vec<_>>().join("`, `"),
+                    err.to_string(),
+                );
+                FanProfiles::default()
+            }
+        };
+
+        let cpu = match load_cpu_profile(&profile_info) {
+            Ok(cpu) => cpu,
+            Err(err) => {
+                tracing::error!(
+                    "Failed to load CPU profile called `{}`: `{}`",
+                    profile_info.cpu,
                     err.to_string(),
                 );
-                FanProfile::default()
+                CpuProfile::default()
             }
         };
 
-        Self { fan, keyboard }
+        let performance_profile = parse_performance_profile(&profile_info.performance_profile)
+            .unwrap_or_else(|err| {
+                tracing::error!("Failed to load the performance profile: `{err}`");
+                None
+            });
+
+        let switches = parse_switches(&profile_info.switches).unwrap_or_else(|err| {
+            tracing::error!("Failed to load the switches: `{err}`");
+            SwitchStates::new()
+        });
+
+        Self {
+            fan,
+            keyboard,
+            keyboard_brightness: profile_info.keyboard_brightness,
+            cpu,
+            performance_profile,
+            switches,
+        }
     }
 
     pub async fn set_active_profile_name(name: &str) -> fdo::Result<()> {
-        std::fs::metadata(util::normalize_json_path(PROFILE_DIR, name)?)
+        let paths = paths();
+        std::fs::metadata(util::normalize_json_path(&paths.profile_dir, name)?)
             .map_err(|_| fdo::Error::FileNotFound(format!("Couldn't find profile `{name}`")))?;
 
-        std::fs::remove_file(ACTIVE_PROFILE_PATH)
+        std::fs::remove_file(&paths.active_profile_path)
             .map_err(|err| fdo::Error::IOError(err.to_string()))?;
         std::os::unix::fs::symlink(
             util::normalize_json_path("profiles", name)?,
-            ACTIVE_PROFILE_PATH,
+            &paths.active_profile_path,
         )
         .map_err(|err| fdo::Error::IOError(err.to_string()))
     }
 
     pub async fn get_active_profile_name() -> fdo::Result<String> {
-        let link = std::fs::read_link(ACTIVE_PROFILE_PATH)
+        let link = std::fs::read_link(&paths().active_profile_path)
             .map_err(|err| fdo::Error::IOError(err.to_string()))?;
         let components: Vec<Component> = link.components().collect();
         if components.len() == 2 {
@@ -111,7 +233,7 @@ impl Profile {
     }
 
     pub fn get_active_profile_info() -> fdo::Result<ProfileInfo> {
-        let data = std::fs::read(ACTIVE_PROFILE_PATH)
+        let data = std::fs::read(&paths().active_profile_path)
             .map_err(|err| fdo::Error::IOError(err.to_string()))?;
         serde_json::from_slice(&data).map_err(|err| fdo::Error::InvalidFileContent(err.to_string()))
     }
@@ -119,8 +241,81 @@ impl Profile {
     pub fn reload() -> fdo::Result<Self> {
         let profile_info: ProfileInfo = Self::get_active_profile_info()?;
         let keyboard = load_keyboard_profile(&profile_info)?;
-        let fan = load_fan_profile(&profile_info)?;
+        let fan = load_fan_profiles(&profile_info)?;
+        let cpu = load_cpu_profile(&profile_info)?;
+        let performance_profile = parse_performance_profile(&profile_info.performance_profile)
+            .map_err(|err| fdo::Error::InvalidFileContent(err.to_string()))?;
+        let switches = parse_switches(&profile_info.switches)?;
+
+        Ok(Self {
+            fan,
+            keyboard,
+            keyboard_brightness: profile_info.keyboard_brightness,
+            cpu,
+            performance_profile,
+            switches,
+        })
+    }
+}
+
+/// Load the rule for switching profiles on power changes.
+///
+/// Without a stored rule, profiles are never switched automatically.
+pub fn load_power_rule() -> fdo::Result<PowerRule> {
+    match std::fs::read(&paths().power_rule_path) {
+        Ok(data) => serde_json::from_slice(&data)
+            .map_err(|err| fdo::Error::InvalidFileContent(err.to_string())),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(PowerRule::default()),
+        Err(err) => Err(fdo::Error::IOError(err.to_string())),
+    }
+}
+
+pub async fn store_power_rule(rule: &PowerRule) -> fdo::Result<()> {
+    let paths = paths();
+    for name in rule.profiles() {
+        std::fs::metadata(util::normalize_json_path(&paths.profile_dir, name)?)
+            .map_err(|_| fdo::Error::FileNotFound(format!("Couldn't find profile `{name}`")))?;
+    }
+
+    let data =
+        serde_json::to_string_pretty(rule).map_err(|err| fdo::Error::Failed(err.to_string()))?;
+    tokio::fs::write(&paths.power_rule_path, data)
+        .await
+        .map_err(|err| fdo::Error::IOError(err.to_string()))
+}
+
+/// Keep the power rule pointing to a global profile after it was renamed.
+pub async fn rename_in_power_rule(from: &str, to: &str) -> fdo::Result<()> {
+    let mut rule = load_power_rule()?;
+    let mut changed = false;
+    for name in [&mut rule.ac, &mut rule.battery] {
+        if name == from {
+            *name = to.to_string();
+            changed = true;
+        }
+    }
+
+    if changed {
+        store_power_rule(&rule).await
+    } else {
+        Ok(())
+    }
+}
 
-        Ok(Self { fan, keyboard })
+/// Load the switch states that were set last.
+pub fn load_switches() -> fdo::Result<SwitchStates> {
+    match std::fs::read(&paths().switches_path) {
+        Ok(data) => serde_json::from_slice(&data)
+            .map_err(|err| fdo::Error::InvalidFileContent(err.to_string())),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(SwitchStates::new()),
+        Err(err) => Err(fdo::Error::IOError(err.to_string())),
     }
 }
+
+pub async fn store_switches(switches: &SwitchStates) -> fdo::Result<()> {
+    let data = serde_json::to_string_pretty(switches)
+        .map_err(|err| fdo::Error::Failed(err.to_string()))?;
+    tokio::fs::write(&paths().switches_path, data)
+        .await
+        .map_err(|err| fdo::Error::IOError(err.to_string()))
+}
@@ -0,0 +1,33 @@
+use std::sync::Arc;
+
+use crate::backend::IoBackend;
+
+/// Hand the fan control back to the firmware.
+///
+/// Manual fan speeds stay active after the daemon exits,
+/// so this must happen before tailord stops in any way.
+pub fn restore_fans(io: &dyn IoBackend) {
+    // The fans were never taken over if the device couldn't be opened.
+    if io.hw().is_err() {
+        return;
+    }
+    match io.set_fans_auto() {
+        Ok(()) => tracing::info!("Handed fan control back to the firmware"),
+        Err(err) => tracing::error!("Failed to hand fan control back to the firmware: `{err}`"),
+    }
+}
+
+/// Restore the fans if any part of the daemon panics.
+///
+/// A panic inside a spawned task would only stop that task and might leave
+/// the fans without a control loop, so the whole process is aborted instead.
+/// The keyboard isn't reset here because it can only be accessed
+/// from the async runtime.
+pub fn install_panic_hook(io: Arc<dyn IoBackend>) {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        default_hook(info);
+        restore_fans(io.as_ref());
+        std::process::abort();
+    }));
+}
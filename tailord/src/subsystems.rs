@@ -0,0 +1,163 @@
+use std::{collections::BTreeMap, fmt::Display, future::Future, time::Duration};
+
+use futures::FutureExt;
+use tailor_api::SubsystemStatus;
+use tokio::sync::{mpsc, watch};
+
+/// How often a missing device is looked for again.
+const RETRY_INTERVAL: Duration = if cfg!(test) {
+    Duration::from_millis(10)
+} else {
+    Duration::from_secs(5)
+};
+
+/// Parts of the daemon that start independently of each other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Subsystem {
+    /// Fans, switches and performance profiles.
+    Io,
+    Keyboard,
+    Cpu,
+}
+
+impl Subsystem {
+    pub const ALL: [Self; 3] = [Self::Io, Self::Keyboard, Self::Cpu];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Io => "tuxedo_io",
+            Self::Keyboard => "keyboard",
+            Self::Cpu => "cpu",
+        }
+    }
+}
+
+/// The error of each subsystem, which is `None` once it's running.
+pub type SubsystemStates = BTreeMap<Subsystem, Option<String>>;
+
+pub fn initial_states() -> SubsystemStates {
+    Subsystem::ALL
+        .into_iter()
+        .map(|subsystem| (subsystem, Some("Not started yet".to_string())))
+        .collect()
+}
+
+pub fn statuses(states: &SubsystemStates) -> Vec<SubsystemStatus> {
+    states
+        .iter()
+        .map(|(subsystem, error)| SubsystemStatus {
+            name: subsystem.name().to_string(),
+            available: error.is_none(),
+            error: error.clone().unwrap_or_default(),
+        })
+        .collect()
+}
+
+pub fn report_available(states: &watch::Sender<SubsystemStates>, subsystem: Subsystem) {
+    tracing::info!("The {} subsystem is available", subsystem.name());
+    states.send_modify(|states| {
+        states.insert(subsystem, None);
+    });
+}
+
+/// Open the device of a subsystem, retrying until it appears.
+///
+/// `idle` runs while the device is missing, so that it can keep
+/// the channels of the subsystem from filling up.
+pub async fn open_with_retry<T, E, Fut>(
+    states: &watch::Sender<SubsystemStates>,
+    subsystem: Subsystem,
+    mut open: impl FnMut() -> Fut,
+    idle: impl Future<Output = ()>,
+) -> T
+where
+    E: Display,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let idle = idle.fuse();
+    tokio::pin!(idle);
+
+    loop {
+        let err = match open().await {
+            Ok(device) => return device,
+            Err(err) => err.to_string(),
+        };
+
+        // Only log new errors to avoid flooding the log while retrying.
+        let changed = states.send_if_modified(|states| {
+            states.insert(subsystem, Some(err.clone())) != Some(Some(err.clone()))
+        });
+        if changed {
+            tracing::warn!(
+                "The {} subsystem isn't available, retrying every {:?}: `{err}`",
+                subsystem.name(),
+                RETRY_INTERVAL
+            );
+        }
+
+        tokio::select! {
+            _ = tokio::time::sleep(RETRY_INTERVAL) => {}
+            _ = &mut idle => {}
+        }
+    }
+}
+
+/// Keep the most recent value of a channel.
+pub async fn keep_latest<T>(receiver: &mut mpsc::Receiver<T>, latest: &mut T) {
+    while let Some(value) = receiver.recv().await {
+        *latest = value;
+    }
+}
+
+/// Drop values that can't be used without the device, for example overrides.
+pub async fn discard<T>(receiver: &mut mpsc::Receiver<T>) {
+    while receiver.recv().await.is_some() {}
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::Cell, future::pending};
+
+    use tokio::sync::{mpsc, watch};
+
+    use super::{initial_states, keep_latest, open_with_retry, statuses, Subsystem};
+
+    #[test]
+    fn retry() {
+        tokio_uring::start(async {
+            let (states, receiver) = watch::channel(initial_states());
+            let attempts = Cell::new(0);
+            let (sender, mut profiles) = mpsc::channel(1);
+            let mut profile = 0;
+
+            sender.send(1).await.unwrap();
+            let device = open_with_retry(
+                &states,
+                Subsystem::Keyboard,
+                || async {
+                    attempts.set(attempts.get() + 1);
+                    if attempts.get() < 2 {
+                        Err("missing")
+                    } else {
+                        Ok("keyboard")
+                    }
+                },
+                async {
+                    keep_latest(&mut profiles, &mut profile).await;
+                    pending().await
+                },
+            )
+            .await;
+
+            assert_eq!(device, "keyboard");
+            assert_eq!(attempts.get(), 2);
+            // The profile that was sent while waiting isn't lost.
+            assert_eq!(profile, 1);
+
+            let statuses = statuses(&receiver.borrow());
+            assert_eq!(statuses[1].name, "keyboard");
+            assert!(!statuses[1].available);
+            assert_eq!(statuses[1].error, "missing");
+        });
+    }
+}
@@ -1,8 +1,15 @@
-use std::{future::pending, time::Duration};
+use std::{future::pending, sync::Arc, time::Duration};
 
 use futures_lite::StreamExt;
-use tokio::sync::broadcast;
-use zbus::{dbus_proxy, Connection};
+use tokio::sync::broadcast::{self, error::RecvError};
+use zbus::{dbus_proxy, fdo, Connection};
+
+use crate::{
+    backend::IoBackend,
+    dbus::set_performance_profile,
+    profiles::{self, Profile},
+    switches::SwitchControl,
+};
 
 #[dbus_proxy(
     interface = "org.freedesktop.login1.Manager",
@@ -50,6 +57,42 @@ async fn try_wait_for_suspend(sender: &mut broadcast::Sender<bool>) -> Result<()
     Ok(())
 }
 
+/// Restore the settings that the firmware resets during suspend.
+///
+/// The fan and keyboard runtimes restore their own state.
+pub async fn restore_after_resume(
+    io: Arc<dyn IoBackend>,
+    switches: SwitchControl,
+    mut receiver: broadcast::Receiver<bool>,
+) {
+    loop {
+        wait_for_sleep(&mut receiver).await;
+        wait_for_wake_up(&mut receiver).await;
+
+        // Nothing was set if the device isn't open yet.
+        if io.hw().is_err() {
+            continue;
+        }
+        tracing::info!("Restoring the performance profile and switches after resume");
+
+        switches.restore().await;
+
+        let profile = Profile::get_active_profile_info().and_then(|info| {
+            profiles::parse_performance_profile(&info.performance_profile)
+                .map_err(|err| fdo::Error::InvalidFileContent(err.to_string()))
+        });
+        match profile {
+            Ok(Some(profile)) => {
+                if let Err(err) = set_performance_profile(io.as_ref(), profile) {
+                    tracing::error!("{err}");
+                }
+            }
+            Ok(None) => (),
+            Err(err) => tracing::error!("Failed to load the active profile: `{err}`"),
+        }
+    }
+}
+
 pub async fn process_suspend(receiver: &mut broadcast::Receiver<bool>) {
     match receiver.recv().await {
         Ok(msg) => {
@@ -68,7 +111,21 @@ pub async fn process_suspend(receiver: &mut broadcast::Receiver<bool>) {
     }
 }
 
-async fn wait_for_wake_up(receiver: &mut broadcast::Receiver<bool>) {
+/// Returns once the system is about to suspend.
+///
+/// Never returns if the suspend messages stop.
+pub async fn wait_for_sleep(receiver: &mut broadcast::Receiver<bool>) {
+    loop {
+        match receiver.recv().await {
+            Ok(true) => return,
+            Ok(false) => tracing::warn!("Wake up message without suspend."),
+            Err(RecvError::Lagged(_)) => (),
+            Err(RecvError::Closed) => pending::<()>().await,
+        }
+    }
+}
+
+pub async fn wait_for_wake_up(receiver: &mut broadcast::Receiver<bool>) {
     // Wait until wake up (suspend msg == false).
     loop {
         match receiver.recv().await {
@@ -80,6 +137,8 @@ async fn wait_for_wake_up(receiver: &mut broadcast::Receiver<bool>) {
                     return;
                 }
             }
+            // Nobody will send a wake-up message anymore.
+            Err(RecvError::Closed) => return,
             Err(err) => {
                 tracing::error!("Error receiving wake-up message: `{err}`");
             }
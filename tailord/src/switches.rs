@@ -0,0 +1,115 @@
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
+
+use tailor_api::Switch;
+use tuxedo_ioctl::IoctlError;
+use zbus::fdo;
+
+use crate::{backend::IoBackend, profiles};
+
+pub type SwitchStates = BTreeMap<Switch, bool>;
+
+fn read_switch(io: &dyn IoBackend, switch: Switch) -> Result<bool, IoctlError> {
+    match switch {
+        Switch::Webcam => io.get_web_cam_enabled(),
+        Switch::Touchpad => io.get_touchpad_enabled(),
+        Switch::FlightMode => io.get_flight_mode(),
+    }
+}
+
+fn write_switch(io: &dyn IoBackend, switch: Switch, enabled: bool) -> Result<(), IoctlError> {
+    match switch {
+        Switch::Webcam => io.set_web_cam_enabled(enabled),
+        Switch::Touchpad => io.set_touchpad_enabled(enabled),
+        Switch::FlightMode => io.set_flight_mode(enabled),
+    }
+}
+
+/// Toggles the hardware switches and remembers their states across reboots.
+///
+/// No switches are available until [`SwitchControl::detect`] found them.
+#[derive(Clone)]
+pub struct SwitchControl {
+    io: Arc<dyn IoBackend>,
+    states: Arc<Mutex<SwitchStates>>,
+}
+
+impl SwitchControl {
+    pub fn new(io: Arc<dyn IoBackend>) -> Self {
+        Self {
+            io,
+            states: Arc::default(),
+        }
+    }
+
+    /// Read the current states, switches that can't
+    /// be read aren't available on the device.
+    pub fn detect(&self) {
+        let states = Switch::ALL
+            .into_iter()
+            .filter_map(|switch| Some((switch, read_switch(self.io.as_ref(), switch).ok()?)))
+            .collect();
+        *self.states.lock().unwrap() = states;
+    }
+
+    pub fn available(&self) -> Vec<Switch> {
+        self.states.lock().unwrap().keys().copied().collect()
+    }
+
+    /// `None` if the switch isn't available.
+    pub fn get(&self, switch: Switch) -> Option<bool> {
+        self.states.lock().unwrap().get(&switch).copied()
+    }
+
+    /// Set a switch and store its new state.
+    ///
+    /// Returns `true` if the state changed.
+    pub async fn set(&self, switch: Switch, enabled: bool) -> fdo::Result<bool> {
+        if self.get(switch).is_none() {
+            return Err(fdo::Error::NotSupported(format!(
+                "The {switch} switch isn't available"
+            )));
+        }
+        write_switch(self.io.as_ref(), switch, enabled).map_err(|err| {
+            fdo::Error::Failed(format!("Failed to set the {switch} switch: `{err}`"))
+        })?;
+
+        let (changed, states) = {
+            let mut states = self.states.lock().unwrap();
+            let changed = states.insert(switch, enabled) != Some(enabled);
+            (changed, states.clone())
+        };
+        profiles::store_switches(&states).await?;
+        Ok(changed)
+    }
+
+    /// Set all given switches, continuing after errors.
+    ///
+    /// Returns the switches that changed.
+    pub async fn apply(&self, states: &SwitchStates) -> SwitchStates {
+        let mut changed = SwitchStates::new();
+        for (switch, enabled) in states {
+            tracing::info!("Setting the {switch} switch to {enabled}");
+            match self.set(*switch, *enabled).await {
+                Ok(true) => {
+                    changed.insert(*switch, *enabled);
+                }
+                Ok(false) => (),
+                Err(err) => tracing::error!("{err}"),
+            }
+        }
+        changed
+    }
+
+    /// Restore the switch states from the last run.
+    pub async fn restore(&self) {
+        match profiles::load_switches() {
+            Ok(states) => {
+                self.apply(&states).await;
+            }
+            Err(err) => tracing::error!("Failed to load the switch states: `{err}`"),
+        }
+    }
+}
@@ -1,3 +1,7 @@
+use std::path::Path;
+
+use crate::error::IoctlError;
+
 pub const TUXEDO_IO_DEVICE_FILE: &str = "/dev/tuxedo_io";
 
 pub const IOCTL_MAGIC: u8 = 0xEC;
@@ -10,9 +14,11 @@ pub const MAGIC_WRITE_CL: u8 = IOCTL_MAGIC + 2;
 pub const MAGIC_READ_UW: u8 = IOCTL_MAGIC + 3;
 pub const MAGIC_WRITE_UW: u8 = IOCTL_MAGIC + 4;
 
-pub fn open_device_file() -> Result<std::fs::File, std::io::Error> {
+pub fn open_device_file(path: impl AsRef<Path>) -> Result<std::fs::File, IoctlError> {
+    let path = path.as_ref();
     std::fs::OpenOptions::new()
         .read(true)
         .write(true)
-        .open(TUXEDO_IO_DEVICE_FILE)
+        .open(path)
+        .map_err(|err| IoctlError::from_open(path.to_path_buf(), err))
 }
@@ -1,4 +1,4 @@
-use std::string::FromUtf8Error;
+use std::{io, path::PathBuf, string::FromUtf8Error};
 
 use nix::errno::Errno;
 
@@ -8,6 +8,33 @@ pub enum IoctlError {
     Utf8(#[from] FromUtf8Error),
     #[error(transparent)]
     Read(#[from] Errno),
-    #[error("Device not available")]
-    DevNotAvailable,
+    #[error("The device file {} doesn't exist, is the tuxedo_io kernel module loaded?", .0.display())]
+    DeviceMissing(PathBuf),
+    #[error("Permission denied for the device file {}, tailord needs to run as root", .0.display())]
+    PermissionDenied(PathBuf),
+    #[error("The tuxedo_io kernel module found neither a Clevo nor a Uniwill platform")]
+    UnknownPlatform,
+    #[error(
+        "The tuxedo_io kernel module (version {0}) doesn't understand the requests of this program"
+    )]
+    DriverMismatch(String),
+    #[error("Not supported on this platform")]
+    Unsupported,
+    #[error("Sensor not available")]
+    SensorNotAvailable,
+}
+
+impl IoctlError {
+    /// Classify an error that occurred while opening the device file.
+    pub(crate) fn from_open(path: PathBuf, err: io::Error) -> Self {
+        match err.kind() {
+            io::ErrorKind::NotFound => Self::DeviceMissing(path),
+            io::ErrorKind::PermissionDenied => Self::PermissionDenied(path),
+            _ => Self::Read(
+                err.raw_os_error()
+                    .map(Errno::from_i32)
+                    .unwrap_or(Errno::UnknownErrno),
+            ),
+        }
+    }
 }
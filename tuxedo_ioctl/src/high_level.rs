@@ -1,8 +1,17 @@
-use std::fs::File;
+use std::{fmt, fs::File, path::Path, str::FromStr, sync::Mutex};
 
-use crate::{config::open_device_file, error::IoctlError, read, write};
+use nix::errno::Errno;
 
-pub const MAX_FAN_SPEED: u8 = 0xff;
+use crate::{
+    config::{open_device_file, TUXEDO_IO_DEVICE_FILE},
+    error::IoctlError,
+    read, write,
+};
+
+/// Raw fan speed at 100 % on Clevo devices.
+pub const CLEVO_MAX_FAN_SPEED: u8 = 0xff;
+/// Raw fan speed at 100 % on Uniwill devices.
+pub const UNIWILL_MAX_FAN_SPEED: u8 = 0xc8;
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum Fan {
@@ -20,54 +29,185 @@ impl Fan {
             _ => None,
         }
     }
+
+    pub fn as_u8(self) -> u8 {
+        match self {
+            Self::Fan1 => 0,
+            Self::Fan2 => 1,
+            Self::Fan3 => 2,
+        }
+    }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+// The Uniwill mode register is the EC register 0x0751. The bits are the
+// same that `tuxedo_io` uses in `uw_set_performance_profile_v1`, `uw_set_fan`
+// and `uw_set_fan_auto` of the TUXEDO kernel drivers.
+
+/// Bits of the Uniwill mode register that select the performance mode.
+const UW_MODE_PERFORMANCE_MASK: u32 = 0xb0;
+/// The "full fan mode" bit of the Uniwill mode register.
+///
+/// The driver sets it itself when the first fan speed is written and
+/// keeps the fans from ramping up while doing so. Setting it without
+/// writing a speed runs the fans at full speed.
+const UW_MODE_FULL_FAN: u32 = 0x40;
+
+/// Platform performance profiles.
+///
+/// Clevo and Uniwill devices support different sets of profiles,
+/// see [`Hw::performance_profiles`].
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
 pub enum PerformanceProfile {
     Quiet,
     Powersave,
     Entertainment,
+    #[default]
     Performance,
+    /// The balanced profile of Uniwill devices.
+    Enthusiast,
+    /// The fastest profile of Uniwill devices.
+    Overboost,
 }
 
-impl Default for PerformanceProfile {
-    fn default() -> Self {
-        Self::Performance
+impl PerformanceProfile {
+    pub const ALL: [Self; 6] = [
+        Self::Quiet,
+        Self::Powersave,
+        Self::Entertainment,
+        Self::Performance,
+        Self::Enthusiast,
+        Self::Overboost,
+    ];
+
+    /// The lowercase name that is used in configuration files.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PerformanceProfile::Quiet => "quiet",
+            PerformanceProfile::Powersave => "powersave",
+            PerformanceProfile::Entertainment => "entertainment",
+            PerformanceProfile::Performance => "performance",
+            PerformanceProfile::Enthusiast => "enthusiast",
+            PerformanceProfile::Overboost => "overboost",
+        }
+    }
+
+    fn as_clevo_arg(&self) -> Option<u32> {
+        match self {
+            PerformanceProfile::Quiet => Some(0x00),
+            PerformanceProfile::Powersave => Some(0x01),
+            PerformanceProfile::Entertainment => Some(0x02),
+            PerformanceProfile::Performance => Some(0x03),
+            _ => None,
+        }
     }
-}
 
-impl PerformanceProfile {
-    fn as_clevo_arg(&self) -> u32 {
+    /// Returns the bits of the Uniwill mode register that select this profile,
+    /// which are the profiles 1 to 3 of `uw_set_performance_profile_v1`.
+    fn as_uniwill_mode(&self) -> Option<u32> {
         match self {
-            PerformanceProfile::Quiet => 0x00,
-            PerformanceProfile::Powersave => 0x01,
-            PerformanceProfile::Entertainment => 0x02,
-            PerformanceProfile::Performance => 0x03,
+            PerformanceProfile::Powersave => Some(0xa0),
+            PerformanceProfile::Enthusiast => Some(0x00),
+            PerformanceProfile::Overboost => Some(0x10),
+            _ => None,
+        }
+    }
+
+    fn from_uniwill_mode(mode: u32) -> Option<Self> {
+        match mode & UW_MODE_PERFORMANCE_MASK {
+            0xa0 => Some(PerformanceProfile::Powersave),
+            0x00 => Some(PerformanceProfile::Enthusiast),
+            0x10 => Some(PerformanceProfile::Overboost),
+            _ => None,
         }
     }
 }
 
-#[derive(Debug)]
+impl fmt::Display for PerformanceProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+#[error("Unknown performance profile `{0}`")]
+pub struct UnknownPerformanceProfile(pub String);
+
+impl FromStr for PerformanceProfile {
+    type Err = UnknownPerformanceProfile;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|profile| profile.as_str() == s)
+            .ok_or_else(|| UnknownPerformanceProfile(s.to_string()))
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum Hw {
     Clevo,
     Uniwill,
 }
 
 impl Hw {
-    pub fn new() -> Result<Self, std::io::Error> {
-        let file = open_device_file()?;
+    /// Returns the raw fan speed that corresponds to 100 %.
+    pub fn max_fan_speed(self) -> u8 {
+        match self {
+            Hw::Clevo => CLEVO_MAX_FAN_SPEED,
+            Hw::Uniwill => UNIWILL_MAX_FAN_SPEED,
+        }
+    }
 
-        if let Ok(value) = read::hwcheck_cl(&file) {
-            if value == 1 {
-                return Ok(Hw::Clevo);
-            }
+    /// Returns the performance profiles that are supported by the platform.
+    pub fn performance_profiles(self) -> &'static [PerformanceProfile] {
+        match self {
+            Hw::Clevo => &[
+                PerformanceProfile::Quiet,
+                PerformanceProfile::Powersave,
+                PerformanceProfile::Entertainment,
+                PerformanceProfile::Performance,
+            ],
+            Hw::Uniwill => &[
+                PerformanceProfile::Powersave,
+                PerformanceProfile::Enthusiast,
+                PerformanceProfile::Overboost,
+            ],
         }
-        if let Ok(value) = read::hwcheck_uw(&file) {
-            if value == 1 {
-                return Ok(Hw::Uniwill);
-            }
+    }
+
+    pub fn new() -> Result<Self, IoctlError> {
+        let file = open_device_file(TUXEDO_IO_DEVICE_FILE)?;
+        Self::detect(&file)
+    }
+
+    fn detect(file: &File) -> Result<Self, IoctlError> {
+        let clevo = read::hwcheck_cl(file);
+        if clevo == Ok(1) {
+            return Ok(Hw::Clevo);
+        }
+        let uniwill = read::hwcheck_uw(file);
+        if uniwill == Ok(1) {
+            return Ok(Hw::Uniwill);
+        }
+        Err(Self::classify_detection_failure(file, clevo, uniwill))
+    }
+
+    /// A driver that rejects both checks as unknown requests
+    /// was built for a different ioctl interface.
+    fn classify_detection_failure(
+        file: &File,
+        clevo: Result<u32, IoctlError>,
+        uniwill: Result<u32, IoctlError>,
+    ) -> IoctlError {
+        let unknown_request = Err(IoctlError::Read(Errno::ENOTTY));
+        if clevo == unknown_request && uniwill == unknown_request {
+            let version = read::mod_version(file)
+                .map(|version| version.trim_end_matches('\0').trim().to_string())
+                .unwrap_or_else(|_| "unknown".to_string());
+            IoctlError::DriverMismatch(version)
+        } else {
+            IoctlError::UnknownPlatform
         }
-        panic!("Platform not supported yet");
     }
 }
 
@@ -75,17 +215,38 @@ impl Hw {
 pub struct IoInterface {
     file: File,
     hw: Hw,
+    /// The performance profile can't be read back, so remember the last one that was set.
+    performance_profile: Mutex<Option<PerformanceProfile>>,
 }
 
 impl IoInterface {
-    pub fn new() -> Result<Self, std::io::Error> {
-        let file = open_device_file()?;
-        let hw = Hw::new()?;
-        Ok(Self { file, hw })
+    pub fn new() -> Result<Self, IoctlError> {
+        Self::with_device_file(TUXEDO_IO_DEVICE_FILE)
+    }
+
+    /// Use a different device file than `/dev/tuxedo_io`.
+    pub fn with_device_file(path: impl AsRef<Path>) -> Result<Self, IoctlError> {
+        let file = open_device_file(path)?;
+        let hw = Hw::detect(&file)?;
+        Ok(Self {
+            file,
+            hw,
+            performance_profile: Mutex::new(None),
+        })
+    }
+
+    pub fn hw(&self) -> Hw {
+        self.hw
+    }
+
+    /// Returns the version of the `tuxedo_io` kernel module.
+    pub fn get_module_version(&self) -> Result<String, IoctlError> {
+        let version = read::mod_version(&self.file)?;
+        Ok(version.trim_end_matches('\0').trim().to_string())
     }
 
     fn set_fan_speed_percent_clevo(
-        &mut self,
+        &self,
         fan: Fan,
         fan_speed_percent: u8,
     ) -> Result<(), IoctlError> {
@@ -96,10 +257,9 @@ impl IoInterface {
         for (i, fan_speed) in fan_speed_raw.iter_mut().enumerate() {
             let selected_fan = Fan::try_from_u8(i as u8).unwrap();
             if selected_fan == fan {
-                *fan_speed =
-                    (fan_speed_percent as f64 * MAX_FAN_SPEED as f64 / 100.0).round() as u8;
+                *fan_speed = fan_speed_to_raw(fan_speed_percent, CLEVO_MAX_FAN_SPEED);
             } else {
-                *fan_speed = self.read_fanspeed_raw(fan)?;
+                *fan_speed = self.read_fanspeed_raw(selected_fan)?;
             }
         }
 
@@ -111,23 +271,22 @@ impl IoInterface {
     }
 
     fn set_fan_speed_percent_uniwill(
-        &mut self,
-        _fan: Fan,
+        &self,
+        fan: Fan,
         fan_speed_percent: u8,
     ) -> Result<(), IoctlError> {
-        let fan_speed_raw =
-            (fan_speed_percent as f64 * MAX_FAN_SPEED as f64 / 100.0).round() as u32;
-        write::uw_fanspeed(&self.file, fan_speed_raw)?;
-        write::uw_fanspeed2(&self.file, fan_speed_raw)
+        let fan_speed_percent = fan_speed_percent.clamp(0, 100);
+        let fan_speed_raw = fan_speed_to_raw(fan_speed_percent, UNIWILL_MAX_FAN_SPEED) as u32;
+        match fan {
+            Fan::Fan1 => write::uw_fanspeed(&self.file, fan_speed_raw),
+            Fan::Fan2 => write::uw_fanspeed2(&self.file, fan_speed_raw),
+            Fan::Fan3 => Err(IoctlError::SensorNotAvailable),
+        }
     }
 
     /// Set the fan speed in percent from 0 to 100.
     /// Values above 100 will be clamped to 100.
-    pub fn set_fan_speed_percent(
-        &mut self,
-        fan: Fan,
-        fan_speed_percent: u8,
-    ) -> Result<(), IoctlError> {
+    pub fn set_fan_speed_percent(&self, fan: Fan, fan_speed_percent: u8) -> Result<(), IoctlError> {
         match self.hw {
             Hw::Clevo => self.set_fan_speed_percent_clevo(fan, fan_speed_percent)?,
             Hw::Uniwill => self.set_fan_speed_percent_uniwill(fan, fan_speed_percent)?,
@@ -137,12 +296,17 @@ impl IoInterface {
 
     pub fn get_fan_speed_percent(&self, fan: Fan) -> Result<u8, IoctlError> {
         let fan_speed_raw = self.read_fanspeed_raw(fan)?;
-        Ok(((fan_speed_raw as f64 / MAX_FAN_SPEED as f64) * 100.0).round() as u8)
+        Ok(fan_speed_from_raw(fan_speed_raw, self.hw.max_fan_speed()))
     }
 
     fn read_fanspeed_raw(&self, fan: Fan) -> Result<u8, IoctlError> {
-        self.read_faninfo_raw(fan)
-            .map(|value| (value & 0xFF).try_into().unwrap())
+        let value = match (&self.hw, fan) {
+            (Hw::Clevo, _) => self.read_faninfo_raw(fan)?,
+            (Hw::Uniwill, Fan::Fan1) => read::uw_fanspeed(&self.file)?,
+            (Hw::Uniwill, Fan::Fan2) => read::uw_fanspeed2(&self.file)?,
+            (Hw::Uniwill, Fan::Fan3) => return Err(IoctlError::SensorNotAvailable),
+        };
+        Ok((value & 0xFF).try_into().unwrap())
     }
 
     fn read_faninfo_raw_1(&self) -> Result<u32, IoctlError> {
@@ -161,7 +325,7 @@ impl IoInterface {
     fn read_faninfo_raw_3(&self) -> Result<u32, IoctlError> {
         match self.hw {
             Hw::Clevo => read::cl_faninfo3(&self.file),
-            Hw::Uniwill => Err(IoctlError::DevNotAvailable),
+            Hw::Uniwill => Err(IoctlError::SensorNotAvailable),
         }
     }
 
@@ -173,37 +337,112 @@ impl IoInterface {
         }
     }
 
+    /// Update the mode register of Uniwill devices.
+    ///
+    /// The register is only evaluated by the firmware
+    /// while setting the mode is enabled.
+    fn update_uniwill_mode(&self, update: impl FnOnce(u32) -> u32) -> Result<(), IoctlError> {
+        if read::uw_mode_enable(&self.file)? & 0x01 == 0 {
+            write::uw_mode_enable(&self.file, 0x01)?;
+        }
+        let mode = read::uw_mode(&self.file)?;
+        let new_mode = update(mode) & 0xff;
+        if new_mode != mode {
+            write::uw_mode(&self.file, new_mode)?;
+        }
+        Ok(())
+    }
+
+    /// Hand the fan control back to the firmware.
     pub fn set_fans_auto(&self) -> Result<(), IoctlError> {
         match &self.hw {
             Hw::Clevo => write::cl_fanauto(&self.file, 0xF),
-            other => {
-                tracing::warn!("set_fans_auto(): {other:?} not supported");
-                Ok(())
-            }
+            // The driver clears the full fan bit of the mode register.
+            Hw::Uniwill => write::uw_fanauto(&self.file),
         }
     }
 
-    // I'm not sure if that works though...
+    /// Take over the fan control from the firmware.
     pub fn set_fans_manual(&self) -> Result<(), IoctlError> {
         match &self.hw {
             Hw::Clevo => write::cl_fanauto(&self.file, 0x0),
-            other => {
-                tracing::warn!("set_fans_manual(): {other:?} not supported");
-                Ok(())
-            }
+            // The driver enters the full fan mode when the first speed is set.
+            Hw::Uniwill => self.update_uniwill_mode(uniwill_manual_fan_mode),
+        }
+    }
+
+    /// The switches are only available on Clevo devices.
+    fn clevo_only(&self) -> Result<(), IoctlError> {
+        match self.hw {
+            Hw::Clevo => Ok(()),
+            Hw::Uniwill => Err(IoctlError::Unsupported),
         }
     }
 
     pub fn set_web_cam_enabled(&self, status: bool) -> Result<(), IoctlError> {
+        self.clevo_only()?;
         write::cl_webcam_sw(&self.file, u32::from(status))
     }
 
     pub fn get_web_cam_enabled(&self) -> Result<bool, IoctlError> {
+        self.clevo_only()?;
         read::cl_webcam_sw(&self.file).map(|val| val != 0)
     }
 
+    pub fn set_touchpad_enabled(&self, status: bool) -> Result<(), IoctlError> {
+        self.clevo_only()?;
+        write::cl_touchpad_sw(&self.file, u32::from(status))
+    }
+
+    pub fn get_touchpad_enabled(&self) -> Result<bool, IoctlError> {
+        self.clevo_only()?;
+        read::cl_touchpad_sw(&self.file).map(|val| val != 0)
+    }
+
+    /// Enabling the flight mode turns off all wireless devices.
+    pub fn set_flight_mode(&self, status: bool) -> Result<(), IoctlError> {
+        self.clevo_only()?;
+        write::cl_flightmode_sw(&self.file, u32::from(status))
+    }
+
+    pub fn get_flight_mode(&self) -> Result<bool, IoctlError> {
+        self.clevo_only()?;
+        read::cl_flightmode_sw(&self.file).map(|val| val != 0)
+    }
+
+    /// Returns the performance profiles that are supported by the device.
+    pub fn available_performance_profiles(&self) -> Vec<PerformanceProfile> {
+        self.hw.performance_profiles().to_vec()
+    }
+
+    /// Fails with [`IoctlError::Unsupported`] if the
+    /// profile isn't supported by the device.
     pub fn set_performance_profile(&self, profile: PerformanceProfile) -> Result<(), IoctlError> {
-        write::cl_perf_profile(&self.file, profile.as_clevo_arg())
+        match self.hw {
+            Hw::Clevo => {
+                let arg = profile.as_clevo_arg().ok_or(IoctlError::Unsupported)?;
+                write::cl_perf_profile(&self.file, arg)?;
+                *self.performance_profile.lock().unwrap() = Some(profile);
+                Ok(())
+            }
+            Hw::Uniwill => {
+                let bits = profile.as_uniwill_mode().ok_or(IoctlError::Unsupported)?;
+                self.update_uniwill_mode(|mode| (mode & !UW_MODE_PERFORMANCE_MASK) | bits)
+            }
+        }
+    }
+
+    /// Returns the current performance profile.
+    ///
+    /// Clevo devices offer no way to read the active profile, so this
+    /// is `None` until a profile was set through this interface.
+    pub fn get_performance_profile(&self) -> Result<Option<PerformanceProfile>, IoctlError> {
+        match self.hw {
+            Hw::Clevo => Ok(*self.performance_profile.lock().unwrap()),
+            Hw::Uniwill => Ok(PerformanceProfile::from_uniwill_mode(read::uw_mode(
+                &self.file,
+            )?)),
+        }
     }
 
     pub fn get_fan_temperature(&self, fan: Fan) -> Result<u8, IoctlError> {
@@ -215,13 +454,30 @@ impl IoInterface {
 
         // If a fan is not available a low value is read out
         if fan_info_raw <= 1 {
-            Err(IoctlError::DevNotAvailable)
+            Err(IoctlError::SensorNotAvailable)
         } else {
             Ok(fan_info_raw as u8)
         }
     }
 }
 
+fn fan_speed_to_raw(fan_speed_percent: u8, max_fan_speed: u8) -> u8 {
+    (fan_speed_percent.min(100) as f64 * max_fan_speed as f64 / 100.0).round() as u8
+}
+
+/// Raw values above the maximum are reported as 100 %.
+fn fan_speed_from_raw(fan_speed_raw: u8, max_fan_speed: u8) -> u8 {
+    ((fan_speed_raw.min(max_fan_speed) as f64 / max_fan_speed as f64) * 100.0).round() as u8
+}
+
+/// Returns the mode register before the fans are taken over.
+///
+/// The full fan bit must be clear, otherwise the fans spin up to
+/// 100 % and the driver skips its ramp-up prevention for the first speed.
+fn uniwill_manual_fan_mode(mode: u32) -> u32 {
+    mode & !UW_MODE_FULL_FAN
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -230,24 +486,29 @@ mod test {
     fn interface() {
         sudo::escalate_if_needed().unwrap();
 
-        let mut io = IoInterface::new().unwrap();
+        let io = IoInterface::new().unwrap();
+        assert!(io.get_module_version().unwrap().contains("0.2"));
 
         // Check webcam
         io.set_web_cam_enabled(false).unwrap();
-        assert_eq!(io.get_web_cam_enabled().unwrap(), false);
+        assert!(!io.get_web_cam_enabled().unwrap());
 
         io.set_web_cam_enabled(true).unwrap();
-        assert_eq!(io.get_web_cam_enabled().unwrap(), true);
+        assert!(io.get_web_cam_enabled().unwrap());
 
         // Set performance profile
         io.set_performance_profile(PerformanceProfile::Quiet)
             .unwrap();
+        assert_eq!(
+            io.get_performance_profile().unwrap(),
+            Some(PerformanceProfile::Quiet)
+        );
 
         // Get temperatures
         assert!(20 < io.get_fan_temperature(Fan::Fan1).unwrap());
         assert_eq!(
             io.get_fan_temperature(Fan::Fan2).unwrap_err(),
-            IoctlError::DevNotAvailable
+            IoctlError::SensorNotAvailable
         );
 
         // Check fans
@@ -263,4 +524,72 @@ mod test {
         io.set_fan_speed_percent(Fan::Fan1, 100).unwrap();
         assert_eq!(io.get_fan_speed_percent(Fan::Fan1).unwrap(), 100);
     }
+
+    #[test]
+    fn detection_errors() {
+        assert_eq!(
+            IoInterface::with_device_file("/nonexistent/tuxedo_io").unwrap_err(),
+            IoctlError::DeviceMissing("/nonexistent/tuxedo_io".into())
+        );
+        // Any device that isn't tuxedo_io rejects the ioctl requests.
+        assert_eq!(
+            IoInterface::with_device_file("/dev/null").unwrap_err(),
+            IoctlError::DriverMismatch("unknown".to_string())
+        );
+    }
+
+    #[test]
+    fn performance_profile_names() {
+        for profile in PerformanceProfile::ALL {
+            assert_eq!(profile.to_string().parse(), Ok(profile));
+        }
+        assert_eq!(
+            "turbo".parse::<PerformanceProfile>(),
+            Err(UnknownPerformanceProfile("turbo".to_string()))
+        );
+    }
+
+    #[test]
+    fn fan_speed_conversion() {
+        for hw in [Hw::Clevo, Hw::Uniwill] {
+            let max = hw.max_fan_speed();
+            assert_eq!(fan_speed_to_raw(0, max), 0);
+            assert_eq!(fan_speed_to_raw(100, max), max);
+            assert_eq!(fan_speed_to_raw(150, max), max);
+            for percent in 0..=100 {
+                let raw = fan_speed_to_raw(percent, max);
+                assert_eq!(fan_speed_from_raw(raw, max), percent);
+            }
+        }
+        assert_eq!(fan_speed_to_raw(50, UNIWILL_MAX_FAN_SPEED), 100);
+        assert_eq!(fan_speed_from_raw(0xff, UNIWILL_MAX_FAN_SPEED), 100);
+        assert_eq!(fan_speed_to_raw(50, CLEVO_MAX_FAN_SPEED), 128);
+    }
+
+    #[test]
+    fn uniwill_mode() {
+        for profile in Hw::Uniwill.performance_profiles() {
+            let bits = profile.as_uniwill_mode().unwrap();
+            assert_eq!(bits & !UW_MODE_PERFORMANCE_MASK, 0);
+            // The full fan bit doesn't change the profile.
+            let mode = bits | UW_MODE_FULL_FAN;
+            assert_eq!(PerformanceProfile::from_uniwill_mode(mode), Some(*profile));
+
+            // Taking over the fans keeps the profile
+            // but leaves the full fan bit clear.
+            for mode in [bits, mode] {
+                let manual = uniwill_manual_fan_mode(mode);
+                assert_eq!(manual & UW_MODE_FULL_FAN, 0);
+                assert_eq!(
+                    PerformanceProfile::from_uniwill_mode(manual),
+                    Some(*profile)
+                );
+            }
+        }
+        for profile in Hw::Clevo.performance_profiles() {
+            assert!(profile.as_clevo_arg().is_some());
+        }
+        assert_eq!(PerformanceProfile::Quiet.as_uniwill_mode(), None);
+        assert_eq!(PerformanceProfile::from_uniwill_mode(0x80), None);
+    }
 }
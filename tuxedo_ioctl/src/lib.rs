@@ -3,3 +3,6 @@ mod error;
 pub mod high_level;
 mod read;
 mod write;
+
+pub use config::TUXEDO_IO_DEVICE_FILE;
+pub use error::IoctlError;
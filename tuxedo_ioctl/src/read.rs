@@ -78,6 +78,8 @@ ioctl_read_int!(hwcheck_cl, IOCTL_MAGIC, 0x05);
 ioctl_read_int!(hwcheck_uw, IOCTL_MAGIC, 0x06);
 
 // Read clevo
+// Only the hardware tests check how the driver talks to the device.
+#[cfg(test)]
 ioctl_read_string!(cl_hw_interface_id, MAGIC_READ_CL, 0x00);
 ioctl_read_int!(cl_faninfo1, MAGIC_READ_CL, 0x10);
 ioctl_read_int!(cl_faninfo2, MAGIC_READ_CL, 0x11);
@@ -99,13 +101,13 @@ ioctl_read_int!(uw_mode_enable, MAGIC_READ_UW, 0x15);
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::config::open_device_file;
+    use crate::config::{open_device_file, TUXEDO_IO_DEVICE_FILE};
 
     #[test]
     fn test_cl_read() {
         sudo::escalate_if_needed().unwrap();
 
-        let file = open_device_file().unwrap();
+        let file = open_device_file(TUXEDO_IO_DEVICE_FILE).unwrap();
         assert!(mod_version(&file).unwrap().contains("0.2"));
 
         assert_eq!(hwcheck_cl(&file).unwrap(), 1);
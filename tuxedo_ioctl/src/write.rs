@@ -16,6 +16,14 @@ fn write_int(file: &File, data: u32, request_code: c_ulong) -> Result<(), IoctlE
     Ok(())
 }
 
+fn write_none(file: &File, request_code: c_ulong) -> Result<(), IoctlError> {
+    let fd = file.as_raw_fd();
+
+    let res = unsafe { ioctl(fd, request_code) };
+    let _ = Errno::result(res)?;
+    Ok(())
+}
+
 macro_rules! ioctl_write_int {
     ($name:ident, $id:expr, $seq:expr) => {
         pub fn $name(
@@ -30,6 +38,18 @@ macro_rules! ioctl_write_int {
     };
 }
 
+macro_rules! ioctl_write_none {
+    ($name:ident, $id:expr, $seq:expr) => {
+        pub fn $name(
+            file: &::std::fs::File,
+        ) -> ::std::result::Result<(), crate::error::IoctlError> {
+            let request_code = ::nix::request_code_none!($id, $seq);
+
+            write_none(file, request_code)
+        }
+    };
+}
+
 // Write clevo
 ioctl_write_int!(cl_fanspeed, MAGIC_WRITE_CL, 0x10);
 ioctl_write_int!(cl_fanauto, MAGIC_WRITE_CL, 0x11);
@@ -44,3 +64,4 @@ ioctl_write_int!(uw_fanspeed, MAGIC_WRITE_UW, 0x10);
 ioctl_write_int!(uw_fanspeed2, MAGIC_WRITE_UW, 0x11);
 ioctl_write_int!(uw_mode, MAGIC_WRITE_UW, 0x12);
 ioctl_write_int!(uw_mode_enable, MAGIC_WRITE_UW, 0x13);
+ioctl_write_none!(uw_fanauto, MAGIC_WRITE_UW, 0x14);
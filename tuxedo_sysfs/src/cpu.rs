@@ -1,48 +1,413 @@
-use std::io;
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
 
-use crate::sys_fs_read_separated;
+use crate::{
+    sys_fs_read, sys_fs_read_cpu_list, sys_fs_read_words, sys_fs_write, NumBool, SysFsRead,
+    SysFsType, SysFsWrite, SYS_FS_ROOT,
+};
 
 use super::sys_fs_type;
 
-sys_fs_type!(CPU, RO, u32, KernelMax, "kernel_max");
-sys_fs_type!(CPU, RO, Vec<u8>, Offline, "offline");
-sys_fs_type!(CPU, RO, Vec<u8>, Online, "online");
-sys_fs_type!(CPU, RO, Vec<u8>, Possible, "possible");
-sys_fs_type!(CPU, RO, Vec<u8>, Present, "present");
-//cpu_subpath!(INTELPSTATE, "intel_pstate");
-sys_fs_type!(CPU, RO, bool, Boost, "cpufreq/boost");
-
-pub struct CpuDriver {
-    kernel_max: KernelMax,
-    offline: Offline,
+sys_fs_type!(CPU, RO, Vec<u32>, Online, "online");
+sys_fs_type!(CPU, RO, Vec<u32>, Possible, "possible");
+sys_fs_type!(CPU, RO, Vec<u32>, Present, "present");
+// Used by acpi-cpufreq and amd-pstate.
+sys_fs_type!(CPU, RW, NumBool, Boost, "cpufreq/boost");
+// Used by intel_pstate, note that the value is inverted.
+sys_fs_type!(CPU, RW, NumBool, NoTurbo, "intel_pstate/no_turbo");
+
+sys_fs_type!(CORE, RW, NumBool, CoreOnline, "online");
+sys_fs_type!(CORE, RO, String, ScalingDriver, "cpufreq/scaling_driver");
+sys_fs_type!(
+    CORE,
+    RW,
+    String,
+    ScalingGovernor,
+    "cpufreq/scaling_governor"
+);
+sys_fs_type!(
+    CORE,
+    RO,
+    Vec<String>,
+    AvailableGovernors,
+    "cpufreq/scaling_available_governors"
+);
+sys_fs_type!(CORE, RW, u32, ScalingMinFreq, "cpufreq/scaling_min_freq");
+sys_fs_type!(CORE, RW, u32, ScalingMaxFreq, "cpufreq/scaling_max_freq");
+sys_fs_type!(CORE, RO, u32, HardwareMinFreq, "cpufreq/cpuinfo_min_freq");
+sys_fs_type!(CORE, RO, u32, HardwareMaxFreq, "cpufreq/cpuinfo_max_freq");
+sys_fs_type!(
+    CORE,
+    RO,
+    Vec<String>,
+    AvailableFrequencies,
+    "cpufreq/scaling_available_frequencies"
+);
+sys_fs_type!(
+    CORE,
+    RW,
+    String,
+    EnergyPerformancePreference,
+    "cpufreq/energy_performance_preference"
+);
+sys_fs_type!(
+    CORE,
+    RO,
+    Vec<String>,
+    AvailableEnergyPerformancePreferences,
+    "cpufreq/energy_performance_available_preferences"
+);
+
+fn not_available(name: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("`{name}` isn't available on this system"),
+    )
+}
+
+async fn read_optional<S>(file: &Option<S>) -> Result<S::Type, io::Error>
+where
+    S: SysFsType + SysFsRead,
+    S::Type: std::str::FromStr,
+    <S::Type as std::str::FromStr>::Err: ToString,
+{
+    let file = file.as_ref().ok_or_else(|| not_available(S::PATH))?;
+    sys_fs_read(file).await
+}
+
+async fn write_optional<S>(file: &Option<S>, value: &S::Type) -> Result<(), io::Error>
+where
+    S: SysFsType + SysFsWrite,
+    S::Type: ToString,
+{
+    let file = file.as_ref().ok_or_else(|| not_available(S::PATH))?;
+    sys_fs_write(file, value).await
+}
+
+async fn read_optional_words<S>(file: &Option<S>) -> Result<Vec<String>, io::Error>
+where
+    S: SysFsType<Type = Vec<String>> + SysFsRead,
+{
+    let file = file.as_ref().ok_or_else(|| not_available(S::PATH))?;
+    sys_fs_read_words(file).await
+}
+
+/// A type that manages all sysfs files related to
+/// CPU frequency scaling.
+pub struct CpuController {
     online: Online,
     possible: Possible,
     present: Present,
-    boost: Boost,
+    boost: Option<Boost>,
+    no_turbo: Option<NoTurbo>,
+    cores: Vec<LogicalCore>,
 }
 
-impl CpuDriver {
-    fn get_available_logical_cores(&mut self) -> Result<Vec<()>, io::Error> {
-        // Add "possible" and "present" logical cores
-        //let cores = Vec::new();
-        let possible_cores = sys_fs_read_separated(&mut self.possible)?;
-        let present_cores = sys_fs_read_separated(&mut self.present)?;
-        let mut core_index_to_add: Vec<u8> = Vec::new();
+impl CpuController {
+    pub async fn new() -> Result<Self, io::Error> {
+        Self::with_root(SYS_FS_ROOT).await
+    }
+
+    /// Use the sysfs tree mounted at `root` instead of `/sys`.
+    pub async fn with_root(root: impl AsRef<Path>) -> Result<Self, io::Error> {
+        let root = root.as_ref();
+        let mut controller = Self {
+            online: Online::new(root).await?,
+            possible: Possible::new(root).await?,
+            present: Present::new(root).await?,
+            boost: Boost::new(root).await.ok(),
+            no_turbo: NoTurbo::new(root).await.ok(),
+            cores: Vec::new(),
+        };
 
-        for possible_core_index in possible_cores {
-            if present_cores.contains(&possible_core_index) {
-                core_index_to_add.push(possible_core_index);
+        for index in controller.get_available_logical_cores().await? {
+            let core = LogicalCore::new(root, index).await;
+            // The first core usually can't be taken offline,
+            // so it's the only core without an `online` file.
+            if index == 0 || core.online.is_some() {
+                controller.cores.push(core);
             }
         }
 
-        core_index_to_add.sort_unstable();
+        Ok(controller)
+    }
+
+    /// Indexes of all logical cores that are possible and present.
+    async fn get_available_logical_cores(&self) -> Result<Vec<u32>, io::Error> {
+        let possible = sys_fs_read_cpu_list(&self.possible).await?;
+        let present = sys_fs_read_cpu_list(&self.present).await?;
+
+        let mut cores: Vec<u32> = possible
+            .into_iter()
+            .filter(|index| present.contains(index))
+            .collect();
+        cores.sort_unstable();
+        Ok(cores)
+    }
+
+    pub fn cores(&self) -> &[LogicalCore] {
+        &self.cores
+    }
+
+    pub fn cores_mut(&mut self) -> &mut [LogicalCore] {
+        &mut self.cores
+    }
+
+    /// Indexes of all logical cores that are currently online.
+    pub async fn get_online_cores(&self) -> Result<Vec<u32>, io::Error> {
+        sys_fs_read_cpu_list(&self.online).await
+    }
+
+    /// Returns `true` if turbo boost can be enabled or disabled.
+    pub fn has_boost(&self) -> bool {
+        self.boost.is_some() || self.no_turbo.is_some()
+    }
+
+    pub async fn get_boost(&self) -> Result<bool, io::Error> {
+        if let Some(boost) = &self.boost {
+            sys_fs_read(boost).await.map(|boost| boost.0)
+        } else {
+            read_optional(&self.no_turbo)
+                .await
+                .map(|no_turbo| !no_turbo.0)
+        }
+    }
+
+    pub async fn set_boost(&self, boost: bool) -> Result<(), io::Error> {
+        if let Some(file) = &self.boost {
+            sys_fs_write(file, &NumBool(boost)).await
+        } else {
+            write_optional(&self.no_turbo, &NumBool(!boost)).await
+        }
+    }
+}
+
+/// The cpufreq files of a logical core.
+///
+/// They are only available while the core is online.
+struct CpuFreq {
+    scaling_driver: Option<ScalingDriver>,
+    governor: Option<ScalingGovernor>,
+    available_governors: Option<AvailableGovernors>,
+    min_freq: Option<ScalingMinFreq>,
+    max_freq: Option<ScalingMaxFreq>,
+    hardware_min_freq: Option<HardwareMinFreq>,
+    hardware_max_freq: Option<HardwareMaxFreq>,
+    available_frequencies: Option<AvailableFrequencies>,
+    energy_performance_preference: Option<EnergyPerformancePreference>,
+    available_energy_performance_preferences: Option<AvailableEnergyPerformancePreferences>,
+}
+
+impl CpuFreq {
+    async fn new(path: &Path) -> Self {
+        Self {
+            scaling_driver: ScalingDriver::new(path).await.ok(),
+            governor: ScalingGovernor::new(path).await.ok(),
+            available_governors: AvailableGovernors::new(path).await.ok(),
+            min_freq: ScalingMinFreq::new(path).await.ok(),
+            max_freq: ScalingMaxFreq::new(path).await.ok(),
+            hardware_min_freq: HardwareMinFreq::new(path).await.ok(),
+            hardware_max_freq: HardwareMaxFreq::new(path).await.ok(),
+            available_frequencies: AvailableFrequencies::new(path).await.ok(),
+            energy_performance_preference: EnergyPerformancePreference::new(path).await.ok(),
+            available_energy_performance_preferences: AvailableEnergyPerformancePreferences::new(
+                path,
+            )
+            .await
+            .ok(),
+        }
+    }
+}
+
+/// A logical core, for example `cpu3`.
+///
+/// All frequencies are in kHz, like in sysfs.
+pub struct LogicalCore {
+    index: u32,
+    path: PathBuf,
+    online: Option<CoreOnline>,
+    cpufreq: CpuFreq,
+}
+
+impl LogicalCore {
+    async fn new(root: &Path, index: u32) -> Self {
+        let path = root.join(format!("devices/system/cpu/cpu{index}"));
+        Self {
+            index,
+            online: CoreOnline::new(&path).await.ok(),
+            cpufreq: CpuFreq::new(&path).await,
+            path,
+        }
+    }
+
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    pub async fn is_online(&self) -> Result<bool, io::Error> {
+        match &self.online {
+            Some(online) => sys_fs_read(online).await.map(|online| online.0),
+            // Cores that can't be taken offline are always online.
+            None => Ok(true),
+        }
+    }
+
+    pub async fn set_online(&mut self, online: bool) -> Result<(), io::Error> {
+        write_optional(&self.online, &NumBool(online)).await?;
+        // The cpufreq files are recreated when a core comes back online.
+        self.cpufreq = CpuFreq::new(&self.path).await;
+        Ok(())
+    }
+
+    /// Name of the frequency scaling driver, for example `intel_pstate`.
+    pub async fn get_scaling_driver(&self) -> Result<String, io::Error> {
+        read_optional(&self.cpufreq.scaling_driver).await
+    }
+
+    pub async fn get_governor(&self) -> Result<String, io::Error> {
+        read_optional(&self.cpufreq.governor).await
+    }
+
+    pub async fn set_governor(&self, governor: &str) -> Result<(), io::Error> {
+        write_optional(&self.cpufreq.governor, &governor.to_owned()).await
+    }
+
+    pub async fn get_available_governors(&self) -> Result<Vec<String>, io::Error> {
+        read_optional_words(&self.cpufreq.available_governors).await
+    }
+
+    pub async fn get_min_freq(&self) -> Result<u32, io::Error> {
+        read_optional(&self.cpufreq.min_freq).await
+    }
+
+    pub async fn set_min_freq(&self, freq: u32) -> Result<(), io::Error> {
+        write_optional(&self.cpufreq.min_freq, &freq).await
+    }
+
+    pub async fn get_max_freq(&self) -> Result<u32, io::Error> {
+        read_optional(&self.cpufreq.max_freq).await
+    }
+
+    pub async fn set_max_freq(&self, freq: u32) -> Result<(), io::Error> {
+        write_optional(&self.cpufreq.max_freq, &freq).await
+    }
+
+    /// The lowest frequency supported by the hardware.
+    pub async fn get_hardware_min_freq(&self) -> Result<u32, io::Error> {
+        read_optional(&self.cpufreq.hardware_min_freq).await
+    }
+
+    /// The highest frequency supported by the hardware.
+    pub async fn get_hardware_max_freq(&self) -> Result<u32, io::Error> {
+        read_optional(&self.cpufreq.hardware_max_freq).await
+    }
+
+    /// Discrete frequencies, only reported by some drivers such as `acpi-cpufreq`.
+    pub async fn get_available_frequencies(&self) -> Result<Vec<u32>, io::Error> {
+        read_optional_words(&self.cpufreq.available_frequencies)
+            .await?
+            .iter()
+            .map(|freq| {
+                freq.parse()
+                    .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+            })
+            .collect()
+    }
+
+    pub async fn get_energy_performance_preference(&self) -> Result<String, io::Error> {
+        read_optional(&self.cpufreq.energy_performance_preference).await
+    }
+
+    pub async fn set_energy_performance_preference(
+        &self,
+        preference: &str,
+    ) -> Result<(), io::Error> {
+        write_optional(
+            &self.cpufreq.energy_performance_preference,
+            &preference.to_owned(),
+        )
+        .await
+    }
+
+    pub async fn get_available_energy_performance_preferences(
+        &self,
+    ) -> Result<Vec<String>, io::Error> {
+        read_optional_words(&self.cpufreq.available_energy_performance_preferences).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use super::CpuController;
+
+    fn write(root: &Path, path: &str, value: &str) {
+        let path = root.join("devices/system/cpu").join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, format!("{value}\n")).unwrap();
+    }
+
+    fn read(root: &Path, path: &str) -> String {
+        std::fs::read_to_string(root.join("devices/system/cpu").join(path)).unwrap()
+    }
+
+    #[test]
+    fn cpu_controller() {
+        let root = std::env::temp_dir().join(format!("tuxedo-sysfs-cpu-{}", std::process::id()));
+        std::fs::remove_dir_all(&root).ok();
+
+        write(&root, "possible", "0-3");
+        write(&root, "present", "0-2");
+        write(&root, "online", "0,2");
+        write(&root, "intel_pstate/no_turbo", "1");
+        for core in [0, 2] {
+            let cpufreq = format!("cpu{core}/cpufreq");
+            write(&root, &format!("{cpufreq}/scaling_governor"), "powersave");
+            write(
+                &root,
+                &format!("{cpufreq}/scaling_available_governors"),
+                "performance powersave",
+            );
+            write(&root, &format!("{cpufreq}/scaling_min_freq"), "800000");
+            write(&root, &format!("{cpufreq}/cpuinfo_max_freq"), "4200000");
+        }
+        write(&root, "cpu2/online", "1");
+        // Cores without an `online` file other than the first are skipped.
+        write(&root, "cpu1/cpufreq/scaling_governor", "powersave");
+
+        tokio_uring::start(async {
+            let controller = CpuController::with_root(&root).await.unwrap();
+            let indexes: Vec<u32> = controller.cores().iter().map(|core| core.index()).collect();
+            assert_eq!(indexes, [0, 2]);
+            assert_eq!(controller.get_online_cores().await.unwrap(), [0, 2]);
+
+            assert!(controller.has_boost());
+            assert!(!controller.get_boost().await.unwrap());
+            controller.set_boost(true).await.unwrap();
+            assert_eq!(read(&root, "intel_pstate/no_turbo"), "0\n");
+
+            let core = &controller.cores()[1];
+            assert!(core.is_online().await.unwrap());
+            assert_eq!(core.get_governor().await.unwrap(), "powersave");
+            assert_eq!(
+                core.get_available_governors().await.unwrap(),
+                ["performance", "powersave"]
+            );
+            assert_eq!(core.get_hardware_max_freq().await.unwrap(), 4200000);
+
+            core.set_governor("performance").await.unwrap();
+            assert_eq!(read(&root, "cpu2/cpufreq/scaling_governor"), "performance");
+            core.set_min_freq(1200000).await.unwrap();
+            assert_eq!(core.get_min_freq().await.unwrap(), 1200000);
+
+            // Missing files are reported as errors.
+            assert!(core.get_energy_performance_preference().await.is_err());
+            assert!(core.set_max_freq(3000000).await.is_err());
+        });
 
-        //    core_index_to_add.into_iter().map(|core_index| {
-        //        let newCore = LogicalCpuController(this.basePath, core_index);
-        //        if (core_index == 0 || newCore.online.isAvailable()) {
-        //            this.cores.push(newCore);
-        //        }
-        //    })
-        todo!();
+        std::fs::remove_dir_all(&root).unwrap();
     }
 }
@@ -1,8 +1,8 @@
-use std::{fmt::Display, io, str::FromStr};
+use std::{fmt::Display, io, path::Path, str::FromStr};
 
-use tailor_api::Color;
+use tailor_api::{Color, KeyboardEffect, KeyboardZone};
 
-use crate::{sys_fs_read, sys_fs_write};
+use crate::{sys_fs_read, sys_fs_write, NumBool, SYS_FS_ROOT};
 
 use super::sys_fs_type;
 
@@ -18,6 +18,48 @@ pub enum KeyboardState {
     Wave,
 }
 
+impl KeyboardState {
+    /// All states except [`KeyboardState::Custom`], which are
+    /// effects that are animated by the keyboard firmware.
+    pub const EFFECTS: [Self; 7] = [
+        Self::Breathe,
+        Self::Cycle,
+        Self::Dance,
+        Self::Flash,
+        Self::RandomColor,
+        Self::Tempo,
+        Self::Wave,
+    ];
+
+    /// A human readable name, unlike the numeric sysfs value.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Custom => "custom",
+            Self::Breathe => "breathe",
+            Self::Cycle => "cycle",
+            Self::Dance => "dance",
+            Self::Flash => "flash",
+            Self::RandomColor => "random_color",
+            Self::Tempo => "tempo",
+            Self::Wave => "wave",
+        }
+    }
+}
+
+impl From<KeyboardEffect> for KeyboardState {
+    fn from(effect: KeyboardEffect) -> Self {
+        match effect {
+            KeyboardEffect::Breathe => Self::Breathe,
+            KeyboardEffect::Cycle => Self::Cycle,
+            KeyboardEffect::Dance => Self::Dance,
+            KeyboardEffect::Flash => Self::Flash,
+            KeyboardEffect::RandomColor => Self::RandomColor,
+            KeyboardEffect::Tempo => Self::Tempo,
+            KeyboardEffect::Wave => Self::Wave,
+        }
+    }
+}
+
 impl Display for KeyboardState {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -58,29 +100,6 @@ impl FromStr for KeyboardState {
     }
 }
 
-struct NumBool(bool);
-
-impl Display for NumBool {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", u8::from(self.0))
-    }
-}
-
-impl FromStr for NumBool {
-    type Err = io::Error;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "0" => Ok(Self(false)),
-            "1" => Ok(Self(true)),
-            _ => Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                format!("Expected value in the range 0-1, found {s}"),
-            )),
-        }
-    }
-}
-
 sys_fs_type!(KB, RW, Color, ColorLeft, "color_left");
 sys_fs_type!(KB, RW, Color, ColorCenter, "color_center");
 sys_fs_type!(KB, RW, Color, ColorRight, "color_right");
@@ -103,17 +122,48 @@ pub struct KeyboardController {
 
 impl KeyboardController {
     pub async fn new() -> Result<Self, io::Error> {
+        Self::with_root(SYS_FS_ROOT).await
+    }
+
+    /// Use the sysfs tree mounted at `root` instead of `/sys`.
+    pub async fn with_root(root: impl AsRef<Path>) -> Result<Self, io::Error> {
+        let root = root.as_ref();
         Ok(Self {
-            color_left: ColorLeft::new().await.ok(),
-            color_right: ColorRight::new().await.ok(),
-            color_center: ColorCenter::new().await.ok(),
-            color_extra: ColorExtra::new().await.ok(),
-            brightness: Brightness::new().await.ok(),
-            mode: Mode::new().await.ok(),
-            state: State::new().await.ok(),
+            color_left: ColorLeft::new(root).await.ok(),
+            color_right: ColorRight::new(root).await.ok(),
+            color_center: ColorCenter::new(root).await.ok(),
+            color_extra: ColorExtra::new(root).await.ok(),
+            brightness: Brightness::new(root).await.ok(),
+            mode: Mode::new(root).await.ok(),
+            state: State::new(root).await.ok(),
         })
     }
 
+    /// Returns all color zones that are present.
+    pub fn zones(&self) -> Vec<KeyboardZone> {
+        let present = [
+            self.color_left.is_some(),
+            self.color_center.is_some(),
+            self.color_right.is_some(),
+            self.color_extra.is_some(),
+        ];
+        KeyboardZone::ALL
+            .into_iter()
+            .zip(present)
+            .filter_map(|(zone, present)| present.then_some(zone))
+            .collect()
+    }
+
+    /// Returns the effects of the keyboard firmware
+    /// which are empty if the state can't be set.
+    pub fn effects(&self) -> Vec<KeyboardState> {
+        if self.state.is_some() {
+            KeyboardState::EFFECTS.to_vec()
+        } else {
+            Vec::new()
+        }
+    }
+
     pub async fn set_color_left(&self, color: &Color) -> Result<(), io::Error> {
         if let Some(color_left) = &self.color_left {
             Ok(sys_fs_write(color_left, color).await?)
@@ -1,13 +1,18 @@
-use std::{io, str::FromStr};
+use std::{fmt::Display, io, str::FromStr};
 
-//mod cpu;
+pub mod cpu;
 pub mod keyboard;
 mod macros;
 
 pub(crate) use tokio_uring::fs;
 
+/// The location where sysfs is usually mounted.
+pub const SYS_FS_ROOT: &str = "/sys";
+
 trait SysFsType: Sized {
     type Type;
+    /// Path relative to the sysfs root
+    /// or the directory of a CPU core.
     const PATH: &'static str;
     fn get_file(&self) -> &tokio_uring::fs::File;
 }
@@ -15,13 +20,41 @@ trait SysFsType: Sized {
 trait SysFsRead: SysFsType {}
 trait SysFsWrite: SysFsType {}
 
+/// Sysfs attributes are never larger than a page.
+const SYS_FS_PAGE_SIZE: usize = 4096;
+
+/// A boolean that is stored as `0` or `1`.
+struct NumBool(bool);
+
+impl Display for NumBool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", u8::from(self.0))
+    }
+}
+
+impl FromStr for NumBool {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "0" => Ok(Self(false)),
+            "1" => Ok(Self(true)),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Expected value in the range 0-1, found {s}"),
+            )),
+        }
+    }
+}
+
 async fn sys_fs_read_string<S>(ty: &S) -> Result<String, io::Error>
 where
     S: SysFsType + SysFsRead,
 {
     let file = ty.get_file();
 
-    let buffer = Vec::new();
+    // The read size is determined by the capacity of the buffer.
+    let buffer = Vec::with_capacity(SYS_FS_PAGE_SIZE);
     let (res, buffer) = file.read_at(buffer, 0).await;
     res?;
     String::from_utf8(buffer).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
@@ -43,28 +76,43 @@ where
         })
 }
 
-async fn sys_fs_read_separated<S>(ty: &S) -> Result<Vec<u8>, io::Error>
+/// Read a list of values separated by whitespace,
+/// for example `performance powersave`.
+async fn sys_fs_read_words<S>(ty: &S) -> Result<Vec<String>, io::Error>
 where
-    S: SysFsType<Type = Vec<u8>> + SysFsRead,
+    S: SysFsType<Type = Vec<String>> + SysFsRead,
 {
     let string = sys_fs_read_string(ty).await?;
+    Ok(string.split_whitespace().map(str::to_owned).collect())
+}
 
-    let mut return_indexes = Vec::new();
-    for value in string.trim().split(',') {
-        let mut values = value.split('-');
-        let first: u8 = values.next().unwrap().parse().unwrap();
+/// Read a list of CPU indexes, for example `0-3,6,8-9`.
+async fn sys_fs_read_cpu_list<S>(ty: &S) -> Result<Vec<u32>, io::Error>
+where
+    S: SysFsType<Type = Vec<u32>> + SysFsRead,
+{
+    let string = sys_fs_read_string(ty).await?;
+    parse_cpu_list(&string)
+}
 
-        if let Some(second_value) = values.next() {
-            assert_eq!(values.next(), None);
-            let second: u8 = second_value.parse().unwrap();
-            for value in first..=second {
-                return_indexes.push(value);
-            }
-        } else {
-            return_indexes.push(first);
+fn parse_cpu_list(list: &str) -> Result<Vec<u32>, io::Error> {
+    let invalid = || {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Invalid CPU list: `{}`", list.trim()),
+        )
+    };
+    let parse = |value: &str| value.parse::<u32>().map_err(|_| invalid());
+
+    let mut indexes = Vec::new();
+    // Empty lists are valid, for example if no CPU is offline.
+    for range in list.trim().split(',').filter(|range| !range.is_empty()) {
+        match range.split_once('-') {
+            Some((first, last)) => indexes.extend(parse(first)?..=parse(last)?),
+            None => indexes.push(parse(range)?),
         }
     }
-    Ok(return_indexes)
+    Ok(indexes)
 }
 
 async fn sys_fs_write<S>(ty: &S, value: &S::Type) -> Result<(), io::Error>
@@ -83,6 +131,19 @@ where
 mod test {
     use std::time::Duration;
 
+    use super::parse_cpu_list;
+
+    #[test]
+    fn cpu_list() {
+        assert_eq!(
+            parse_cpu_list("0-3,6,8-9\n").unwrap(),
+            [0, 1, 2, 3, 6, 8, 9]
+        );
+        assert_eq!(parse_cpu_list("0\n").unwrap(), [0]);
+        assert!(parse_cpu_list("\n").unwrap().is_empty());
+        assert!(parse_cpu_list("0-x").is_err());
+    }
+
     #[test]
     fn test_io_uring() {
         sudo::escalate_if_needed().unwrap();
@@ -2,12 +2,12 @@
 macro_rules! sys_fs_impls {
     (RW, $name:ident) => {
         impl $name {
-            async fn new() -> Result<Self, ::std::io::Error> {
+            async fn new(root: &::std::path::Path) -> Result<Self, ::std::io::Error> {
                 Ok(Self {
                     file: $crate::fs::OpenOptions::new()
                         .read(true)
                         .write(true)
-                        .open(<Self as $crate::SysFsType>::PATH)
+                        .open(root.join(<Self as $crate::SysFsType>::PATH))
                         .await?,
                 })
             }
@@ -18,12 +18,12 @@ macro_rules! sys_fs_impls {
     };
     (RO, $name:ident) => {
         impl $name {
-            async fn new() -> Result<Self, ::std::io::Error> {
+            async fn new(root: &::std::path::Path) -> Result<Self, ::std::io::Error> {
                 Ok(Self {
                     file: $crate::fs::OpenOptions::new()
                         .read(true)
                         .write(false)
-                        .open(<Self as $crate::SysFsType>::PATH)
+                        .open(root.join(<Self as $crate::SysFsType>::PATH))
                         .await?,
                 })
             }
@@ -33,12 +33,12 @@ macro_rules! sys_fs_impls {
     };
     (WO, $name:ident) => {
         impl $name {
-            async fn new() -> Result<Self, ::std::io::Error> {
+            async fn new(root: &::std::path::Path) -> Result<Self, ::std::io::Error> {
                 Ok(Self {
                     file: $crate::fs::OpenOptions::new()
                         .read(false)
                         .write(true)
-                        .open(<Self as $crate::SysFsType>::PATH)
+                        .open(root.join(<Self as $crate::SysFsType>::PATH))
                         .await?,
                 })
             }
@@ -67,17 +67,15 @@ macro_rules! sys_fs_type {
         $crate::sys_fs_impls!($permission, $name);
     };
     (CPU, $permission:ident, $ty:ty, $name:ident, $subpath:literal) => {
-        sys_fs_type!(
-            "/sys/devices/system/cpu/",
-            $permission,
-            $ty,
-            $name,
-            $subpath
-        );
+        sys_fs_type!("devices/system/cpu/", $permission, $ty, $name, $subpath);
+    };
+    // Relative to the directory of a CPU core, for example `devices/system/cpu/cpu0/`.
+    (CORE, $permission:ident, $ty:ty, $name:ident, $subpath:literal) => {
+        sys_fs_type!("", $permission, $ty, $name, $subpath);
     };
     (KB, $permission:ident, $ty:ty, $name:ident, $subpath:literal) => {
         sys_fs_type!(
-            "/sys/devices/platform/tuxedo_keyboard/",
+            "devices/platform/tuxedo_keyboard/",
             $permission,
             $ty,
             $name,